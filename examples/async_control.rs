@@ -0,0 +1,61 @@
+//! 异步控制示例
+//! 使用 tokio::io::duplex 连接虚拟舵机，真实串口可换成 tokio-serial 的 SerialStream
+//!
+//! 运行: cargo run --example async_control --features async
+
+use ftservo_sdk::{
+    sim::{ServoChain, SimFamily, SimServo},
+    AsyncSmsSts, FtServoError, Result,
+};
+use std::time::Duration;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    println!("=== FTServo SDK 异步控制示例 ===");
+
+    // 创建虚拟舵机链，在字节流的另一端运行
+    let (client, server) = tokio::io::duplex(256);
+    let mut chain = ServoChain::new();
+    chain.add_servo(SimServo::new(1, SimFamily::SmsSts));
+    let simulator = tokio::spawn(async move { chain.serve_async(server).await });
+
+    let mut sms_sts = AsyncSmsSts::new(client);
+    let servo_id = 1;
+
+    // 使能扭矩并写入目标位置
+    sms_sts.write_torque_enable(servo_id, true).await?;
+    sms_sts.write_pos_ex(servo_id, 3000, 0, 2400).await?;
+
+    // 等待运动完成
+    for _ in 0..20 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let (pos, speed) = sms_sts.read_pos_speed(servo_id).await?;
+        println!("[ID:{:03}] 位置: {}, 速度: {}", servo_id, pos, speed);
+        if !sms_sts.read_moving(servo_id).await? {
+            break;
+        }
+    }
+
+    // 不存在的舵机返回超时错误
+    match sms_sts.read_pos(9).await {
+        Ok(pos) => println!("[ID:009] 位置: {}", pos),
+        Err(FtServoError::Timeout { id, .. }) => println!("[ID:{:03}] 无应答", id),
+        Err(e) => println!("读取失败: {}", e),
+    }
+
+    // 指令发出后取消读取，下一次通信不会收到被取消指令的应答
+    let cancelled = tokio::select! {
+        biased;
+        voltage = sms_sts.read_voltage(servo_id) => voltage.is_err(),
+        _ = std::future::ready(()) => true,
+    };
+    println!("被取消的读取: {}", if cancelled { "已取消" } else { "已完成" });
+    println!("[ID:{:03}] 温度: {}°C", servo_id, sms_sts.read_temperature(servo_id).await?);
+
+    // 关闭字节流，模拟器随之退出
+    drop(sms_sts);
+    simulator.await.map_err(|e| FtServoError::InvalidParameter(e.to_string()))??;
+
+    println!("=== 异步控制示例完成 ===");
+    Ok(())
+}
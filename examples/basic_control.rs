@@ -2,8 +2,8 @@
 //! 对应Python SDK中的基本控制功能
 
 use ftservo_sdk::{
-    create_port_handler, create_sms_sts, create_scscl,
-    COMM, Result
+    create_port_handler, create_sms_sts,
+    Result
 };
 use std::time::Duration;
 use std::thread;
@@ -32,11 +32,11 @@ fn main() -> Result<()> {
     
     // 1. 测试连接 (对应Python: sms_sts.ping(servo_id))
     println!("\n--- 测试舵机连接 ---");
-    let ping_result = sms_sts.ping(servo_id);
+    let ping_result = sms_sts.ping_with_model(servo_id);
     match ping_result {
-        COMM::Success => println!("[ID:{}] 连接成功 ✓", servo_id),
-        _ => {
-            println!("[ID:{}] 连接失败: {:?}", servo_id, ping_result);
+        Ok(info) => println!("[ID:{}] 连接成功 ✓ 型号: {:?}", servo_id, info.model),
+        Err(e) => {
+            println!("[ID:{}] 连接失败: {}", servo_id, e);
             return Ok(());
         }
     }
@@ -45,8 +45,8 @@ fn main() -> Result<()> {
     println!("\n--- 使能扭矩 ---");
     let result = sms_sts.write_torque_enable(servo_id, true);
     match result {
-        COMM::Success => println!("[ID:{}] 扭矩使能成功 ✓", servo_id),
-        _ => println!("[ID:{}] 扭矩使能失败: {:?}", servo_id, result),
+        Ok(status) => println!("[ID:{}] 扭矩使能成功 ✓ (状态: {})", servo_id, status),
+        Err(e) => println!("[ID:{}] 扭矩使能失败: {}", servo_id, e),
     }
     
     // 3. 写入位置 (对应Python: sms_sts.WritePosEx(servo_id, position, time, speed))
@@ -58,8 +58,8 @@ fn main() -> Result<()> {
     println!("控制舵机移动到位置: {}", target_position);
     let result = sms_sts.write_pos_ex(servo_id, target_position, move_time, move_speed);
     match result {
-        COMM::Success => println!("[ID:{}] 位置控制指令发送成功 ✓", servo_id),
-        _ => println!("[ID:{}] 位置控制失败: {:?}", servo_id, result),
+        Ok(()) => println!("[ID:{}] 位置控制指令发送成功 ✓", servo_id),
+        Err(e) => println!("[ID:{}] 位置控制失败: {}", servo_id, e),
     }
     
     // 等待运动完成
@@ -101,8 +101,8 @@ fn main() -> Result<()> {
     println!("\n--- 禁用扭矩 ---");
     let result = sms_sts.write_torque_enable(servo_id, false);
     match result {
-        COMM::Success => println!("[ID:{}] 扭矩禁用成功 ✓", servo_id),
-        _ => println!("[ID:{}] 扭矩禁用失败: {:?}", servo_id, result),
+        Ok(status) => println!("[ID:{}] 扭矩禁用成功 ✓ (状态: {})", servo_id, status),
+        Err(e) => println!("[ID:{}] 扭矩禁用失败: {}", servo_id, e),
     }
     
     println!("\n=== 基本控制示例完成 ===");
@@ -0,0 +1,68 @@
+//! 读取舵机状态示例
+//! 对应Python SDK中的read示例
+
+use ftservo_sdk::{
+    create_port_handler, create_sms_sts,
+    Result
+};
+
+fn main() -> Result<()> {
+    println!("=== FTServo SDK 读取状态示例 ===");
+
+    // 配置串口参数
+    let port_name = "/dev/ttyUSB0";
+    let baudrate = 1000000;
+
+    // 创建端口处理器
+    let mut port_handler = create_port_handler(port_name);
+    port_handler.set_baudrate(baudrate)?;
+    port_handler.open_port()?;
+    println!("串口打开成功!");
+
+    // 创建SMS/STS舵机控制器
+    let mut sms_sts = create_sms_sts(port_handler);
+
+    let servo_id = 1;
+
+    // 测试连接
+    let ping_result = sms_sts.ping(servo_id);
+    if let Err(e) = ping_result {
+        println!("[ID:{}] 舵机连接失败: {}", servo_id, e);
+        return Ok(());
+    }
+
+    // 读取位置和速度 (对应Python: sms_sts.ReadPosSpeed(servo_id))
+    match sms_sts.read_pos_speed(servo_id) {
+        Ok((pos, speed)) => println!("[ID:{}] 位置: {} 速度: {}", servo_id, pos, speed),
+        Err(e) => println!("[ID:{}] 读取位置速度失败: {:?}", servo_id, e),
+    }
+
+    // 读取负载和电流
+    match sms_sts.read_load(servo_id) {
+        Ok(load) => println!("[ID:{}] 负载: {}", servo_id, load),
+        Err(e) => println!("[ID:{}] 读取负载失败: {:?}", servo_id, e),
+    }
+    match sms_sts.read_current(servo_id) {
+        Ok(current) => println!("[ID:{}] 电流: {}", servo_id, current),
+        Err(e) => println!("[ID:{}] 读取电流失败: {:?}", servo_id, e),
+    }
+
+    // 读取电压和温度
+    match sms_sts.read_voltage(servo_id) {
+        Ok(voltage) => println!("[ID:{}] 电压: {:.1}V", servo_id, voltage as f32 / 10.0),
+        Err(e) => println!("[ID:{}] 读取电压失败: {:?}", servo_id, e),
+    }
+    match sms_sts.read_temperature(servo_id) {
+        Ok(temp) => println!("[ID:{}] 温度: {}°C", servo_id, temp),
+        Err(e) => println!("[ID:{}] 读取温度失败: {:?}", servo_id, e),
+    }
+
+    // 读取运动状态
+    match sms_sts.read_moving(servo_id) {
+        Ok(moving) => println!("[ID:{}] 运动状态: {}", servo_id, if moving { "运动中" } else { "静止" }),
+        Err(e) => println!("[ID:{}] 读取运动状态失败: {:?}", servo_id, e),
+    }
+
+    println!("\n=== 读取状态示例完成 ===");
+    Ok(())
+}
@@ -3,7 +3,7 @@
 
 use ftservo_sdk::{
     create_port_handler, create_scscl,
-    COMM, Result
+    Result
 };
 use std::time::Duration;
 use std::thread;
@@ -28,11 +28,11 @@ fn main() -> Result<()> {
     
     // 1. 测试连接 (对应Python: scscl.ping(servo_id))
     println!("\n--- 测试SCSCL舵机连接 ---");
-    let ping_result = scscl.ping(servo_id);
+    let ping_result = scscl.ping_with_model(servo_id);
     match ping_result {
-        COMM::Success => println!("[ID:{}] SCSCL舵机连接成功 ✓", servo_id),
-        _ => {
-            println!("[ID:{}] SCSCL舵机连接失败: {:?}", servo_id, ping_result);
+        Ok(info) => println!("[ID:{}] SCSCL舵机连接成功 ✓ 型号: {:?}", servo_id, info.model),
+        Err(e) => {
+            println!("[ID:{}] SCSCL舵机连接失败: {}", servo_id, e);
             return Ok(());
         }
     }
@@ -41,8 +41,8 @@ fn main() -> Result<()> {
     println!("\n--- 使能扭矩 ---");
     let result = scscl.write_torque_enable(servo_id, true);
     match result {
-        COMM::Success => println!("[ID:{}] 扭矩使能成功 ✓", servo_id),
-        _ => println!("[ID:{}] 扭矩使能失败: {:?}", servo_id, result),
+        Ok(status) => println!("[ID:{}] 扭矩使能成功 ✓ (状态: {})", servo_id, status),
+        Err(e) => println!("[ID:{}] 扭矩使能失败: {}", servo_id, e),
     }
     
     // 3. 位置控制 (对应Python: scscl.WritePos(servo_id, position, time, speed))
@@ -54,8 +54,8 @@ fn main() -> Result<()> {
     println!("控制SCSCL舵机移动到位置: {}", target_position);
     let result = scscl.write_pos(servo_id, target_position, move_time, move_speed);
     match result {
-        COMM::Success => println!("[ID:{}] 位置控制指令发送成功 ✓", servo_id),
-        _ => println!("[ID:{}] 位置控制失败: {:?}", servo_id, result),
+        Ok(()) => println!("[ID:{}] 位置控制指令发送成功 ✓", servo_id),
+        Err(e) => println!("[ID:{}] 位置控制失败: {}", servo_id, e),
     }
     
     thread::sleep(Duration::from_millis(1500));
@@ -108,8 +108,8 @@ fn main() -> Result<()> {
     
     let result = scscl.sync_write_pos(servo_ids, positions, times, speeds);
     match result {
-        COMM::Success => println!("SCSCL同步控制指令发送成功 ✓"),
-        _ => println!("SCSCL同步控制失败: {:?}", result),
+        Ok(()) => println!("SCSCL同步控制指令发送成功 ✓"),
+        Err(e) => println!("SCSCL同步控制失败: {}", e),
     }
     
     thread::sleep(Duration::from_millis(1500));
@@ -118,8 +118,8 @@ fn main() -> Result<()> {
     println!("\n--- 禁用扭矩 ---");
     let result = scscl.write_torque_enable(servo_id, false);
     match result {
-        COMM::Success => println!("[ID:{}] 扭矩禁用成功 ✓", servo_id),
-        _ => println!("[ID:{}] 扭矩禁用失败: {:?}", servo_id, result),
+        Ok(status) => println!("[ID:{}] 扭矩禁用成功 ✓ (状态: {})", servo_id, status),
+        Err(e) => println!("[ID:{}] 扭矩禁用失败: {}", servo_id, e),
     }
     
     println!("\n=== SCSCL控制示例完成 ===");
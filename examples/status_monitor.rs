@@ -3,7 +3,7 @@
 
 use ftservo_sdk::{
     create_port_handler, create_sms_sts,
-    COMM, Result
+    Result
 };
 use std::time::Duration;
 use std::thread;
@@ -29,19 +29,19 @@ fn main() -> Result<()> {
     // 测试连接
     let ping_result = sms_sts.ping(servo_id);
     match ping_result {
-        COMM::Success => println!("[ID:{}] 舵机连接成功 ✓", servo_id),
-        _ => {
-            println!("[ID:{}] 舵机连接失败: {:?}", servo_id, ping_result);
+        Ok(info) => println!("[ID:{}] 舵机连接成功 ✓ (状态: {})", servo_id, info.status),
+        Err(e) => {
+            println!("[ID:{}] 舵机连接失败: {}", servo_id, e);
             return Ok(());
         }
     }
     
     // 使能扭矩
-    sms_sts.write_torque_enable(servo_id, true);
+    sms_sts.write_torque_enable(servo_id, true)?;
     
     // 开始监控循环
     println!("\n开始状态监控 (按Ctrl+C退出)...");
-    println!("时间\t\t位置\t速度\t负载\t电压\t温度\t运动状态");
+    println!("时间\t\t位置\t速度\t负载\t电压\t温度\t运动状态\t舵机状态");
     println!("{}","-".repeat(70));
     
     for i in 0..30 {  // 监控30次
@@ -57,22 +57,25 @@ fn main() -> Result<()> {
         let voltage = sms_sts.read_voltage(servo_id).unwrap_or(0);
         let temperature = sms_sts.read_temperature(servo_id).unwrap_or(0);
         let is_moving = sms_sts.read_moving(servo_id).unwrap_or(false);
+        // 最近一次应答中的故障标志，故障时读取仍会返回数据
+        let status = sms_sts.last_status();
         
         println!(
-            "{}\t{}\t{}\t{}\t{:.1}V\t{}°C\t{}",
+            "{}\t{}\t{}\t{}\t{:.1}V\t{}°C\t{}\t{}",
             timestamp,
             position,
             speed,
             load,
             voltage as f32 / 10.0,
             temperature,
-            if is_moving { "运动中" } else { "静止" }
+            if is_moving { "运动中" } else { "静止" },
+            status
         );
         
         // 每5次发送一个新的位置指令
         if i % 5 == 0 {
             let target_pos = if (i / 5) % 2 == 0 { 1024 } else { 3072 };
-            sms_sts.write_pos_ex(servo_id, target_pos, 2000, 1800);
+            sms_sts.write_pos_ex(servo_id, target_pos, 2000, 1800)?;
             println!(">>> 发送新位置指令: {}", target_pos);
         }
         
@@ -80,7 +83,7 @@ fn main() -> Result<()> {
     }
     
     // 禁用扭矩
-    sms_sts.write_torque_enable(servo_id, false);
+    sms_sts.write_torque_enable(servo_id, false)?;
     println!("\n=== 状态监控示例完成 ===");
     Ok(())
 }
\ No newline at end of file
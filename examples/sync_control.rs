@@ -3,7 +3,7 @@
 
 use ftservo_sdk::{
     create_port_handler, create_sms_sts,
-    COMM, Result
+    Result
 };
 use std::time::Duration;
 use std::thread;
@@ -32,8 +32,8 @@ fn main() -> Result<()> {
     for &id in &servo_ids {
         let ping_result = sms_sts.ping(id);
         match ping_result {
-            COMM::Success => println!("[ID:{}] 连接成功 ✓", id),
-            _ => println!("[ID:{}] 连接失败: {:?}", id, ping_result),
+            Ok(info) => println!("[ID:{}] 连接成功 ✓ (状态: {})", id, info.status),
+            Err(e) => println!("[ID:{}] 连接失败: {}", id, e),
         }
     }
     
@@ -42,8 +42,8 @@ fn main() -> Result<()> {
     for &id in &servo_ids {
         let result = sms_sts.write_torque_enable(id, true);
         match result {
-            COMM::Success => println!("[ID:{}] 扭矩使能成功 ✓", id),
-            _ => println!("[ID:{}] 扭矩使能失败: {:?}", id, result),
+            Ok(status) => println!("[ID:{}] 扭矩使能成功 ✓ (状态: {})", id, status),
+            Err(e) => println!("[ID:{}] 扭矩使能失败: {}", id, e),
         }
     }
     
@@ -62,8 +62,8 @@ fn main() -> Result<()> {
         speeds1.clone()
     );
     match result {
-        COMM::Success => println!("同步控制指令发送成功 ✓"),
-        _ => println!("同步控制失败: {:?}", result),
+        Ok(()) => println!("同步控制指令发送成功 ✓"),
+        Err(e) => println!("同步控制失败: {}", e),
     }
     
     // 等待动作完成
@@ -92,8 +92,8 @@ fn main() -> Result<()> {
         speeds2
     );
     match result {
-        COMM::Success => println!("同步控制指令发送成功 ✓"),
-        _ => println!("同步控制失败: {:?}", result),
+        Ok(()) => println!("同步控制指令发送成功 ✓"),
+        Err(e) => println!("同步控制失败: {}", e),
     }
     
     thread::sleep(Duration::from_millis(2000));
@@ -111,8 +111,8 @@ fn main() -> Result<()> {
         home_speeds
     );
     match result {
-        COMM::Success => println!("回到初始位置指令发送成功 ✓"),
-        _ => println!("回到初始位置失败: {:?}", result),
+        Ok(()) => println!("回到初始位置指令发送成功 ✓"),
+        Err(e) => println!("回到初始位置失败: {}", e),
     }
     
     thread::sleep(Duration::from_millis(2500));
@@ -122,8 +122,8 @@ fn main() -> Result<()> {
     for &id in &servo_ids {
         let result = sms_sts.write_torque_enable(id, false);
         match result {
-            COMM::Success => println!("[ID:{}] 扭矩禁用成功 ✓", id),
-            _ => println!("[ID:{}] 扭矩禁用失败: {:?}", id, result),
+            Ok(status) => println!("[ID:{}] 扭矩禁用成功 ✓ (状态: {})", id, status),
+            Err(e) => println!("[ID:{}] 扭矩禁用失败: {}", id, e),
         }
     }
     
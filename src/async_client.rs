@@ -0,0 +1,1201 @@
+//! 基于 tokio 的异步客户端（需要启用 `async` feature）
+//!
+//! `AsyncSmsSts` 与 `AsyncScscl` 运行在任何实现了 `AsyncRead + AsyncWrite` 的字节流上，
+//! 例如 tokio-serial 的 `SerialStream`、网络串口桥接或测试用的 `tokio::io::duplex`。
+//! 指令编码、应答校验以及修改ID、备份配置等流程的检查与同步客户端共用，这里只有读写
+//! 字节和计时是异步的。接收超时返回 `FtServoError::Timeout`。收发过程中 future 被取消
+//! （外层 `select!`、`tokio::time::timeout` 等）时，下一次通信会先丢弃迟到的应答，不会把
+//! 上一条指令的状态包当作本次的结果。
+
+use std::{collections::HashMap, future, pin::Pin, task::Poll, time::Duration};
+
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf},
+    time::{self, Instant},
+};
+
+#[cfg(feature = "config")]
+use crate::config::{asynchronous as config, BusConfig, Difference, ServoConfig};
+use crate::{
+    exchange::{Exchange, Request},
+    model::{Family, ServoModel},
+    packet::{InstructionPacket, StatusPacket, RXPACKET_MAX_LEN},
+    protocol_packet_handler::{
+        check_sync_lengths, make_dword, sign_magnitude_to_host, signed_word, split_dword, Endian, PingInfo,
+        LATENCY_TIMER, MODEL_ADDRESS,
+    },
+    register::Register,
+    scscl::{self, pos_param, SCSCL_POS_LEN},
+    scservo_def::{ServoStatus, COMM, INST},
+    setup::asynchronous as setup,
+    sms_sts::{self, pid_param, pos_ex_param, voltage_limits_param, OperatingMode, SMS_STS_POS_EX_LEN},
+    Result,
+};
+
+// 默认波特率，用于计算接收超时
+const DEFAULT_BAUDRATE: u32 = 1_000_000;
+
+// 异步协议处理器，负责指令包的发送与状态包的接收
+#[derive(Debug)]
+pub struct AsyncPacketHandler<S> {
+    stream: S,
+    // 指令编码与应答校验，与同步的 ProtocolPacketHandler 共用
+    exchange: Exchange,
+    baudrate: u32,
+    // 未读取的应答的最晚到达时间
+    stale_deadline: Instant,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncPacketHandler<S> {
+    pub fn new(stream: S, scs_end: Endian) -> Self {
+        Self {
+            stream,
+            exchange: Exchange::new(scs_end),
+            baudrate: DEFAULT_BAUDRATE,
+            stale_deadline: Instant::now(),
+        }
+    }
+
+    // 字节流的波特率，只用于计算接收超时，不会修改串口设置
+    pub fn set_baudrate(&mut self, baudrate: u32) {
+        self.baudrate = baudrate;
+    }
+
+    pub fn get_baudrate(&self) -> u32 {
+        self.baudrate
+    }
+
+    pub fn scs_getend(&self) -> Endian {
+        self.exchange.scs_end.clone()
+    }
+
+    pub fn get_stream(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn get_stream_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    // 最近一次收到的状态包中的舵机状态
+    pub fn last_status(&self) -> ServoStatus {
+        self.exchange.last_status
+    }
+
+    // 单播的写入、寄存器写入和动作指令是否等待并校验状态包（默认开启）
+    // 关闭后只发送，应答在下一次通信前被丢弃
+    pub fn set_write_ack(&mut self, enabled: bool) {
+        self.exchange.write_ack = enabled;
+    }
+
+    pub fn write_ack(&self) -> bool {
+        self.exchange.write_ack
+    }
+
+    // 应答中的舵机状态带有故障标志时是否返回 Hardware 错误（默认关闭，读取仍返回数据，
+    // 状态通过写入的返回值或 last_status 取得），Ping 不受影响
+    pub fn set_strict_status(&mut self, enabled: bool) {
+        self.exchange.strict_status = enabled;
+    }
+
+    pub fn strict_status(&self) -> bool {
+        self.exchange.strict_status
+    }
+
+    // 按数据包长度计算接收超时
+    fn packet_timeout(&self, packet_length: usize) -> Duration {
+        let tx_time_per_byte = Duration::from_secs_f64(10.0 / self.baudrate.max(1) as f64);
+        tx_time_per_byte * (packet_length as u32 + 3) + Duration::from_millis(LATENCY_TIMER)
+    }
+
+    // 丢弃之前的指令（被取消的事务或不等待应答的写入）留在总线上的应答
+    // wait 为 true 时等待这些应答到达（最多到截止时间），否则只处理已经到达的数据
+    async fn discard_stale(&mut self, wait: bool) {
+        let mut chunk = [0u8; RXPACKET_MAX_LEN];
+        self.exchange.decoder.clear();
+
+        while self.exchange.has_stale() {
+            let bytes_read = if wait {
+                match time::timeout_at(self.stale_deadline, self.stream.read(&mut chunk)).await {
+                    Ok(Ok(bytes_read)) => bytes_read,
+                    _ => 0,
+                }
+            } else {
+                self.read_available(&mut chunk).await
+            };
+            if bytes_read == 0 {
+                break;
+            }
+            self.exchange.discard(&chunk[..bytes_read]);
+        }
+        if wait || Instant::now() >= self.stale_deadline {
+            self.exchange.forget_stale();
+        }
+
+        // 清除已经到达的残留数据
+        while self.read_available(&mut chunk).await > 0 {}
+        self.exchange.decoder.clear();
+    }
+
+    // 读取已经到达的数据，没有数据时立即返回 0
+    async fn read_available(&mut self, buf: &mut [u8]) -> usize {
+        let stream = &mut self.stream;
+        future::poll_fn(|cx| {
+            let mut read_buf = ReadBuf::new(buf);
+            match Pin::new(&mut *stream).poll_read(cx, &mut read_buf) {
+                Poll::Ready(Ok(())) => Poll::Ready(read_buf.filled().len()),
+                Poll::Ready(Err(_)) | Poll::Pending => Poll::Ready(0),
+            }
+        })
+        .await
+    }
+
+    // 编码并发送指令包，返回接收应答的截止时间；wait_stale 为 true 时先等待之前指令的应答
+    async fn send_frame(&mut self, id: u8, instruction: INST, parts: &[&[u8]], wait_stale: bool) -> std::result::Result<Instant, COMM> {
+        let total_packet_length = self.exchange.encode(id, instruction, parts)?;
+
+        self.discard_stale(wait_stale).await;
+
+        // 单播指令都会得到应答，在读取它之前记为待丢弃；发送被取消时，已发出的部分指令也可能得到应答
+        let deadline = Instant::now() + self.packet_timeout(total_packet_length);
+        if self.exchange.sent(id) {
+            self.stale_deadline = deadline;
+        }
+
+        self.stream
+            .write_all(self.exchange.frame(total_packet_length))
+            .await
+            .map_err(|_| COMM::TxFail)?;
+        self.stream.flush().await.map_err(|_| COMM::TxFail)?;
+        Ok(deadline)
+    }
+
+    // 发送指令包，不等待应答
+    pub async fn tx_packet(&mut self, packet: &InstructionPacket<'_>) -> COMM {
+        match self.send_frame(packet.id, packet.instruction, &[packet.params], false).await {
+            Ok(_) => COMM::Success,
+            Err(result) => result,
+        }
+    }
+
+    // 读取数据直到解码器中有完整数据包、超时或字节流结束
+    async fn wait_packet(&mut self, deadline: Instant) -> bool {
+        let mut chunk = [0u8; RXPACKET_MAX_LEN];
+
+        while !self.exchange.decoder.has_packet() {
+            match time::timeout_at(deadline, self.stream.read(&mut chunk)).await {
+                Ok(Ok(bytes_read)) if bytes_read > 0 => self.exchange.decoder.push(&chunk[..bytes_read]),
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    // 发送并接收应答
+    pub async fn tx_rx_packet(&mut self, packet: &InstructionPacket<'_>) -> std::result::Result<StatusPacket<'_>, COMM> {
+        self.tx_rx(packet.id, packet.instruction, &[packet.params]).await
+    }
+
+    async fn tx_rx(&mut self, id: u8, instruction: INST, parts: &[&[u8]]) -> std::result::Result<StatusPacket<'_>, COMM> {
+        let deadline = self.send_frame(id, instruction, parts, true).await?;
+        let received = self.wait_packet(deadline).await;
+        // 应答已收到或已超时，不再需要丢弃
+        self.exchange.forget_stale();
+
+        if !received {
+            return Err(self.exchange.rx_failure());
+        }
+        self.exchange.take_reply(id)
+    }
+
+    // 发送指令，不等待应答
+    async fn send(&mut self, request: &Request<'_>) -> Result<()> {
+        self.send_frame(request.id, request.instruction, &request.parts(), false)
+            .await
+            .map(|_| ())
+            .map_err(|result| request.error(result))
+    }
+
+    // 发送指令，需要应答时接收并校验状态包，状态字节记录在 last_status 中，
+    // 故障由调用者判断，开启 strict_status 时返回 Hardware
+    // 广播或关闭了写入应答时只发送，返回不带数据、状态为空的应答
+    async fn request(&mut self, request: &Request<'_>) -> Result<StatusPacket<'_>> {
+        if !self.exchange.expects_reply(request) {
+            self.send(request).await?;
+            return Ok(StatusPacket::new(request.id, 0, &[]));
+        }
+        let strict = self.exchange.strict_status;
+        let packet = self
+            .tx_rx(request.id, request.instruction, &request.parts())
+            .await
+            .map_err(|result| request.error(result))?;
+        request.check(packet, strict)
+    }
+
+    // 发送 Ping 并等待应答，舵机不存在时返回 Timeout
+    pub async fn ping(&mut self, scs_id: u32) -> Result<PingInfo> {
+        let packet = self.request(&Request::ping(scs_id)?).await?;
+        Ok(PingInfo { id: packet.id, status: packet.status(), model: None })
+    }
+
+    // Ping 后读取型号号（地址 3/4），舵机报告故障时也会读取
+    pub async fn ping_with_model(&mut self, scs_id: u32) -> Result<PingInfo> {
+        let mut info = self.ping(scs_id).await?;
+        info.model = Some(self.read_2byte_tx_rx(scs_id, MODEL_ADDRESS as u32).await?);
+        Ok(info)
+    }
+
+    pub async fn action(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        Ok(self.request(&Request::action(scs_id)).await?.status())
+    }
+
+    // 读取指定长度的数据，返回应答包
+    async fn read_tx_rx_packet(&mut self, scs_id: u32, address: u32, length: u32) -> Result<StatusPacket<'_>> {
+        self.request(&Request::read(scs_id, address, length)?).await
+    }
+
+    // 从任意地址读取连续的 length 个字节，一次收发完成
+    pub async fn read_tx_rx(&mut self, scs_id: u32, address: u32, length: u32) -> Result<Vec<u8>> {
+        let packet = self.read_tx_rx_packet(scs_id, address, length).await?;
+        Ok(packet.params.to_vec())
+    }
+
+    pub async fn read_1byte_tx_rx(&mut self, scs_id: u32, address: u32) -> Result<u8> {
+        let packet = self.read_tx_rx_packet(scs_id, address, 1).await?;
+        Ok(packet.params[0])
+    }
+
+    pub async fn read_2byte_tx_rx(&mut self, scs_id: u32, address: u32) -> Result<u16> {
+        let end = self.scs_getend();
+        let packet = self.read_tx_rx_packet(scs_id, address, 2).await?;
+        Ok(end.make_word(packet.params[0], packet.params[1]))
+    }
+
+    pub async fn read_4byte_tx_rx(&mut self, scs_id: u32, address: u32) -> Result<u32> {
+        let end = self.scs_getend();
+        let packet = self.read_tx_rx_packet(scs_id, address, 4).await?;
+        let data = packet.params;
+        Ok(make_dword(&end, [data[0], data[1], data[2], data[3]]))
+    }
+
+    // 按寄存器描述读取并解码
+    pub async fn read_reg(&mut self, scs_id: u32, reg: Register) -> Result<i32> {
+        let end = self.scs_getend();
+        let packet = self.read_tx_rx_packet(scs_id, reg.address as u32, reg.size as u32).await?;
+        Ok(reg.decode(&end, packet.params))
+    }
+
+    // 按寄存器描述编码并写入，只读寄存器或超出范围的值返回 InvalidParameter
+    pub async fn write_reg(&mut self, scs_id: u32, reg: Register, value: i32) -> Result<ServoStatus> {
+        let data = reg.encode(&self.exchange.scs_end, value)?;
+        self.write_tx_rx(scs_id, reg.address as u32, &data).await
+    }
+
+    // 从任意地址写入连续的数据，单播时等待状态包并返回舵机状态
+    pub async fn write_tx_rx(&mut self, scs_id: u32, address: u32, data: &[u8]) -> Result<ServoStatus> {
+        Ok(self.request(&Request::write(scs_id, INST::Write, address, data)?).await?.status())
+    }
+
+    pub async fn write_1byte_tx_rx(&mut self, scs_id: u32, address: u32, data: u8) -> Result<ServoStatus> {
+        self.write_tx_rx(scs_id, address, &[data]).await
+    }
+
+    pub async fn write_2byte_tx_rx(&mut self, scs_id: u32, address: u32, data: u16) -> Result<ServoStatus> {
+        let bytes = self.exchange.scs_end.split_word(data);
+        self.write_tx_rx(scs_id, address, &bytes).await
+    }
+
+    pub async fn write_4byte_tx_rx(&mut self, scs_id: u32, address: u32, data: u32) -> Result<ServoStatus> {
+        let bytes = split_dword(&self.exchange.scs_end, data);
+        self.write_tx_rx(scs_id, address, &bytes).await
+    }
+
+    // 只发送写入指令，不等待应答
+    pub async fn write_tx_only(&mut self, scs_id: u32, address: u32, data: &[u8]) -> Result<()> {
+        self.send(&Request::write(scs_id, INST::Write, address, data)?).await
+    }
+
+    pub async fn write_1byte_tx_only(&mut self, scs_id: u32, address: u32, data: u8) -> Result<()> {
+        self.write_tx_only(scs_id, address, &[data]).await
+    }
+
+    pub async fn write_2byte_tx_only(&mut self, scs_id: u32, address: u32, data: u16) -> Result<()> {
+        let bytes = self.exchange.scs_end.split_word(data);
+        self.write_tx_only(scs_id, address, &bytes).await
+    }
+
+    // 寄存器写入，等待动作指令后生效
+    pub async fn reg_write_tx_rx(&mut self, scs_id: u32, address: u32, data: &[u8]) -> Result<ServoStatus> {
+        Ok(self.request(&Request::write(scs_id, INST::RegWrite, address, data)?).await?.status())
+    }
+
+    pub async fn reg_write_1byte(&mut self, scs_id: u32, address: u32, data: u8) -> Result<ServoStatus> {
+        self.reg_write_tx_rx(scs_id, address, &[data]).await
+    }
+
+    pub async fn reg_write_2byte(&mut self, scs_id: u32, address: u32, data: u16) -> Result<ServoStatus> {
+        let bytes = self.exchange.scs_end.split_word(data);
+        self.reg_write_tx_rx(scs_id, address, &bytes).await
+    }
+
+    pub async fn sync_write_tx_only(&mut self, start_address: u32, data_length: u32, param: &[u8]) -> Result<()> {
+        let prefix = [start_address as u8, data_length as u8];
+        self.send(&Request::broadcast(INST::SyncWrite, &prefix, param)).await
+    }
+}
+
+// SMS/STS 系列异步控制器
+#[derive(Debug)]
+pub struct AsyncSmsSts<S> {
+    ph: AsyncPacketHandler<S>,
+    // 已检测的舵机型号
+    models: HashMap<u8, ServoModel>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncSmsSts<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            ph: AsyncPacketHandler::new(stream, Endian::SmallEndian),
+            models: HashMap::new(),
+        }
+    }
+
+    // 获取协议处理器，用于设置波特率或直接收发指令包
+    pub fn packet_handler_mut(&mut self) -> &mut AsyncPacketHandler<S> {
+        &mut self.ph
+    }
+
+    pub fn packet_handler(&self) -> &AsyncPacketHandler<S> {
+        &self.ph
+    }
+
+    pub fn into_inner(self) -> S {
+        self.ph.into_inner()
+    }
+
+    // 最近一次应答中的舵机状态
+    pub fn last_status(&self) -> ServoStatus {
+        self.ph.last_status()
+    }
+
+    // 按控制表描述读取寄存器
+    pub async fn read_reg(&mut self, scs_id: u32, reg: Register) -> Result<i32> {
+        self.ph.read_reg(scs_id, reg).await
+    }
+
+    // 按控制表描述写入寄存器，检查读写权限和取值范围
+    pub async fn write_reg(&mut self, scs_id: u32, reg: Register, value: i32) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, reg, value).await
+    }
+
+    // 写入是否等待舵机应答，关闭后写入方法返回空状态
+    pub fn set_write_ack(&mut self, enabled: bool) {
+        self.ph.set_write_ack(enabled);
+    }
+
+    // 舵机报告故障时是否返回 Hardware 错误
+    pub fn set_strict_status(&mut self, enabled: bool) {
+        self.ph.set_strict_status(enabled);
+    }
+
+    // 读取型号号并缓存该舵机的型号，未知型号按 SMS/STS 系列默认值处理
+    pub async fn detect_model(&mut self, scs_id: u32) -> Result<ServoModel> {
+        let number = self.read_model(scs_id).await?;
+        let model = ServoModel::from_number_or_generic(number, Family::SmsSts);
+        self.models.insert(scs_id as u8, model);
+        Ok(model)
+    }
+
+    // 已检测或指定的型号
+    pub fn model(&self, scs_id: u32) -> Option<ServoModel> {
+        self.models.get(&(scs_id as u8)).copied()
+    }
+
+    // 指定舵机型号，不读取舵机
+    pub fn set_model(&mut self, scs_id: u32, model: ServoModel) {
+        self.models.insert(scs_id as u8, model);
+    }
+
+    // 读取 EEPROM 中所有可写寄存器
+    #[cfg(feature = "config")]
+    pub async fn backup_config(&mut self, scs_id: u32) -> Result<ServoConfig> {
+        config::backup(&mut self.ph, Family::SmsSts, scs_id).await
+    }
+
+    // 依次备份多个舵机
+    #[cfg(feature = "config")]
+    pub async fn backup_configs(&mut self, scs_ids: &[u32]) -> Result<BusConfig> {
+        config::backup_all(&mut self.ph, Family::SmsSts, scs_ids).await
+    }
+
+    // 将配置写入舵机，自动解锁和锁定 EEPROM；配置中的ID不同时最后修改ID
+    // 波特率不写入，与舵机当前值不同时作为差异返回，需要用 change_baud 修改
+    #[cfg(feature = "config")]
+    pub async fn restore_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        let skipped = config::restore(&mut self.ph, Family::SmsSts, scs_id, config).await?;
+        if let Some(new_id) = config.id() {
+            self.move_model(scs_id, new_id);
+        }
+        Ok(skipped)
+    }
+
+    // 按配置中记录的ID恢复每个舵机，返回各舵机没有写入的差异
+    #[cfg(feature = "config")]
+    pub async fn restore_configs(&mut self, config: &BusConfig) -> Result<Vec<(u32, Difference)>> {
+        config::restore_all(&mut self.ph, Family::SmsSts, config).await
+    }
+
+    // 读取舵机当前的 EEPROM，返回与配置不一致的寄存器
+    #[cfg(feature = "config")]
+    pub async fn verify_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        config::verify(&mut self.ph, Family::SmsSts, scs_id, config).await
+    }
+
+    // 只写入与配置不一致的寄存器（ID 和波特率除外），返回写入的差异
+    #[cfg(feature = "config")]
+    pub async fn apply_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        config::apply(&mut self.ph, Family::SmsSts, scs_id, config).await
+    }
+
+    // 修改ID后，已检测的型号随ID迁移
+    fn move_model(&mut self, old_id: u32, new_id: u32) {
+        if let Some(model) = self.models.remove(&(old_id as u8)) {
+            self.models.insert(new_id as u8, model);
+        }
+    }
+
+    // 缓存的型号，该舵机第一次角度读写时读取型号号并缓存
+    async fn model_or_detect(&mut self, scs_id: u32) -> Result<ServoModel> {
+        match self.model(scs_id) {
+            Some(model) => Ok(model),
+            None => self.detect_model(scs_id).await,
+        }
+    }
+
+    // 读取当前位置（度），按型号换算
+    pub async fn read_pos_degrees(&mut self, scs_id: u32) -> Result<f64> {
+        let model = self.model_or_detect(scs_id).await?;
+        Ok(model.steps_to_degrees(self.read_pos(scs_id).await?))
+    }
+
+    // 读取当前速度（度/秒），按型号换算
+    pub async fn read_speed_degrees(&mut self, scs_id: u32) -> Result<f64> {
+        let model = self.model_or_detect(scs_id).await?;
+        Ok(model.steps_to_degrees(self.read_speed(scs_id).await?))
+    }
+
+    // 写入目标位置（度）和速度（度/秒），位置超出型号行程或速度超过型号最大速度时返回错误
+    pub async fn write_pos_ex_degrees(&mut self, scs_id: u32, degrees: f64, time: u32, speed: f64) -> Result<()> {
+        let (position, speed) = self.model_or_detect(scs_id).await?.goal_steps(degrees, speed)?;
+        self.write_pos_ex(scs_id, position, time, speed).await
+    }
+
+    // 写入位置扩展（包含时间和速度）
+    pub async fn write_pos_ex(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> Result<()> {
+        let param = pos_ex_param(scs_id, position, time, speed)?;
+        self.ph
+            .sync_write_tx_only(sms_sts::SMS_STS_ACC as u32, SMS_STS_POS_EX_LEN, &param)
+            .await
+    }
+
+    // 读取当前位置
+    pub async fn read_pos(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, sms_sts::SMS_STS_PRESENT_POSITION_L as u32).await?;
+        Ok(sign_magnitude_to_host(data as i32, 15))
+    }
+
+    // 读取当前速度
+    pub async fn read_speed(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, sms_sts::SMS_STS_PRESENT_SPEED_L as u32).await?;
+        Ok(sign_magnitude_to_host(data as i32, 15))
+    }
+
+    // 读取块数据中 offset 处的双字节有符号值
+    fn signed_word(&self, data: &[u8], offset: usize, sign_bit: i32) -> i32 {
+        signed_word(&self.ph.scs_getend(), data, offset, sign_bit)
+    }
+
+    // 同时读取位置和速度（一次读取连续的4个字节）
+    pub async fn read_pos_speed(&mut self, scs_id: u32) -> Result<(i32, i32)> {
+        let data = self.ph.read_tx_rx(scs_id, sms_sts::SMS_STS_PRESENT_POSITION_L as u32, 4).await?;
+        Ok((self.signed_word(&data, 0, 15), self.signed_word(&data, 2, 15)))
+    }
+
+    // 同时读取位置、速度和负载（一次读取连续的6个字节）
+    pub async fn read_pos_speed_load(&mut self, scs_id: u32) -> Result<(i32, i32, i32)> {
+        let data = self.ph.read_tx_rx(scs_id, sms_sts::SMS_STS_PRESENT_POSITION_L as u32, 6).await?;
+        Ok((
+            self.signed_word(&data, 0, 15),
+            self.signed_word(&data, 2, 15),
+            self.signed_word(&data, 4, 10),
+        ))
+    }
+
+    // 同时读取电压和温度
+    pub async fn read_voltage_temperature(&mut self, scs_id: u32) -> Result<(u8, u8)> {
+        let data = self.ph.read_tx_rx(scs_id, sms_sts::SMS_STS_PRESENT_VOLTAGE as u32, 2).await?;
+        Ok((data[0], data[1]))
+    }
+
+    // 读取运动状态
+    pub async fn read_moving(&mut self, scs_id: u32) -> Result<bool> {
+        let data = self.ph.read_1byte_tx_rx(scs_id, sms_sts::SMS_STS_MOVING as u32).await?;
+        Ok(data != 0)
+    }
+
+    // 读取型号号
+    pub async fn read_model(&mut self, scs_id: u32) -> Result<u16> {
+        self.ph.read_2byte_tx_rx(scs_id, sms_sts::SMS_STS_MODEL_L as u32).await
+    }
+
+    // 读取固件版本（主版本, 次版本）
+    pub async fn read_firmware_version(&mut self, scs_id: u32) -> Result<(u8, u8)> {
+        let data = self.ph.read_tx_rx(scs_id, sms_sts::SMS_STS_FIRMWARE_MAJOR as u32, 2).await?;
+        Ok((data[0], data[1]))
+    }
+
+    // 读取运行模式
+    pub async fn read_mode(&mut self, scs_id: u32) -> Result<OperatingMode> {
+        let mode = self.ph.read_1byte_tx_rx(scs_id, sms_sts::SMS_STS_MODE as u32).await?;
+        OperatingMode::decode(mode)
+    }
+
+    // 写入运行模式（EEPROM，需先解锁）
+    pub async fn write_mode(&mut self, scs_id: u32, mode: OperatingMode) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, sms_sts::regs::MODE, mode as i32).await
+    }
+
+    // 读取位置环 PID 系数 (P, D, I)
+    pub async fn read_pid(&mut self, scs_id: u32) -> Result<(u8, u8, u8)> {
+        let data = self.ph.read_tx_rx(scs_id, sms_sts::SMS_STS_P_COEFFICIENT as u32, 3).await?;
+        Ok((data[0], data[1], data[2]))
+    }
+
+    // 写入位置环 PID 系数 (P, D, I)，三个寄存器一次写入（EEPROM，需先解锁）
+    pub async fn write_pid(&mut self, scs_id: u32, p: u8, d: u8, i: u8) -> Result<ServoStatus> {
+        let param = pid_param(p, d, i)?;
+        self.ph.write_tx_rx(scs_id, sms_sts::SMS_STS_P_COEFFICIENT as u32, &param).await
+    }
+
+    // 读取运行时扭矩限制（SRAM，0.1%）
+    pub async fn read_torque_limit(&mut self, scs_id: u32) -> Result<u16> {
+        self.ph.read_2byte_tx_rx(scs_id, sms_sts::SMS_STS_TORQUE_LIMIT_L as u32).await
+    }
+
+    // 写入运行时扭矩限制（SRAM，0~1000，0.1%），上电后恢复为最大扭矩
+    pub async fn write_torque_limit(&mut self, scs_id: u32, limit: u16) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, sms_sts::regs::TORQUE_LIMIT, limit as i32).await
+    }
+
+    // 读取最大扭矩（EEPROM，0.1%）
+    pub async fn read_max_torque(&mut self, scs_id: u32) -> Result<u16> {
+        self.ph.read_2byte_tx_rx(scs_id, sms_sts::SMS_STS_MAX_TORQUE_L as u32).await
+    }
+
+    // 写入最大扭矩（EEPROM，0~1000，0.1%，需先解锁）
+    pub async fn write_max_torque(&mut self, scs_id: u32, torque: u16) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, sms_sts::regs::MAX_TORQUE, torque as i32).await
+    }
+
+    // 读取保护电流（6.5mA）
+    pub async fn read_protection_current(&mut self, scs_id: u32) -> Result<u16> {
+        self.ph.read_2byte_tx_rx(scs_id, sms_sts::SMS_STS_PROTECTION_CURRENT_L as u32).await
+    }
+
+    // 写入保护电流（EEPROM，0~511，6.5mA，需先解锁）
+    pub async fn write_protection_current(&mut self, scs_id: u32, current: u16) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, sms_sts::regs::PROTECTION_CURRENT, current as i32).await
+    }
+
+    // 读取最高温度限制（°C）
+    pub async fn read_max_temperature(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, sms_sts::SMS_STS_MAX_TEMPERATURE_LIMIT as u32).await
+    }
+
+    // 写入最高温度限制（EEPROM，0~100°C，需先解锁）
+    pub async fn write_max_temperature(&mut self, scs_id: u32, temperature: u8) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, sms_sts::regs::MAX_TEMPERATURE_LIMIT, temperature as i32).await
+    }
+
+    // 读取输入电压范围（最低, 最高），单位 0.1V
+    pub async fn read_voltage_limits(&mut self, scs_id: u32) -> Result<(u8, u8)> {
+        let data = self.ph.read_tx_rx(scs_id, sms_sts::SMS_STS_MAX_INPUT_VOLTAGE as u32, 2).await?;
+        Ok((data[1], data[0]))
+    }
+
+    // 写入输入电压范围（最低, 最高），单位 0.1V（EEPROM，需先解锁）
+    pub async fn write_voltage_limits(&mut self, scs_id: u32, min: u8, max: u8) -> Result<ServoStatus> {
+        let param = voltage_limits_param(min, max)?;
+        self.ph.write_tx_rx(scs_id, sms_sts::SMS_STS_MAX_INPUT_VOLTAGE as u32, &param).await
+    }
+
+    // 读取卸载条件：出现这些故障时舵机卸载扭矩
+    pub async fn read_unloading_condition(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        let data = self.ph.read_1byte_tx_rx(scs_id, sms_sts::SMS_STS_UNLOADING_CONDITION as u32).await?;
+        Ok(ServoStatus::from_bits_retain(data))
+    }
+
+    // 写入卸载条件（EEPROM，需先解锁）
+    pub async fn write_unloading_condition(&mut self, scs_id: u32, condition: ServoStatus) -> Result<ServoStatus> {
+        self.ph
+            .write_1byte_tx_rx(scs_id, sms_sts::SMS_STS_UNLOADING_CONDITION as u32, condition.bits())
+            .await
+    }
+
+    // 读取 LED 报警条件：出现这些故障时 LED 闪烁
+    pub async fn read_led_alarm_condition(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        let data = self.ph.read_1byte_tx_rx(scs_id, sms_sts::SMS_STS_LED_ALARM_CONDITION as u32).await?;
+        Ok(ServoStatus::from_bits_retain(data))
+    }
+
+    // 写入 LED 报警条件（EEPROM，需先解锁）
+    pub async fn write_led_alarm_condition(&mut self, scs_id: u32, condition: ServoStatus) -> Result<ServoStatus> {
+        self.ph
+            .write_1byte_tx_rx(scs_id, sms_sts::SMS_STS_LED_ALARM_CONDITION as u32, condition.bits())
+            .await
+    }
+
+    // 读取运行相位
+    pub async fn read_phase(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, sms_sts::SMS_STS_PHASE as u32).await
+    }
+
+    // 读取舵机当前的故障状态（STATUS 寄存器）
+    pub async fn read_status(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        let data = self.ph.read_1byte_tx_rx(scs_id, sms_sts::SMS_STS_STATUS as u32).await?;
+        Ok(ServoStatus::from_bits_retain(data))
+    }
+
+    // 同步写入多个舵机位置
+    pub async fn sync_write_pos_ex(&mut self, scs_ids: &[u32], positions: &[i32], times: &[u32], speeds: &[u32]) -> Result<()> {
+        check_sync_lengths(scs_ids.len(), positions.len(), times.len(), speeds.len())?;
+
+        let mut param = Vec::with_capacity(scs_ids.len() * (1 + SMS_STS_POS_EX_LEN as usize));
+        for i in 0..scs_ids.len() {
+            param.extend_from_slice(&pos_ex_param(scs_ids[i], positions[i], times[i], speeds[i])?);
+        }
+
+        self.ph
+            .sync_write_tx_only(sms_sts::SMS_STS_ACC as u32, SMS_STS_POS_EX_LEN, &param)
+            .await
+    }
+
+    // 寄存器写入位置扩展：一条 RegWrite 写入 ACC/位置/时间/速度，舵机在 reg_action 后才开始运动
+    pub async fn reg_write_pos_ex(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> Result<ServoStatus> {
+        let param = pos_ex_param(scs_id, position, time, speed)?;
+        self.ph.reg_write_tx_rx(scs_id, sms_sts::SMS_STS_ACC as u32, &param[1..]).await
+    }
+
+    // 寄存器动作，scs_id 为 BROADCAST_ID 时所有舵机同时开始
+    pub async fn reg_action(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        self.ph.action(scs_id).await
+    }
+
+    // 轮式模式
+    pub async fn wheel_mode(&mut self, scs_id: u32, mode: u8) -> Result<ServoStatus> {
+        self.ph.write_1byte_tx_rx(scs_id, sms_sts::SMS_STS_MODE as u32, mode).await
+    }
+
+    // 写入扭矩使能
+    pub async fn write_torque_enable(&mut self, scs_id: u32, enable: bool) -> Result<ServoStatus> {
+        self.ph
+            .write_1byte_tx_rx(scs_id, sms_sts::SMS_STS_TORQUE_ENABLE as u32, u8::from(enable))
+            .await
+    }
+
+    // 锁定EPROM
+    pub async fn lock_eprom(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        self.ph.write_1byte_tx_rx(scs_id, sms_sts::SMS_STS_LOCK as u32, 1).await
+    }
+
+    // 解锁EPROM
+    pub async fn unlock_eprom(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        self.ph.write_1byte_tx_rx(scs_id, sms_sts::SMS_STS_LOCK as u32, 0).await
+    }
+
+    // Ping舵机，等待应答
+    pub async fn ping(&mut self, scs_id: u32) -> Result<PingInfo> {
+        self.ph.ping(scs_id).await
+    }
+
+    // Ping舵机并读取型号号
+    pub async fn ping_with_model(&mut self, scs_id: u32) -> Result<PingInfo> {
+        self.ph.ping_with_model(scs_id).await
+    }
+
+    // 读取电压
+    pub async fn read_voltage(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, sms_sts::SMS_STS_PRESENT_VOLTAGE as u32).await
+    }
+
+    // 读取温度
+    pub async fn read_temperature(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, sms_sts::SMS_STS_PRESENT_TEMPERATURE as u32).await
+    }
+
+    // 读取负载
+    pub async fn read_load(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, sms_sts::SMS_STS_PRESENT_LOAD_L as u32).await?;
+        Ok(sign_magnitude_to_host(data as i32, 10))
+    }
+
+    // 读取电流
+    pub async fn read_current(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, sms_sts::SMS_STS_PRESENT_CURRENT_L as u32).await?;
+        Ok(sign_magnitude_to_host(data as i32, 15))
+    }
+
+    // 设置ID，只写入寄存器（需先解锁 EEPROM），完整流程见 change_id
+    // 舵机写入后以新ID应答，只发送不等待
+    pub async fn set_id(&mut self, old_id: u32, new_id: u32) -> Result<()> {
+        self.ph.write_1byte_tx_only(old_id, sms_sts::SMS_STS_ID as u32, new_id as u8).await?;
+        self.move_model(old_id, new_id);
+        Ok(())
+    }
+
+    // 设置波特率寄存器（SMS_STS_1M 等），只写入寄存器，完整流程见 change_baud
+    // 舵机写入后以新波特率应答，只发送不等待
+    pub async fn set_baudrate(&mut self, scs_id: u32, baudrate: u8) -> Result<()> {
+        self.ph.write_1byte_tx_only(scs_id, sms_sts::SMS_STS_BAUD_RATE as u32, baudrate).await
+    }
+
+    // 安全修改ID：确认目标ID空闲，自动解锁和锁定 EEPROM，最后在新ID上 Ping 确认
+    pub async fn change_id(&mut self, old_id: u32, new_id: u32) -> Result<()> {
+        setup::change_id(&mut self.ph, Family::SmsSts, old_id, new_id).await?;
+        self.move_model(old_id, new_id);
+        Ok(())
+    }
+
+    // 修改波特率（如 115200）：舵机写入后调用 switch 切换字节流的波特率（如 tokio-serial 的
+    // set_baud_rate），然后在新波特率下锁定 EEPROM 并确认
+    pub async fn change_baud<F>(&mut self, scs_id: u32, baudrate: u32, switch: F) -> Result<()>
+    where
+        F: FnOnce(&mut S, u32) -> Result<()>,
+    {
+        setup::change_baud(&mut self.ph, Family::SmsSts, &[scs_id], baudrate, switch).await
+    }
+
+    // 同时修改多个舵机的波特率，字节流只切换一次
+    pub async fn change_baud_all<F>(&mut self, scs_ids: &[u32], baudrate: u32, switch: F) -> Result<()>
+    where
+        F: FnOnce(&mut S, u32) -> Result<()>,
+    {
+        setup::change_baud(&mut self.ph, Family::SmsSts, scs_ids, baudrate, switch).await
+    }
+}
+
+// SCSCL 系列异步控制器
+#[derive(Debug)]
+pub struct AsyncScscl<S> {
+    ph: AsyncPacketHandler<S>,
+    // 已检测的舵机型号
+    models: HashMap<u8, ServoModel>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncScscl<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            ph: AsyncPacketHandler::new(stream, Endian::BigEndian),
+            models: HashMap::new(),
+        }
+    }
+
+    // 获取协议处理器，用于设置波特率或直接收发指令包
+    pub fn packet_handler_mut(&mut self) -> &mut AsyncPacketHandler<S> {
+        &mut self.ph
+    }
+
+    pub fn packet_handler(&self) -> &AsyncPacketHandler<S> {
+        &self.ph
+    }
+
+    pub fn into_inner(self) -> S {
+        self.ph.into_inner()
+    }
+
+    // 最近一次应答中的舵机状态
+    pub fn last_status(&self) -> ServoStatus {
+        self.ph.last_status()
+    }
+
+    // 按控制表描述读取寄存器
+    pub async fn read_reg(&mut self, scs_id: u32, reg: Register) -> Result<i32> {
+        self.ph.read_reg(scs_id, reg).await
+    }
+
+    // 按控制表描述写入寄存器，检查读写权限和取值范围
+    pub async fn write_reg(&mut self, scs_id: u32, reg: Register, value: i32) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, reg, value).await
+    }
+
+    // 写入是否等待舵机应答，关闭后写入方法返回空状态
+    pub fn set_write_ack(&mut self, enabled: bool) {
+        self.ph.set_write_ack(enabled);
+    }
+
+    // 舵机报告故障时是否返回 Hardware 错误
+    pub fn set_strict_status(&mut self, enabled: bool) {
+        self.ph.set_strict_status(enabled);
+    }
+
+    // 读取型号号并缓存该舵机的型号，未知型号按 SCSCL 系列默认值处理
+    pub async fn detect_model(&mut self, scs_id: u32) -> Result<ServoModel> {
+        let number = self.read_model(scs_id).await?;
+        let model = ServoModel::from_number_or_generic(number, Family::Scscl);
+        self.models.insert(scs_id as u8, model);
+        Ok(model)
+    }
+
+    // 已检测或指定的型号
+    pub fn model(&self, scs_id: u32) -> Option<ServoModel> {
+        self.models.get(&(scs_id as u8)).copied()
+    }
+
+    // 指定舵机型号，不读取舵机
+    pub fn set_model(&mut self, scs_id: u32, model: ServoModel) {
+        self.models.insert(scs_id as u8, model);
+    }
+
+    // 读取 EEPROM 中所有可写寄存器
+    #[cfg(feature = "config")]
+    pub async fn backup_config(&mut self, scs_id: u32) -> Result<ServoConfig> {
+        config::backup(&mut self.ph, Family::Scscl, scs_id).await
+    }
+
+    // 依次备份多个舵机
+    #[cfg(feature = "config")]
+    pub async fn backup_configs(&mut self, scs_ids: &[u32]) -> Result<BusConfig> {
+        config::backup_all(&mut self.ph, Family::Scscl, scs_ids).await
+    }
+
+    // 将配置写入舵机，自动解锁和锁定 EEPROM；配置中的ID不同时最后修改ID
+    // 波特率不写入，与舵机当前值不同时作为差异返回，需要用 change_baud 修改
+    #[cfg(feature = "config")]
+    pub async fn restore_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        let skipped = config::restore(&mut self.ph, Family::Scscl, scs_id, config).await?;
+        if let Some(new_id) = config.id() {
+            self.move_model(scs_id, new_id);
+        }
+        Ok(skipped)
+    }
+
+    // 按配置中记录的ID恢复每个舵机，返回各舵机没有写入的差异
+    #[cfg(feature = "config")]
+    pub async fn restore_configs(&mut self, config: &BusConfig) -> Result<Vec<(u32, Difference)>> {
+        config::restore_all(&mut self.ph, Family::Scscl, config).await
+    }
+
+    // 读取舵机当前的 EEPROM，返回与配置不一致的寄存器
+    #[cfg(feature = "config")]
+    pub async fn verify_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        config::verify(&mut self.ph, Family::Scscl, scs_id, config).await
+    }
+
+    // 只写入与配置不一致的寄存器（ID 和波特率除外），返回写入的差异
+    #[cfg(feature = "config")]
+    pub async fn apply_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        config::apply(&mut self.ph, Family::Scscl, scs_id, config).await
+    }
+
+    // 修改ID后，已检测的型号随ID迁移
+    fn move_model(&mut self, old_id: u32, new_id: u32) {
+        if let Some(model) = self.models.remove(&(old_id as u8)) {
+            self.models.insert(new_id as u8, model);
+        }
+    }
+
+    // 缓存的型号，该舵机第一次角度读写时读取型号号并缓存
+    async fn model_or_detect(&mut self, scs_id: u32) -> Result<ServoModel> {
+        match self.model(scs_id) {
+            Some(model) => Ok(model),
+            None => self.detect_model(scs_id).await,
+        }
+    }
+
+    // 读取当前位置（度），按型号换算
+    pub async fn read_pos_degrees(&mut self, scs_id: u32) -> Result<f64> {
+        let model = self.model_or_detect(scs_id).await?;
+        Ok(model.steps_to_degrees(self.read_pos(scs_id).await?))
+    }
+
+    // 读取当前速度（度/秒），按型号换算
+    pub async fn read_speed_degrees(&mut self, scs_id: u32) -> Result<f64> {
+        let model = self.model_or_detect(scs_id).await?;
+        Ok(model.steps_to_degrees(self.read_speed(scs_id).await?))
+    }
+
+    // 写入目标位置（度）和速度（度/秒），位置超出型号行程或速度超过型号最大速度时返回错误
+    pub async fn write_pos_degrees(&mut self, scs_id: u32, degrees: f64, time: u32, speed: f64) -> Result<()> {
+        let (position, speed) = self.model_or_detect(scs_id).await?.goal_steps(degrees, speed)?;
+        self.write_pos(scs_id, position, time, speed).await
+    }
+
+    // 写入位置、时间和速度
+    pub async fn write_pos(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> Result<()> {
+        let param = pos_param(scs_id, position, time, speed);
+        self.ph
+            .sync_write_tx_only(scscl::SCSCL_GOAL_POSITION_L as u32, SCSCL_POS_LEN, &param)
+            .await
+    }
+
+    // 读取当前位置
+    pub async fn read_pos(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, scscl::SCSCL_PRESENT_POSITION_L as u32).await?;
+        Ok(sign_magnitude_to_host(data as i32, 15))
+    }
+
+    // 读取当前速度
+    pub async fn read_speed(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, scscl::SCSCL_PRESENT_SPEED_L as u32).await?;
+        Ok(sign_magnitude_to_host(data as i32, 15))
+    }
+
+    // 读取当前负载
+    pub async fn read_load(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, scscl::SCSCL_PRESENT_LOAD_L as u32).await?;
+        Ok(sign_magnitude_to_host(data as i32, 10))
+    }
+
+    // 读取电压
+    pub async fn read_voltage(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, scscl::SCSCL_PRESENT_VOLTAGE as u32).await
+    }
+
+    // 读取温度
+    pub async fn read_temperature(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, scscl::SCSCL_PRESENT_TEMPERATURE as u32).await
+    }
+
+    // 读取块数据中 offset 处的双字节有符号值
+    fn signed_word(&self, data: &[u8], offset: usize, sign_bit: i32) -> i32 {
+        signed_word(&self.ph.scs_getend(), data, offset, sign_bit)
+    }
+
+    // 同时读取位置和速度（一次读取连续的4个字节）
+    pub async fn read_pos_speed(&mut self, scs_id: u32) -> Result<(i32, i32)> {
+        let data = self.ph.read_tx_rx(scs_id, scscl::SCSCL_PRESENT_POSITION_L as u32, 4).await?;
+        Ok((self.signed_word(&data, 0, 15), self.signed_word(&data, 2, 15)))
+    }
+
+    // 同时读取位置、速度和负载（一次读取连续的6个字节）
+    pub async fn read_pos_speed_load(&mut self, scs_id: u32) -> Result<(i32, i32, i32)> {
+        let data = self.ph.read_tx_rx(scs_id, scscl::SCSCL_PRESENT_POSITION_L as u32, 6).await?;
+        Ok((
+            self.signed_word(&data, 0, 15),
+            self.signed_word(&data, 2, 15),
+            self.signed_word(&data, 4, 10),
+        ))
+    }
+
+    // 同时读取电压和温度
+    pub async fn read_voltage_temperature(&mut self, scs_id: u32) -> Result<(u8, u8)> {
+        let data = self.ph.read_tx_rx(scs_id, scscl::SCSCL_PRESENT_VOLTAGE as u32, 2).await?;
+        Ok((data[0], data[1]))
+    }
+
+    // 读取是否在运动
+    pub async fn read_moving(&mut self, scs_id: u32) -> Result<bool> {
+        let data = self.ph.read_1byte_tx_rx(scs_id, scscl::SCSCL_MOVING as u32).await?;
+        Ok(data != 0)
+    }
+
+    // 读取电流
+    pub async fn read_current(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, scscl::SCSCL_PRESENT_CURRENT_L as u32).await?;
+        Ok(sign_magnitude_to_host(data as i32, 15))
+    }
+
+    // 同步写入多个舵机位置
+    pub async fn sync_write_pos(&mut self, scs_ids: &[u32], positions: &[i32], times: &[u32], speeds: &[u32]) -> Result<()> {
+        check_sync_lengths(scs_ids.len(), positions.len(), times.len(), speeds.len())?;
+
+        let mut param = Vec::with_capacity(scs_ids.len() * (1 + SCSCL_POS_LEN as usize));
+        for i in 0..scs_ids.len() {
+            param.extend_from_slice(&pos_param(scs_ids[i], positions[i], times[i], speeds[i]));
+        }
+
+        self.ph
+            .sync_write_tx_only(scscl::SCSCL_GOAL_POSITION_L as u32, SCSCL_POS_LEN, &param)
+            .await
+    }
+
+    // 设置舵机模式
+    pub async fn wheel_mode(&mut self, scs_id: u32, mode: u8) -> Result<ServoStatus> {
+        self.ph.write_1byte_tx_rx(scs_id, scscl::SCSCL_MODE as u32, mode).await
+    }
+
+    // 设置扭矩使能
+    pub async fn write_torque_enable(&mut self, scs_id: u32, enable: bool) -> Result<ServoStatus> {
+        self.ph
+            .write_1byte_tx_rx(scs_id, scscl::SCSCL_TORQUE_ENABLE as u32, u8::from(enable))
+            .await
+    }
+
+    // 设置角度限制
+    pub async fn write_angle_limit(&mut self, scs_id: u32, min_angle: i32, max_angle: i32) -> Result<ServoStatus> {
+        let status = self.ph.write_2byte_tx_rx(scs_id, scscl::SCSCL_MIN_ANGLE_LIMIT_L as u32, min_angle as u16).await?;
+        Ok(status | self.ph.write_2byte_tx_rx(scs_id, scscl::SCSCL_MAX_ANGLE_LIMIT_L as u32, max_angle as u16).await?)
+    }
+
+    // 设置死区
+    pub async fn write_dead_zone(&mut self, scs_id: u32, cw_dead: u8, ccw_dead: u8) -> Result<ServoStatus> {
+        let status = self.ph.write_1byte_tx_rx(scs_id, scscl::SCSCL_CW_DEAD as u32, cw_dead).await?;
+        Ok(status | self.ph.write_1byte_tx_rx(scs_id, scscl::SCSCL_CCW_DEAD as u32, ccw_dead).await?)
+    }
+
+    // 设置偏移量，按 regs::OFS 的符号位编码
+    pub async fn write_offset(&mut self, scs_id: u32, offset: i32) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, scscl::regs::OFS, offset).await
+    }
+
+    // 锁定EPROM
+    pub async fn lock_eprom(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        self.ph.write_1byte_tx_rx(scs_id, scscl::SCSCL_LOCK as u32, 1).await
+    }
+
+    // 解锁EPROM
+    pub async fn unlock_eprom(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        self.ph.write_1byte_tx_rx(scs_id, scscl::SCSCL_LOCK as u32, 0).await
+    }
+
+    // 设置波特率寄存器（SCSCL_1M 等），只写入寄存器，完整流程见 change_baud
+    // 舵机写入后以新波特率应答，只发送不等待
+    pub async fn write_baudrate(&mut self, scs_id: u32, baudrate: u8) -> Result<()> {
+        self.ph.write_1byte_tx_only(scs_id, scscl::SCSCL_BAUD_RATE as u32, baudrate).await
+    }
+
+    // 设置ID，只写入寄存器（需先解锁 EEPROM），完整流程见 change_id
+    // 舵机写入后以新ID应答，只发送不等待
+    pub async fn write_id(&mut self, scs_id: u32, new_id: u8) -> Result<()> {
+        self.ph.write_1byte_tx_only(scs_id, scscl::SCSCL_ID as u32, new_id).await?;
+        self.move_model(scs_id, new_id as u32);
+        Ok(())
+    }
+
+    // 安全修改ID：确认目标ID空闲，自动解锁和锁定 EEPROM，最后在新ID上 Ping 确认
+    pub async fn change_id(&mut self, old_id: u32, new_id: u32) -> Result<()> {
+        setup::change_id(&mut self.ph, Family::Scscl, old_id, new_id).await?;
+        self.move_model(old_id, new_id);
+        Ok(())
+    }
+
+    // 修改波特率（如 115200）：舵机写入后调用 switch 切换字节流的波特率，
+    // 然后在新波特率下锁定 EEPROM 并确认
+    pub async fn change_baud<F>(&mut self, scs_id: u32, baudrate: u32, switch: F) -> Result<()>
+    where
+        F: FnOnce(&mut S, u32) -> Result<()>,
+    {
+        setup::change_baud(&mut self.ph, Family::Scscl, &[scs_id], baudrate, switch).await
+    }
+
+    // 同时修改多个舵机的波特率，字节流只切换一次
+    pub async fn change_baud_all<F>(&mut self, scs_ids: &[u32], baudrate: u32, switch: F) -> Result<()>
+    where
+        F: FnOnce(&mut S, u32) -> Result<()>,
+    {
+        setup::change_baud(&mut self.ph, Family::Scscl, scs_ids, baudrate, switch).await
+    }
+
+    // 读取模型号
+    pub async fn read_model(&mut self, scs_id: u32) -> Result<u16> {
+        self.ph.read_2byte_tx_rx(scs_id, scscl::SCSCL_MODEL_L as u32).await
+    }
+
+    // ping测试，等待应答
+    pub async fn ping(&mut self, scs_id: u32) -> Result<PingInfo> {
+        self.ph.ping(scs_id).await
+    }
+
+    // Ping舵机并读取型号号
+    pub async fn ping_with_model(&mut self, scs_id: u32) -> Result<PingInfo> {
+        self.ph.ping_with_model(scs_id).await
+    }
+
+    // 一条 RegWrite 写入位置/时间/速度，舵机在 reg_action 后才开始运动
+    pub async fn reg_write_pos(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> Result<ServoStatus> {
+        let param = pos_param(scs_id, position, time, speed);
+        self.ph.reg_write_tx_rx(scs_id, scscl::SCSCL_GOAL_POSITION_L as u32, &param[1..]).await
+    }
+
+    // 执行寄存器写入的动作，scs_id 为 BROADCAST_ID 时所有舵机同时开始
+    pub async fn reg_action(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        self.ph.action(scs_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        sim::{sim_chain, SimFamily},
+        sms_sts::regs,
+        FtServoError,
+    };
+    use tokio::io::DuplexStream;
+
+    // 在 duplex 字节流的另一端运行虚拟舵机
+    fn sim_stream(ids: &[u8]) -> AsyncSmsSts<DuplexStream> {
+        let (client, server) = tokio::io::duplex(256);
+        let mut chain = sim_chain(SimFamily::SmsSts, ids);
+        tokio::spawn(async move { chain.serve_async(server).await });
+        AsyncSmsSts::new(client)
+    }
+
+    #[tokio::test]
+    async fn reads_and_writes_over_a_duplex_stream() {
+        let mut sms_sts = sim_stream(&[1]);
+        sms_sts.write_torque_limit(1, 500).await.unwrap();
+        assert_eq!(sms_sts.read_torque_limit(1).await.unwrap(), 500);
+
+        assert!(matches!(sms_sts.read_pos(9).await, Err(FtServoError::Timeout { id: 9, .. })));
+        assert_eq!(sms_sts.ping(1).await.unwrap().id, 1);
+    }
+
+    #[tokio::test]
+    async fn cancelled_read_does_not_answer_the_next_one() {
+        let mut sms_sts = sim_stream(&[1]);
+        sms_sts.write_torque_limit(1, 500).await.unwrap();
+
+        // 指令已发出，应答到达前取消
+        tokio::select! {
+            biased;
+            _ = sms_sts.read_voltage(1) => panic!("read should still be waiting"),
+            _ = std::future::ready(()) => {}
+        }
+        assert_eq!(sms_sts.read_torque_limit(1).await.unwrap(), 500);
+    }
+
+    #[tokio::test]
+    async fn change_id_and_degrees_over_a_duplex_stream() {
+        let mut sms_sts = sim_stream(&[1, 2]);
+        assert!(matches!(sms_sts.change_id(1, 2).await, Err(FtServoError::InvalidParameter(_))));
+
+        sms_sts.write_pos_ex_degrees(1, 90.0, 0, 180.0).await.unwrap();
+        assert_eq!(sms_sts.model(1).map(|model| model.name), Some("STS3215"));
+        assert_eq!(sms_sts.read_reg(1, regs::GOAL_POSITION).await.unwrap(), 1024);
+
+        sms_sts.change_id(1, 7).await.unwrap();
+        assert_eq!(sms_sts.ping(7).await.unwrap().id, 7);
+        assert!(sms_sts.ping(1).await.is_err());
+        assert_eq!(sms_sts.read_reg(7, regs::LOCK).await.unwrap(), 1);
+        assert!(sms_sts.model(7).is_some());
+    }
+
+    #[cfg(feature = "config")]
+    #[tokio::test]
+    async fn apply_config_over_a_duplex_stream() {
+        let mut sms_sts = sim_stream(&[1]);
+        let mut profile = sms_sts.backup_config(1).await.unwrap();
+        profile.set("OFS", -50);
+
+        let differences = sms_sts.apply_config(1, &profile).await.unwrap();
+        let written: Vec<&str> = differences.iter().map(|difference| difference.register.name).collect();
+        assert_eq!(written, ["OFS"]);
+        assert!(sms_sts.verify_config(1, &profile).await.unwrap().is_empty());
+        assert_eq!(sms_sts.read_reg(1, regs::LOCK).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,177 @@
+//! 舵机命令行工具
+//!
+//! 用法:
+//!   ftservo scan --port PATH [--baud BAUD,BAUD...] [--ids FIRST-LAST] [--scscl] [--timeout-ms MS]
+//!   ftservo id   --port PATH --from ID --to ID [--baud BAUD] [--scscl]
+//!   ftservo baud --port PATH --id ID[,ID...] --to BAUD [--baud BAUD] [--scscl]
+
+use ftservo_sdk::{scan, Endian, FtServoError, PortHandler, Result, ScanOptions, Scscl, SmsSts};
+use std::{collections::HashMap, time::Duration};
+
+const USAGE: &str = "用法:
+  ftservo scan --port PATH [--baud BAUD,BAUD...] [--ids FIRST-LAST] [--scscl] [--timeout-ms MS]
+  ftservo id   --port PATH --from ID --to ID [--baud BAUD] [--scscl]
+  ftservo baud --port PATH --id ID[,ID...] --to BAUD [--baud BAUD] [--scscl]";
+
+// 未指定 --baud 时端口使用的波特率
+const DEFAULT_BAUDRATE: u32 = 1_000_000;
+
+fn main() {
+    if let Err(e) = run(std::env::args().skip(1).collect()) {
+        eprintln!("ftservo: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn invalid(message: String) -> FtServoError {
+    FtServoError::InvalidParameter(message)
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, what: &str) -> Result<T> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| invalid(format!("无效的{}: {}", what, value)))
+}
+
+// 解析 "1-20" 或单个ID
+fn parse_id_range(value: &str) -> Result<std::ops::RangeInclusive<u8>> {
+    let (first, last) = value.split_once('-').unwrap_or((value, value));
+    let (first, last) = (parse_number(first, "舵机ID")?, parse_number(last, "舵机ID")?);
+    if first > last {
+        return Err(invalid(format!("无效的ID范围: {}", value)));
+    }
+    Ok(first..=last)
+}
+
+fn run(args: Vec<String>) -> Result<()> {
+    let mut iter = args.into_iter();
+    match iter.next().as_deref() {
+        Some("scan") => scan_command(iter.collect()),
+        Some("id") => id_command(iter.collect()),
+        Some("baud") => baud_command(iter.collect()),
+        Some(command) => Err(invalid(format!("未知命令: {}\n{}", command, USAGE))),
+        None => Err(invalid(USAGE.to_string())),
+    }
+}
+
+fn scan_command(args: Vec<String>) -> Result<()> {
+    let mut port = None;
+    let mut options = ScanOptions::default();
+
+    let mut iter = args.into_iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or_else(|| invalid(format!("{} 缺少参数", arg)));
+        match arg.as_str() {
+            "--port" => port = Some(value()?),
+            "--baud" => {
+                options.baudrates = value()?
+                    .split(',')
+                    .filter(|s| !s.is_empty())
+                    .map(|s| parse_number(s, "波特率"))
+                    .collect::<Result<_>>()?
+            }
+            "--ids" => options.ids = parse_id_range(&value()?)?,
+            "--scscl" => options.end = Endian::BigEndian,
+            "--timeout-ms" => options.timeout = Duration::from_millis(parse_number(&value()?, "超时")?),
+            _ => return Err(invalid(format!("未知参数: {}", arg))),
+        }
+    }
+    let port = port.ok_or_else(|| invalid(format!("缺少 --port\n{}", USAGE)))?;
+
+    let mut port_handler = PortHandler::new(&port);
+    port_handler.open_port()?;
+
+    println!("正在扫描 {}: ID {}-{}", port, options.ids.start(), options.ids.end());
+    let found = scan(&mut port_handler, &options)?;
+    port_handler.close_port()?;
+
+    if found.is_empty() {
+        println!("未找到舵机");
+        return Ok(());
+    }
+
+    println!("{:>8}  {:>3}  {:>8}  {:>6}  状态", "波特率", "ID", "型号", "固件");
+    for servo in &found {
+        let model = match (servo.servo_model(), servo.model) {
+            (Some(model), _) => model.name.to_string(),
+            (None, Some(number)) => number.to_string(),
+            (None, None) => "-".to_string(),
+        };
+        let firmware = servo
+            .firmware
+            .map_or("-".to_string(), |(major, minor)| format!("{}.{}", major, minor));
+        println!("{:>8}  {:>3}  {:>8}  {:>6}  {}", servo.baudrate, servo.id, model, firmware, servo.status);
+    }
+    println!("共找到 {} 个舵机", found.len());
+    Ok(())
+}
+
+// 解析 "--name value" 形式的参数，--scscl 不带值
+fn parse_flags(args: Vec<String>, names: &[&str]) -> Result<HashMap<String, String>> {
+    let mut flags = HashMap::new();
+    let mut iter = args.into_iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--scscl" {
+            flags.insert(arg, String::new());
+        } else if names.contains(&arg.as_str()) {
+            let value = iter.next().ok_or_else(|| invalid(format!("{} 缺少参数", arg)))?;
+            flags.insert(arg, value);
+        } else {
+            return Err(invalid(format!("未知参数: {}", arg)));
+        }
+    }
+    Ok(flags)
+}
+
+fn required<'a>(flags: &'a HashMap<String, String>, name: &str) -> Result<&'a str> {
+    flags
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| invalid(format!("缺少 {}\n{}", name, USAGE)))
+}
+
+// 按 --port 和 --baud 打开端口
+fn open_port(flags: &HashMap<String, String>) -> Result<PortHandler> {
+    let baudrate = match flags.get("--baud") {
+        Some(baud) => parse_number(baud, "波特率")?,
+        None => DEFAULT_BAUDRATE,
+    };
+    let mut port_handler = PortHandler::new(required(flags, "--port")?);
+    port_handler.set_baudrate(baudrate)?;
+    port_handler.open_port()?;
+    Ok(port_handler)
+}
+
+fn id_command(args: Vec<String>) -> Result<()> {
+    let flags = parse_flags(args, &["--port", "--baud", "--from", "--to"])?;
+    let old_id: u32 = parse_number(required(&flags, "--from")?, "舵机ID")?;
+    let new_id: u32 = parse_number(required(&flags, "--to")?, "舵机ID")?;
+
+    let mut port_handler = open_port(&flags)?;
+    if flags.contains_key("--scscl") {
+        Scscl::new(&mut port_handler).change_id(old_id, new_id)?;
+    } else {
+        SmsSts::new(&mut port_handler).change_id(old_id, new_id)?;
+    }
+    println!("舵机ID已从 {} 修改为 {}", old_id, new_id);
+    Ok(())
+}
+
+fn baud_command(args: Vec<String>) -> Result<()> {
+    let flags = parse_flags(args, &["--port", "--baud", "--id", "--to"])?;
+    let ids = required(&flags, "--id")?
+        .split(',')
+        .map(|id| parse_number(id, "舵机ID"))
+        .collect::<Result<Vec<u32>>>()?;
+    let baudrate: u32 = parse_number(required(&flags, "--to")?, "波特率")?;
+
+    let mut port_handler = open_port(&flags)?;
+    if flags.contains_key("--scscl") {
+        Scscl::new(&mut port_handler).change_baud_all(&ids, baudrate)?;
+    } else {
+        SmsSts::new(&mut port_handler).change_baud_all(&ids, baudrate)?;
+    }
+    println!("舵机 {:?} 的波特率已修改为 {}", ids, baudrate);
+    Ok(())
+}
@@ -0,0 +1,211 @@
+//! 伪终端舵机模拟器
+//! 打开一对 PTY，在从设备端模拟一串舵机，其他程序可通过 PortHandler::new("/dev/pts/N") 连接
+//!
+//! 用法: ftservo-sim [--sms ID,ID...] [--scscl ID,ID...] [--link PATH]
+
+#[cfg(unix)]
+fn main() {
+    if let Err(e) = pty::run(std::env::args().skip(1).collect()) {
+        eprintln!("ftservo-sim: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("ftservo-sim: 仅支持类 Unix 系统");
+    std::process::exit(1);
+}
+
+#[cfg(unix)]
+mod pty {
+    use ftservo_sdk::sim::{ServoChain, SimFamily, SimServo};
+    use std::{
+        ffi::CStr,
+        fs::File,
+        io::{self, Read, Write},
+        os::fd::{AsRawFd, FromRawFd},
+        time::Instant,
+    };
+
+    // 轮询间隔（毫秒）
+    const POLL_INTERVAL_MS: i32 = 1;
+
+    // 命令行参数
+    struct Options {
+        sms_ids: Vec<u8>,
+        scscl_ids: Vec<u8>,
+        link: Option<String>,
+    }
+
+    // 创建指向从设备的符号链接，只替换已有的符号链接，不覆盖普通文件和目录
+    fn link_slave(slave_path: &str, link: &str) -> io::Result<()> {
+        match std::fs::symlink_metadata(link) {
+            Ok(metadata) if metadata.file_type().is_symlink() => std::fs::remove_file(link)?,
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} 已存在且不是符号链接", link),
+                ))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        std::os::unix::fs::symlink(slave_path, link)
+    }
+
+    fn parse_ids(value: &str) -> io::Result<Vec<u8>> {
+        value
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                s.trim().parse::<u8>().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidInput, format!("无效的舵机ID: {}", s))
+                })
+            })
+            .collect()
+    }
+
+    fn parse_args(args: Vec<String>) -> io::Result<Options> {
+        let mut options = Options {
+            sms_ids: Vec::new(),
+            scscl_ids: Vec::new(),
+            link: None,
+        };
+
+        let mut iter = args.into_iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || {
+                iter.next().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, format!("{} 缺少参数", arg))
+                })
+            };
+            match arg.as_str() {
+                "--sms" | "--sts" => options.sms_ids.extend(parse_ids(&value()?)?),
+                "--scscl" => options.scscl_ids.extend(parse_ids(&value()?)?),
+                "--link" => options.link = Some(value()?),
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("未知参数: {}", arg),
+                    ))
+                }
+            }
+        }
+
+        // 默认模拟三个 SMS/STS 舵机
+        if options.sms_ids.is_empty() && options.scscl_ids.is_empty() {
+            options.sms_ids = vec![1, 2, 3];
+        }
+        Ok(options)
+    }
+
+    // 打开 PTY 主设备，返回主设备与从设备路径
+    fn open_pty() -> io::Result<(File, String)> {
+        unsafe {
+            let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let master = File::from_raw_fd(fd);
+
+            if libc::grantpt(fd) != 0 || libc::unlockpt(fd) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut name = [0 as libc::c_char; 128];
+            if libc::ptsname_r(fd, name.as_mut_ptr(), name.len()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let path = CStr::from_ptr(name.as_ptr()).to_string_lossy().into_owned();
+            Ok((master, path))
+        }
+    }
+
+    // 将终端设置为原始模式，避免回显和换行转换
+    fn make_raw(file: &File) -> io::Result<()> {
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(file.as_raw_fd(), &mut termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            libc::cfmakeraw(&mut termios);
+            if libc::tcsetattr(file.as_raw_fd(), libc::TCSANOW, &termios) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    // 等待主设备可读
+    fn poll_readable(file: &File, timeout_ms: i32) -> io::Result<bool> {
+        let mut fds = libc::pollfd {
+            fd: file.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ret = unsafe { libc::poll(&mut fds, 1, timeout_ms) };
+        if ret < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                return Ok(false);
+            }
+            return Err(err);
+        }
+        Ok(ret > 0 && fds.revents & libc::POLLIN != 0)
+    }
+
+    pub fn run(args: Vec<String>) -> io::Result<()> {
+        let options = parse_args(args)?;
+
+        let mut chain = ServoChain::new();
+        for &id in &options.sms_ids {
+            chain.add_servo(SimServo::new(id, SimFamily::SmsSts));
+        }
+        for &id in &options.scscl_ids {
+            chain.add_servo(SimServo::new(id, SimFamily::Scscl));
+        }
+
+        let (mut master, slave_path) = open_pty()?;
+
+        // 保持从设备打开：设置原始模式，并避免客户端断开时主设备读取出错
+        let slave = File::options().read(true).write(true).open(&slave_path)?;
+        make_raw(&slave)?;
+
+        if let Some(link) = &options.link {
+            link_slave(&slave_path, link)?;
+            println!("{} -> {}", link, slave_path);
+        }
+
+        println!("{}", slave_path);
+        for servo in chain.servos() {
+            println!("  [ID:{:03}] {:?}", servo.id(), servo.family());
+        }
+        io::stdout().flush()?;
+
+        let mut buffer = [0u8; 256];
+        let mut last_update = Instant::now();
+        loop {
+            let readable = poll_readable(&master, POLL_INTERVAL_MS)?;
+
+            // 按真实时间推进运动仿真
+            let now = Instant::now();
+            chain.advance(now - last_update);
+            last_update = now;
+
+            if !readable {
+                continue;
+            }
+
+            let count = match master.read(&mut buffer) {
+                Ok(count) => count,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            for response in chain.process(&buffer[..count]) {
+                master.write_all(&response)?;
+            }
+        }
+    }
+}
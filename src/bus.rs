@@ -0,0 +1,273 @@
+//! 共享总线
+//!
+//! 同一条总线上的 `SmsSts`、`Scscl` 与 group 读写各自持有一个 `SharedBus` 克隆，
+//! 它们共用同一个已打开的端口。`SharedBus` 可以跨线程使用：一次完整的收发事务
+//! （发送指令包 + 接收对应的状态包）期间，其他句柄的访问会等待事务结束，
+//! 不同线程的字节不会在总线上交错。关闭写入应答后留下的未读取应答也登记在总线上，
+//! 由下一个收发的句柄丢弃，不会被其他克隆当作自己的应答。
+
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex, MutexGuard, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{port_handler::PortHandler, transport::Transport};
+
+// 句柄编号，用于识别事务的持有者
+static NEXT_HANDLE_ID: AtomicU64 = AtomicU64::new(1);
+
+// 当前事务的持有者
+#[derive(Debug, Default)]
+struct TransactionState {
+    owner: Option<u64>,
+    // 嵌套深度，回到 0 时释放总线
+    depth: usize,
+    // 已发出但还没有读取的应答数量及其最晚到达时间
+    deferred: Option<(usize, Instant)>,
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    transport: Mutex<T>,
+    state: Mutex<TransactionState>,
+    released: Condvar,
+}
+
+// 多个控制器共用的总线句柄，克隆后指向同一个端口
+#[derive(Debug)]
+pub struct SharedBus<T: Transport = PortHandler> {
+    shared: Arc<Shared<T>>,
+    handle_id: u64,
+}
+
+// 持有锁的线程 panic 不影响端口本身，继续使用
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<T: Transport> SharedBus<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                transport: Mutex::new(transport),
+                state: Mutex::new(TransactionState::default()),
+                released: Condvar::new(),
+            }),
+            handle_id: NEXT_HANDLE_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    // 锁定底层端口，用于打开/关闭端口、修改波特率等操作
+    // 会等待其他句柄正在进行的事务结束；持有锁期间不要在同一线程通过控制器通信
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        let mut state = lock(&self.shared.state);
+        while state.owner.is_some_and(|owner| owner != self.handle_id) {
+            state = self.shared.released.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+        lock(&self.shared.transport)
+    }
+
+    // 两个句柄是否指向同一条总线
+    pub fn same_bus(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared)
+    }
+
+    // 当前句柄是否持有事务
+    fn in_transaction(&self) -> bool {
+        lock(&self.shared.state).owner == Some(self.handle_id)
+    }
+
+    fn acquire(&self) {
+        let mut state = lock(&self.shared.state);
+        while state.owner.is_some_and(|owner| owner != self.handle_id) {
+            state = self.shared.released.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+        state.owner = Some(self.handle_id);
+        state.depth += 1;
+    }
+
+    fn release(&self) {
+        let mut state = lock(&self.shared.state);
+        if state.owner != Some(self.handle_id) {
+            return;
+        }
+        state.depth = state.depth.saturating_sub(1);
+        if state.depth == 0 {
+            state.owner = None;
+            self.shared.released.notify_all();
+        }
+    }
+
+    // 在事务内访问端口；不在事务中时只占用总线一次调用的时间
+    fn with_transport<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let implicit = !self.in_transaction();
+        if implicit {
+            self.acquire();
+        }
+        let result = f(&mut lock(&self.shared.transport));
+        if implicit {
+            self.release();
+        }
+        result
+    }
+}
+
+impl<T: Transport> Clone for SharedBus<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+            handle_id: NEXT_HANDLE_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+impl<T: Transport> Drop for SharedBus<T> {
+    // 事务未结束时句柄被丢弃（例如 panic），释放总线避免其他句柄永久等待
+    fn drop(&mut self) {
+        let mut state = lock(&self.shared.state);
+        if state.owner == Some(self.handle_id) {
+            state.owner = None;
+            state.depth = 0;
+            self.shared.released.notify_all();
+        }
+    }
+}
+
+impl<T: Transport> Transport for SharedBus<T> {
+    fn write_port(&mut self, packet: &[u8]) -> io::Result<usize> {
+        self.with_transport(|transport| transport.write_port(packet))
+    }
+
+    fn read_port_until(&mut self, buf: &mut [u8], deadline: Instant) -> io::Result<usize> {
+        self.with_transport(|transport| transport.read_port_until(buf, deadline))
+    }
+
+    fn clear_port(&mut self) -> io::Result<()> {
+        self.with_transport(|transport| transport.clear_port())
+    }
+
+    fn get_bytes_available(&self) -> io::Result<u32> {
+        self.with_transport(|transport| transport.get_bytes_available())
+    }
+
+    fn get_baudrate(&self) -> u32 {
+        self.with_transport(|transport| transport.get_baudrate())
+    }
+
+    fn set_baudrate(&mut self, baudrate: u32) -> io::Result<()> {
+        self.with_transport(|transport| transport.set_baudrate(baudrate))
+    }
+
+    fn get_tx_time_per_byte(&self) -> Duration {
+        self.with_transport(|transport| transport.get_tx_time_per_byte())
+    }
+
+    fn begin_transaction(&mut self) {
+        self.acquire();
+    }
+
+    fn end_transaction(&mut self) {
+        self.release();
+    }
+
+    fn defer_replies(&mut self, count: usize, deadline: Instant) -> bool {
+        let mut state = lock(&self.shared.state);
+        state.deferred = Some(match state.deferred {
+            Some((pending, latest)) => (pending + count, latest.max(deadline)),
+            None => (count, deadline),
+        });
+        true
+    }
+
+    fn take_deferred_replies(&mut self) -> Option<(usize, Instant)> {
+        lock(&self.shared.state).deferred.take()
+    }
+}
+
+// SharedBus 可以在线程间移动和共享
+#[allow(dead_code)]
+fn assert_send_sync() {
+    fn check<T: Send + Sync>() {}
+    check::<SharedBus<PortHandler>>();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::mpsc, thread};
+
+    use super::*;
+    use crate::{
+        packet::InstructionPacket,
+        protocol_packet_handler::{Endian, ProtocolPacketHandler},
+        sim::{sim_bus, Recorder, SimFamily},
+        sms_sts::{regs, SMS_STS_TORQUE_ENABLE},
+        SmsSts,
+    };
+
+    #[test]
+    fn threads_on_clones_send_whole_packets() {
+        let ids = [1, 2, 3, 4];
+        let bus = SharedBus::new(Recorder::new(sim_bus(SimFamily::SmsSts, &ids)));
+
+        let workers: Vec<_> = ids
+            .iter()
+            .map(|&id| {
+                let mut sms_sts = SmsSts::new(bus.clone());
+                thread::spawn(move || {
+                    for n in 0..25 {
+                        let time = id as i32 * 100 + n;
+                        sms_sts.write_reg(id as u32, regs::GOAL_TIME, time).unwrap();
+                        assert_eq!(sms_sts.read_reg(id as u32, regs::GOAL_TIME).unwrap(), time);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        let recorder = bus.lock();
+        assert_eq!(recorder.writes.len(), ids.len() * 50);
+        for write in &recorder.writes {
+            assert!(InstructionPacket::decode(write).is_ok(), "interleaved packet {:02X?}", write);
+        }
+    }
+
+    #[test]
+    fn nested_transaction_by_the_owner_does_not_deadlock() {
+        let bus = SharedBus::new(sim_bus(SimFamily::SmsSts, &[1]));
+        let mut ph = ProtocolPacketHandler::new(bus.clone(), Endian::SmallEndian);
+
+        ph.begin_transaction();
+        ph.begin_transaction();
+        assert!(ph.ping(1).is_ok());
+        ph.end_transaction();
+        assert!(ph.ping(1).is_ok());
+        ph.end_transaction();
+
+        // 事务完全结束后，其他线程的克隆可以使用总线
+        let (done, finished) = mpsc::channel();
+        let mut other = ProtocolPacketHandler::new(bus.clone(), Endian::SmallEndian);
+        thread::spawn(move || done.send(other.ping(1).is_ok()).unwrap());
+        assert_eq!(finished.recv_timeout(Duration::from_secs(1)), Ok(true));
+    }
+
+    #[test]
+    fn unread_reply_from_one_clone_is_discarded_by_another() {
+        let mut virtual_bus = sim_bus(SimFamily::SmsSts, &[1]);
+        virtual_bus.set_latency(Duration::from_millis(5));
+        let bus = SharedBus::new(virtual_bus);
+
+        let mut writer = SmsSts::new(bus.clone());
+        writer.set_write_ack(false);
+        writer.write_torque_enable(1, true).unwrap();
+
+        let mut reader = SmsSts::new(bus.clone());
+        assert_eq!(reader.read_temperature(1).unwrap(), 30);
+        assert_eq!(bus.lock().chain().servo(1).unwrap().read_byte(SMS_STS_TORQUE_ENABLE), 1);
+    }
+}
@@ -0,0 +1,550 @@
+//! EEPROM 配置的备份与恢复（需要启用 `config` feature）
+//!
+//! `ServoConfig` 按寄存器名记录一个舵机 EEPROM 中所有可写寄存器的值，可以保存为 JSON 或
+//! TOML。`SmsSts`/`Scscl` 的 `backup_config` 一次读出整块 EEPROM，`restore_config` 自动
+//! 解锁、写入并重新锁定 EEPROM。更换舵机时先备份旧舵机，再把配置恢复到新舵机上。
+//! `verify_config` 将舵机当前的 EEPROM 与配置比较，`apply_config` 只写入不一致的寄存器。
+
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    model::{family_regs, Family, FamilyRegs},
+    protocol_packet_handler::{ProtocolPacketHandler, MODEL_ADDRESS},
+    register::{Area, Register},
+    setup::{id_in_use, id_taken, relock, write_id_and_lock},
+    transport::Transport,
+    FtServoError, Result,
+};
+
+// 单个舵机的 EEPROM 配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServoConfig {
+    pub family: Family,
+    // 型号号和固件版本（主, 次），仅作记录，恢复时不写入
+    pub model: u16,
+    pub firmware: (u8, u8),
+    // 可写 EEPROM 寄存器的值，键为控制表中的寄存器名
+    pub registers: BTreeMap<String, i32>,
+}
+
+// 舵机当前值与配置不一致的寄存器
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Difference {
+    pub register: Register,
+    // 配置中的值
+    pub expected: i32,
+    // 舵机当前的值
+    pub actual: i32,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: expected {}, actual {}",
+            self.register.name, self.expected, self.actual
+        )?;
+        if !self.register.unit.is_empty() {
+            write!(f, " ({})", self.register.unit)?;
+        }
+        Ok(())
+    }
+}
+
+// 多个舵机的配置，按备份顺序排列
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BusConfig {
+    pub servos: Vec<ServoConfig>,
+}
+
+impl ServoConfig {
+    pub fn get(&self, name: &str) -> Option<i32> {
+        self.registers.get(name).copied()
+    }
+
+    pub fn set(&mut self, name: &str, value: i32) {
+        self.registers.insert(name.to_string(), value);
+    }
+
+    // 配置中记录的舵机ID
+    pub fn id(&self) -> Option<u32> {
+        self.get(family_regs(self.family).id.name).map(|id| id as u32)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        to_json(self)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        from_json(text)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        to_toml(self)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        from_toml(text)
+    }
+
+    // 按扩展名保存：.toml 为 TOML，其他为 JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        save(self, path.as_ref())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        load(path.as_ref())
+    }
+}
+
+impl BusConfig {
+    // 按ID查找配置
+    pub fn servo(&self, scs_id: u32) -> Option<&ServoConfig> {
+        self.servos.iter().find(|config| config.id() == Some(scs_id))
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        to_json(self)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        from_json(text)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        to_toml(self)
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        from_toml(text)
+    }
+
+    // 按扩展名保存：.toml 为 TOML，其他为 JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        save(self, path.as_ref())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        load(path.as_ref())
+    }
+}
+
+fn to_json<S: Serialize>(value: &S) -> Result<String> {
+    serde_json::to_string_pretty(value).map_err(|e| FtServoError::Config(e.to_string()))
+}
+
+fn from_json<D: DeserializeOwned>(text: &str) -> Result<D> {
+    serde_json::from_str(text).map_err(|e| FtServoError::Config(e.to_string()))
+}
+
+fn to_toml<S: Serialize>(value: &S) -> Result<String> {
+    toml::to_string(value).map_err(|e| FtServoError::Config(e.to_string()))
+}
+
+fn from_toml<D: DeserializeOwned>(text: &str) -> Result<D> {
+    toml::from_str(text).map_err(|e| FtServoError::Config(e.to_string()))
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+}
+
+fn save<S: Serialize>(value: &S, path: &Path) -> Result<()> {
+    let text = if is_toml(path) { to_toml(value)? } else { to_json(value)? };
+    fs::write(path, text)?;
+    Ok(())
+}
+
+fn load<D: DeserializeOwned>(path: &Path) -> Result<D> {
+    let text = fs::read_to_string(path)?;
+    if is_toml(path) {
+        from_toml(&text)
+    } else {
+        from_json(&text)
+    }
+}
+
+// 备份的寄存器：EEPROM 中可写的寄存器
+pub(crate) fn config_registers(family: Family) -> impl Iterator<Item = &'static Register> {
+    family
+        .registers()
+        .iter()
+        .filter(|reg| reg.area == Area::Eeprom && reg.is_writable())
+}
+
+// 备份时一次读出的长度：从地址 0 到最后一个可写寄存器
+fn backup_length(family: Family) -> u32 {
+    config_registers(family)
+        .map(|reg| reg.address + reg.size)
+        .max()
+        .unwrap_or(0)
+        .max(MODEL_ADDRESS + 2) as u32
+}
+
+// 解码整块 EEPROM
+fn decode_backup(family: Family, data: &[u8]) -> ServoConfig {
+    let end = family.endian();
+    let registers = config_registers(family)
+        .map(|reg| (reg.name.to_string(), reg.decode(&end, &data[reg.address as usize..])))
+        .collect();
+    let model = MODEL_ADDRESS as usize;
+    ServoConfig {
+        family,
+        model: end.make_word(data[model], data[model + 1]),
+        firmware: (data[0], data[1]),
+        registers,
+    }
+}
+
+// 一次读出整块 EEPROM
+pub(crate) fn backup<T: Transport>(ph: &mut ProtocolPacketHandler<T>, family: Family, scs_id: u32) -> Result<ServoConfig> {
+    let data = ph.read_tx_rx(scs_id, 0, backup_length(family))?;
+    Ok(decode_backup(family, &data))
+}
+
+pub(crate) fn backup_all<T: Transport>(ph: &mut ProtocolPacketHandler<T>, family: Family, scs_ids: &[u32]) -> Result<BusConfig> {
+    let servos = scs_ids
+        .iter()
+        .map(|&scs_id| backup(ph, family, scs_id))
+        .collect::<Result<_>>()?;
+    Ok(BusConfig { servos })
+}
+
+// 写入前检查全部寄存器名和取值，返回按地址排列的写入列表
+fn check_config(config: &ServoConfig, family: Family) -> Result<Vec<(Register, i32)>> {
+    if config.family != family {
+        return Err(FtServoError::InvalidParameter(format!(
+            "config is for {:?} servos, not {:?}",
+            config.family, family
+        )));
+    }
+
+    let mut writes = Vec::with_capacity(config.registers.len());
+    for (name, &value) in &config.registers {
+        let reg = config_registers(family)
+            .find(|reg| reg.name == name)
+            .ok_or_else(|| FtServoError::InvalidParameter(format!("unknown EEPROM register {}", name)))?;
+        reg.check_write(value)?;
+        writes.push((*reg, value));
+    }
+    writes.sort_by_key(|(reg, _)| reg.address);
+    Ok(writes)
+}
+
+// 写入列表中的ID，没有时为当前ID
+fn target_id(regs: &FamilyRegs, scs_id: u32, writes: &[(Register, i32)]) -> u32 {
+    writes
+        .iter()
+        .find(|(reg, _)| *reg == regs.id)
+        .map_or(scs_id, |&(_, id)| id as u32)
+}
+
+// 解锁后写入的寄存器：ID 最后单独写入，波特率需要与端口一起切换，不写入
+fn unlocked_writes<'a>(regs: &'a FamilyRegs, writes: &'a [(Register, i32)]) -> impl Iterator<Item = (Register, i32)> + 'a {
+    writes
+        .iter()
+        .copied()
+        .filter(|(reg, _)| *reg != regs.id && *reg != regs.baud_rate)
+}
+
+// 配置中的波特率，恢复时不写入
+fn baud_rate(regs: &FamilyRegs, writes: &[(Register, i32)]) -> Option<i32> {
+    writes.iter().find(|(reg, _)| *reg == regs.baud_rate).map(|&(_, value)| value)
+}
+
+// 没有写入的波特率与舵机当前值不同时作为差异返回，由调用者用 change_baud 与端口一起切换
+fn skipped_baud_rate(regs: &FamilyRegs, expected: i32, actual: i32) -> Vec<Difference> {
+    if expected == actual {
+        return Vec::new();
+    }
+    vec![Difference { register: regs.baud_rate, expected, actual }]
+}
+
+// 按配置中记录的ID
+fn config_id(config: &ServoConfig) -> Result<u32> {
+    config
+        .id()
+        .ok_or_else(|| FtServoError::InvalidParameter("servo config has no ID".to_string()))
+}
+
+// 舵机当前值与配置不一致的寄存器
+fn differences(expected: Vec<(Register, i32)>, live: &ServoConfig) -> Vec<Difference> {
+    expected
+        .into_iter()
+        .filter_map(|(register, expected)| {
+            let actual = live.registers[register.name];
+            (actual != expected).then_some(Difference { register, expected, actual })
+        })
+        .collect()
+}
+
+// apply 写入的差异：ID 和波特率除外，模板配置不应改变舵机的地址，修改时使用 change_id/change_baud
+fn applied(regs: &FamilyRegs, differences: Vec<Difference>) -> (Vec<Difference>, Vec<(Register, i32)>) {
+    let differences: Vec<Difference> = differences
+        .into_iter()
+        .filter(|difference| difference.register != regs.id && difference.register != regs.baud_rate)
+        .collect();
+    let writes = differences
+        .iter()
+        .map(|difference| (difference.register, difference.expected))
+        .collect();
+    (differences, writes)
+}
+
+// 解锁 EEPROM，按地址顺序写入后重新锁定
+// ID 最后写入，舵机随后以新ID应答；目标ID已有舵机时不写入任何寄存器
+fn write_registers<T: Transport>(
+    ph: &mut ProtocolPacketHandler<T>,
+    family: Family,
+    scs_id: u32,
+    writes: &[(Register, i32)],
+) -> Result<()> {
+    let regs = family_regs(family);
+    let new_id = target_id(&regs, scs_id, writes);
+    if new_id != scs_id && id_in_use(ph, new_id)? {
+        return Err(id_taken(new_id));
+    }
+
+    ph.write_reg(scs_id, regs.lock, 0)?;
+    let result = unlocked_writes(&regs, writes).try_for_each(|(reg, value)| ph.write_reg(scs_id, reg, value).map(|_| ()));
+    if let Err(e) = result {
+        return Err(relock(ph, &regs, scs_id, e));
+    }
+
+    write_id_and_lock(ph, &regs, scs_id, new_id)
+}
+
+// 返回没有写入的差异（与舵机当前值不同的波特率）
+pub(crate) fn restore<T: Transport>(
+    ph: &mut ProtocolPacketHandler<T>,
+    family: Family,
+    scs_id: u32,
+    config: &ServoConfig,
+) -> Result<Vec<Difference>> {
+    let writes = check_config(config, family)?;
+    let regs = family_regs(family);
+    let skipped = match baud_rate(&regs, &writes) {
+        Some(expected) => skipped_baud_rate(&regs, expected, ph.read_reg(scs_id, regs.baud_rate)?),
+        None => Vec::new(),
+    };
+    write_registers(ph, family, scs_id, &writes)?;
+    Ok(skipped)
+}
+
+// 按配置中记录的ID恢复每个舵机，返回各舵机没有写入的差异
+pub(crate) fn restore_all<T: Transport>(
+    ph: &mut ProtocolPacketHandler<T>,
+    family: Family,
+    config: &BusConfig,
+) -> Result<Vec<(u32, Difference)>> {
+    let mut skipped = Vec::new();
+    for servo in &config.servos {
+        let scs_id = config_id(servo)?;
+        skipped.extend(restore(ph, family, scs_id, servo)?.into_iter().map(|difference| (scs_id, difference)));
+    }
+    Ok(skipped)
+}
+
+// 读取舵机的 EEPROM，与配置中的每个寄存器比较
+pub(crate) fn verify<T: Transport>(
+    ph: &mut ProtocolPacketHandler<T>,
+    family: Family,
+    scs_id: u32,
+    config: &ServoConfig,
+) -> Result<Vec<Difference>> {
+    let expected = check_config(config, family)?;
+    let live = backup(ph, family, scs_id)?;
+    Ok(differences(expected, &live))
+}
+
+// 只写入与配置不一致的寄存器，返回写入的差异；没有差异时不解锁 EEPROM
+pub(crate) fn apply<T: Transport>(
+    ph: &mut ProtocolPacketHandler<T>,
+    family: Family,
+    scs_id: u32,
+    config: &ServoConfig,
+) -> Result<Vec<Difference>> {
+    let (differences, writes) = applied(&family_regs(family), verify(ph, family, scs_id, config)?);
+    if !differences.is_empty() {
+        write_registers(ph, family, scs_id, &writes)?;
+    }
+    Ok(differences)
+}
+
+// 异步客户端的相同流程，读取的解码、配置检查和写入顺序与上面共用
+#[cfg(feature = "async")]
+pub(crate) mod asynchronous {
+    use tokio::io::{AsyncRead, AsyncWrite};
+
+    use super::*;
+    use crate::{
+        async_client::AsyncPacketHandler,
+        setup::asynchronous::{id_in_use, relock, write_id_and_lock},
+    };
+
+    pub(crate) async fn backup<S: AsyncRead + AsyncWrite + Unpin>(
+        ph: &mut AsyncPacketHandler<S>,
+        family: Family,
+        scs_id: u32,
+    ) -> Result<ServoConfig> {
+        let data = ph.read_tx_rx(scs_id, 0, backup_length(family)).await?;
+        Ok(decode_backup(family, &data))
+    }
+
+    pub(crate) async fn backup_all<S: AsyncRead + AsyncWrite + Unpin>(
+        ph: &mut AsyncPacketHandler<S>,
+        family: Family,
+        scs_ids: &[u32],
+    ) -> Result<BusConfig> {
+        let mut servos = Vec::with_capacity(scs_ids.len());
+        for &scs_id in scs_ids {
+            servos.push(backup(ph, family, scs_id).await?);
+        }
+        Ok(BusConfig { servos })
+    }
+
+    async fn write_registers<S: AsyncRead + AsyncWrite + Unpin>(
+        ph: &mut AsyncPacketHandler<S>,
+        family: Family,
+        scs_id: u32,
+        writes: &[(Register, i32)],
+    ) -> Result<()> {
+        let regs = family_regs(family);
+        let new_id = target_id(&regs, scs_id, writes);
+        if new_id != scs_id && id_in_use(ph, new_id).await? {
+            return Err(id_taken(new_id));
+        }
+
+        ph.write_reg(scs_id, regs.lock, 0).await?;
+        for (reg, value) in unlocked_writes(&regs, writes) {
+            if let Err(e) = ph.write_reg(scs_id, reg, value).await {
+                return Err(relock(ph, &regs, scs_id, e).await);
+            }
+        }
+
+        write_id_and_lock(ph, &regs, scs_id, new_id).await
+    }
+
+    pub(crate) async fn restore<S: AsyncRead + AsyncWrite + Unpin>(
+        ph: &mut AsyncPacketHandler<S>,
+        family: Family,
+        scs_id: u32,
+        config: &ServoConfig,
+    ) -> Result<Vec<Difference>> {
+        let writes = check_config(config, family)?;
+        let regs = family_regs(family);
+        let skipped = match baud_rate(&regs, &writes) {
+            Some(expected) => skipped_baud_rate(&regs, expected, ph.read_reg(scs_id, regs.baud_rate).await?),
+            None => Vec::new(),
+        };
+        write_registers(ph, family, scs_id, &writes).await?;
+        Ok(skipped)
+    }
+
+    pub(crate) async fn restore_all<S: AsyncRead + AsyncWrite + Unpin>(
+        ph: &mut AsyncPacketHandler<S>,
+        family: Family,
+        config: &BusConfig,
+    ) -> Result<Vec<(u32, Difference)>> {
+        let mut skipped = Vec::new();
+        for servo in &config.servos {
+            let scs_id = config_id(servo)?;
+            skipped.extend(restore(ph, family, scs_id, servo).await?.into_iter().map(|difference| (scs_id, difference)));
+        }
+        Ok(skipped)
+    }
+
+    pub(crate) async fn verify<S: AsyncRead + AsyncWrite + Unpin>(
+        ph: &mut AsyncPacketHandler<S>,
+        family: Family,
+        scs_id: u32,
+        config: &ServoConfig,
+    ) -> Result<Vec<Difference>> {
+        let expected = check_config(config, family)?;
+        let live = backup(ph, family, scs_id).await?;
+        Ok(differences(expected, &live))
+    }
+
+    pub(crate) async fn apply<S: AsyncRead + AsyncWrite + Unpin>(
+        ph: &mut AsyncPacketHandler<S>,
+        family: Family,
+        scs_id: u32,
+        config: &ServoConfig,
+    ) -> Result<Vec<Difference>> {
+        let (differences, writes) = applied(&family_regs(family), verify(ph, family, scs_id, config).await?);
+        if !differences.is_empty() {
+            write_registers(ph, family, scs_id, &writes).await?;
+        }
+        Ok(differences)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BusConfig;
+    use crate::{
+        sim::{sim_bus, SimFamily},
+        sms_sts::regs,
+        FtServoError, SmsSts,
+    };
+
+    #[test]
+    fn restore_refuses_to_take_an_id_in_use() {
+        let mut sms_sts = SmsSts::new(sim_bus(SimFamily::SmsSts, &[1, 2]));
+        let mut config = sms_sts.backup_config(1).unwrap();
+        config.set("ID", 2);
+        config.set("OFS", -50);
+
+        let result = sms_sts.restore_config(1, &config);
+        assert!(matches!(result, Err(FtServoError::InvalidParameter(_))));
+        assert_eq!(sms_sts.read_reg(1, regs::OFS).unwrap(), 0);
+        assert_eq!(sms_sts.read_reg(1, regs::LOCK).unwrap(), 1);
+        assert_eq!(sms_sts.ping(2).unwrap().id, 2);
+    }
+
+    #[test]
+    fn restore_moves_the_servo_to_a_free_id() {
+        let mut sms_sts = SmsSts::new(sim_bus(SimFamily::SmsSts, &[1]));
+        let mut config = sms_sts.backup_config(1).unwrap();
+        config.set("ID", 3);
+        config.set("OFS", -50);
+
+        assert!(sms_sts.restore_config(1, &config).unwrap().is_empty());
+        assert_eq!(sms_sts.read_reg(3, regs::OFS).unwrap(), -50);
+        assert_eq!(sms_sts.read_reg(3, regs::LOCK).unwrap(), 1);
+    }
+
+    #[test]
+    fn restore_reports_the_baud_rate_it_skips() {
+        let mut sms_sts = SmsSts::new(sim_bus(SimFamily::SmsSts, &[1, 2]));
+        let mut config = sms_sts.backup_config(1).unwrap();
+        config.set("BAUD_RATE", 4);
+        config.set("OFS", -50);
+
+        let skipped = sms_sts.restore_config(1, &config).unwrap();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!((skipped[0].register, skipped[0].expected, skipped[0].actual), (regs::BAUD_RATE, 4, 0));
+        assert_eq!(sms_sts.read_reg(1, regs::BAUD_RATE).unwrap(), 0);
+        assert_eq!(sms_sts.read_reg(1, regs::OFS).unwrap(), -50);
+
+        let bus = BusConfig { servos: vec![config, sms_sts.backup_config(2).unwrap()] };
+        let skipped = sms_sts.restore_configs(&bus).unwrap();
+        assert_eq!(skipped.iter().map(|(id, difference)| (*id, difference.register)).collect::<Vec<_>>(), [(1, regs::BAUD_RATE)]);
+    }
+
+    #[test]
+    fn apply_leaves_the_id_alone() {
+        let mut sms_sts = SmsSts::new(sim_bus(SimFamily::SmsSts, &[1]));
+        let mut profile = sms_sts.backup_config(1).unwrap();
+        profile.set("ID", 4);
+        profile.set("OFS", -50);
+
+        let differences = sms_sts.apply_config(1, &profile).unwrap();
+        let written: Vec<&str> = differences.iter().map(|difference| difference.register.name).collect();
+        assert_eq!(written, ["OFS"]);
+        assert_eq!(sms_sts.read_reg(1, regs::OFS).unwrap(), -50);
+        assert!(sms_sts.ping(4).is_err());
+    }
+}
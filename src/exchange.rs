@@ -0,0 +1,220 @@
+//! 收发流程中与 I/O 无关的部分
+//!
+//! `Request` 描述一条指令的参数和期望的应答，`Exchange` 持有发送缓冲区和状态包解码器，
+//! 负责编码指令、记录尚未读取的应答并校验收到的状态包。同步的 `ProtocolPacketHandler`
+//! 与异步客户端共用这些逻辑，各自只负责读写字节和计时。
+
+use crate::{
+    packet::{encode_instruction, StatusDecoder, StatusPacket, MIN_PACKET_LEN, RXPACKET_MAX_LEN, TXPACKET_MAX_LEN},
+    protocol_packet_handler::Endian,
+    scservo_def::{ServoStatus, BROADCAST_ID, COMM, INST},
+    FtServoError, Result,
+};
+
+// 单播指令期望的应答
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Reply {
+    // 写入类指令的确认，不带数据，关闭 write_ack 时不等待
+    Ack,
+    // 带指定长度数据的应答，Ping 为 0
+    Data(usize),
+}
+
+// 一条指令：参数为 前缀 + 数据，两段直接编码到发送缓冲区
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Request<'a> {
+    pub id: u8,
+    pub instruction: INST,
+    prefix: [u8; 2],
+    prefix_len: usize,
+    data: &'a [u8],
+    pub reply: Reply,
+}
+
+impl<'a> Request<'a> {
+    fn new(id: u8, instruction: INST, prefix: &[u8], data: &'a [u8], reply: Reply) -> Self {
+        let mut buf = [0u8; 2];
+        buf[..prefix.len()].copy_from_slice(prefix);
+        Self {
+            id,
+            instruction,
+            prefix: buf,
+            prefix_len: prefix.len(),
+            data,
+            reply,
+        }
+    }
+
+    // Ping，只能发给单个舵机
+    pub fn ping(scs_id: u32) -> Result<Self> {
+        if scs_id as u8 == BROADCAST_ID {
+            return Err(FtServoError::InvalidParameter("ping needs a single servo id".to_string()));
+        }
+        Ok(Self::new(scs_id as u8, INST::Ping, &[], &[], Reply::Data(0)))
+    }
+
+    // 从 address 读取 length 个字节，应答装不下时返回 InvalidParameter
+    pub fn read(scs_id: u32, address: u32, length: u32) -> Result<Self> {
+        if length == 0 || length as usize + MIN_PACKET_LEN > RXPACKET_MAX_LEN {
+            return Err(FtServoError::InvalidParameter(format!("invalid read length {}", length)));
+        }
+        let prefix = [address as u8, length as u8];
+        Ok(Self::new(scs_id as u8, INST::Read, &prefix, &[], Reply::Data(length as usize)))
+    }
+
+    // 写入类指令（Write、RegWrite）：地址 + 数据
+    pub fn write(scs_id: u32, instruction: INST, address: u32, data: &'a [u8]) -> Result<Self> {
+        if data.is_empty() {
+            return Err(FtServoError::InvalidParameter("no data to write".to_string()));
+        }
+        Ok(Self::new(scs_id as u8, instruction, &[address as u8], data, Reply::Ack))
+    }
+
+    pub fn action(scs_id: u32) -> Self {
+        Self::new(scs_id as u8, INST::Action, &[], &[], Reply::Ack)
+    }
+
+    // 广播指令（SyncWrite、SyncRead、BulkRead）：前缀 + 各舵机的参数
+    pub fn broadcast(instruction: INST, prefix: &[u8], data: &'a [u8]) -> Self {
+        Self::new(BROADCAST_ID, instruction, prefix, data, Reply::Ack)
+    }
+
+    pub fn parts(&self) -> [&[u8]; 2] {
+        [&self.prefix[..self.prefix_len], self.data]
+    }
+
+    // 通信失败时带舵机ID和指令的错误
+    pub fn error(&self, result: COMM) -> FtServoError {
+        FtServoError::communication(self.id, self.instruction, result)
+    }
+
+    // 应答的参数长度必须与期望一致；strict 时舵机报告的故障返回 Hardware，
+    // Ping 的结果中本身带有状态，不受影响
+    pub fn check<'p>(&self, packet: StatusPacket<'p>, strict: bool) -> Result<StatusPacket<'p>> {
+        let expected = match self.reply {
+            Reply::Ack => 0,
+            Reply::Data(length) => length,
+        };
+        if packet.params.len() != expected {
+            return Err(self.error(COMM::RxCorrupt));
+        }
+        if strict && self.instruction != INST::Ping && !packet.status().is_empty() {
+            return Err(FtServoError::Hardware {
+                id: self.id,
+                instruction: self.instruction,
+                status: packet.status(),
+            });
+        }
+        Ok(packet)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Exchange {
+    pub scs_end: Endian,
+    // 发送缓冲区，避免每次通信分配内存
+    tx_buf: [u8; TXPACKET_MAX_LEN],
+    // 状态包解码器，保留同一次读取中多余的数据包
+    pub decoder: StatusDecoder,
+    pub last_status: ServoStatus,
+    // 单播写入是否等待状态包
+    pub write_ack: bool,
+    // 舵机报告故障时是否返回 Hardware 错误
+    pub strict_status: bool,
+    // 已发出但没有读取应答的单播指令数量，它们的状态包仍可能到达
+    stale_replies: usize,
+}
+
+impl Exchange {
+    pub fn new(scs_end: Endian) -> Self {
+        Self {
+            scs_end,
+            tx_buf: [0; TXPACKET_MAX_LEN],
+            decoder: StatusDecoder::new(),
+            last_status: ServoStatus::empty(),
+            write_ack: true,
+            strict_status: false,
+            stale_replies: 0,
+        }
+    }
+
+    // 是否需要接收应答：广播不应答，关闭写入应答时写入类指令只发送
+    pub fn expects_reply(&self, request: &Request) -> bool {
+        request.id != BROADCAST_ID && (request.reply != Reply::Ack || self.write_ack)
+    }
+
+    // 将指令编码到发送缓冲区，返回数据包长度；超出最大长度时返回 TxError
+    pub fn encode(&mut self, id: u8, instruction: INST, parts: &[&[u8]]) -> std::result::Result<usize, COMM> {
+        encode_instruction(id, instruction, parts, &mut self.tx_buf).map_err(|e| e.comm())
+    }
+
+    // 已编码的数据包
+    pub fn frame(&self, length: usize) -> &[u8] {
+        &self.tx_buf[..length]
+    }
+
+    // 记录一条已发出的指令，单播指令都会得到应答，返回是否会有应答
+    pub fn sent(&mut self, id: u8) -> bool {
+        if id == BROADCAST_ID {
+            return false;
+        }
+        self.stale_replies += 1;
+        true
+    }
+
+    pub fn has_stale(&self) -> bool {
+        self.stale_replies > 0
+    }
+
+    pub fn stale(&self) -> usize {
+        self.stale_replies
+    }
+
+    // 接管其他句柄留下的未读取应答
+    pub fn add_stale(&mut self, count: usize) {
+        self.stale_replies += count;
+    }
+
+    // 收到的数据中属于之前指令的状态包直接丢弃
+    pub fn discard(&mut self, bytes: &[u8]) {
+        self.decoder.push(bytes);
+        while self.stale_replies > 0 && self.decoder.next_frame().is_some() {
+            self.stale_replies -= 1;
+        }
+    }
+
+    // 应答已收到或已超时，不再丢弃
+    pub fn forget_stale(&mut self) {
+        self.stale_replies = 0;
+    }
+
+    // 收到了一条应答
+    pub fn reply_received(&mut self) {
+        self.stale_replies = self.stale_replies.saturating_sub(1);
+    }
+
+    // 超时原因：没有收到任何数据为 RxTimeout，只收到噪声或不完整的数据包为 RxCorrupt
+    pub fn rx_failure(&self) -> COMM {
+        if self.decoder.discarded() == 0 && self.decoder.buffered() == 0 {
+            COMM::RxTimeout
+        } else {
+            COMM::RxCorrupt
+        }
+    }
+
+    // 取出解码器中已收到的数据包，记录其中的舵机状态
+    pub fn take_packet(&mut self) -> std::result::Result<StatusPacket<'_>, COMM> {
+        let packet = self.decoder.next_packet().ok_or(COMM::RxCorrupt)?;
+        self.last_status = packet.status();
+        Ok(packet)
+    }
+
+    // 取出对 id 的应答，ID 不一致时为 RxCorrupt
+    pub fn take_reply(&mut self, id: u8) -> std::result::Result<StatusPacket<'_>, COMM> {
+        let packet = self.take_packet()?;
+        if packet.id != id {
+            return Err(COMM::RxCorrupt);
+        }
+        Ok(packet)
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use crate::{
+    packet::{MIN_PACKET_LEN, RXPACKET_MAX_LEN},
+    port_handler::PortHandler,
+    protocol_packet_handler::{make_dword, sign_magnitude_to_host, Endian, ProtocolPacketHandler, SyncReadReply},
+    scservo_def::{ServoStatus, COMM, INST},
+    transport::Transport,
+    FtServoError, Result,
+};
+
+// 单个舵机的读取范围和最近一次的结果
+#[derive(Debug)]
+struct BulkReadParam {
+    start_address: u32,
+    data_length: u32,
+    // 解析数据使用的字节序，同一条总线上可以混合 SMS/STS 与 SCSCL 舵机
+    end: Endian,
+    reply: SyncReadReply,
+}
+
+// 批量读取：一条指令读取多个舵机，每个舵机有各自的起始地址和长度
+#[derive(Debug)]
+pub struct GroupBulkRead<T: Transport = PortHandler> {
+    pub ph: ProtocolPacketHandler<T>,
+
+    last_result: bool,
+    // 按添加顺序排列的舵机ID
+    param: Vec<u32>,
+    data_dict: HashMap<u32, BulkReadParam>,
+}
+
+impl<T: Transport> GroupBulkRead<T> {
+    pub fn new(ph: ProtocolPacketHandler<T>) -> Self {
+        Self {
+            ph,
+            last_result: false,
+            param: Vec::new(),
+            data_dict: HashMap::new(),
+        }
+    }
+
+    // 添加舵机，数据按协议处理器的字节序解析
+    pub fn add_param(&mut self, scs_id: u32, start_address: u32, data_length: u32) -> Result<()> {
+        let end = self.ph.scs_getend();
+        self.add_param_with_endian(scs_id, start_address, data_length, end)
+    }
+
+    // 添加舵机并指定其字节序：SMS/STS 为小端，SCSCL 为大端
+    pub fn add_param_with_endian(&mut self, scs_id: u32, start_address: u32, data_length: u32, end: Endian) -> Result<()> {
+        // 起始地址和长度在指令中各占一个字节，应答的状态包不能超过接收缓冲区
+        if data_length == 0
+            || start_address + data_length > 0x100
+            || data_length as usize + MIN_PACKET_LEN > RXPACKET_MAX_LEN
+        {
+            return Err(FtServoError::InvalidParameter(format!(
+                "invalid read range: address {} length {}",
+                start_address, data_length
+            )));
+        }
+
+        match self.data_dict.entry(scs_id) {
+            Entry::Occupied(_) => Err(FtServoError::InvalidParameter(format!(
+                "SCS ID {} already exists",
+                scs_id
+            ))),
+            Entry::Vacant(entry) => {
+                entry.insert(BulkReadParam {
+                    start_address,
+                    data_length,
+                    end,
+                    reply: Err(COMM::RxWaiting),
+                });
+                self.param.push(scs_id);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn remove_param(&mut self, scs_id: u32) -> Result<()> {
+        if self.data_dict.remove(&scs_id).is_some() {
+            self.param.retain(|&x| x != scs_id);
+            Ok(())
+        } else {
+            Err(FtServoError::InvalidParameter(format!(
+                "SCS ID {} is not found",
+                scs_id
+            )))
+        }
+    }
+
+    pub fn clear_param(&mut self) {
+        self.data_dict.clear();
+        self.param.clear();
+    }
+
+    pub fn tx_packet(&mut self) -> Result<()> {
+        if self.data_dict.is_empty() {
+            return Err(FtServoError::InvalidParameter("no servo ids added".to_string()));
+        }
+
+        self.last_result = false;
+        let mut param = Vec::with_capacity(self.param.len() * 3);
+        for scs_id in &self.param {
+            if let Some(entry) = self.data_dict.get_mut(scs_id) {
+                entry.reply = Err(COMM::RxWaiting);
+                param.extend_from_slice(&[entry.data_length as u8, *scs_id as u8, entry.start_address as u8]);
+            }
+        }
+        self.ph.bulk_read_tx(&param)
+    }
+
+    // 接收应答，个别舵机没有应答不会影响其他舵机的数据
+    pub fn rx_packet(&mut self) -> Result<()> {
+        self.last_result = false;
+
+        if self.data_dict.is_empty() {
+            return Err(FtServoError::InvalidParameter("no servo ids added".to_string()));
+        }
+
+        let expected: Vec<(u32, u32)> = self
+            .param
+            .iter()
+            .map(|scs_id| (*scs_id, self.data_dict[scs_id].data_length))
+            .collect();
+        let replies = self.ph.bulk_read_rx(&expected);
+
+        self.last_result = replies.iter().all(|reply| reply.is_ok());
+        for ((scs_id, _), reply) in expected.into_iter().zip(replies) {
+            if let Some(entry) = self.data_dict.get_mut(&scs_id) {
+                entry.reply = reply;
+            }
+        }
+        Ok(())
+    }
+
+    // 发送和接收作为一次事务，共享总线上其他线程的通信不会插入其间
+    pub fn tx_rx_packet(&mut self) -> Result<()> {
+        self.ph.begin_transaction();
+        let result = self.tx_packet().and_then(|()| self.rx_packet());
+        self.ph.end_transaction();
+        result
+    }
+
+    // 上一次接收中所有舵机是否都应答
+    pub fn last_result(&self) -> bool {
+        self.last_result
+    }
+
+    // 数据是否可用，同时返回该舵机应答中的状态
+    pub fn is_available(&self, scs_id: u32, address: u32, data_length: u32) -> (bool, ServoStatus) {
+        match self.data(scs_id, address, data_length) {
+            Ok(_) => (true, self.get_status(scs_id).unwrap_or_default()),
+            Err(_) => (false, ServoStatus::empty()),
+        }
+    }
+
+    // 舵机应答中的状态；没有应答或应答损坏时返回对应的通信错误
+    pub fn get_status(&self, scs_id: u32) -> Result<ServoStatus> {
+        self.reply(scs_id).map(|(status, _, _)| status)
+    }
+
+    fn reply(&self, scs_id: u32) -> Result<(ServoStatus, &[u8], &BulkReadParam)> {
+        let entry = self.data_dict.get(&scs_id).ok_or_else(|| {
+            FtServoError::InvalidParameter(format!("SCS ID {} is not found", scs_id))
+        })?;
+        match &entry.reply {
+            Ok((status, data)) => Ok((*status, data, entry)),
+            Err(result) => Err(FtServoError::communication(scs_id as u8, INST::BulkRead, *result)),
+        }
+    }
+
+    // 取出指定地址开始的数据和该舵机的字节序，检查地址范围和读取结果
+    fn data(&self, scs_id: u32, address: u32, data_length: u32) -> Result<(&[u8], &Endian)> {
+        let (_, data, entry) = self.reply(scs_id)?;
+        if address < entry.start_address
+            || address + data_length > entry.start_address + entry.data_length
+        {
+            return Err(FtServoError::InvalidParameter(format!(
+                "address {} length {} is outside the read range of SCS ID {}",
+                address, data_length, scs_id
+            )));
+        }
+
+        let offset = (address - entry.start_address) as usize;
+        Ok((&data[offset..offset + data_length as usize], &entry.end))
+    }
+
+    pub fn get_1byte(&self, scs_id: u32, address: u32) -> Result<u8> {
+        Ok(self.data(scs_id, address, 1)?.0[0])
+    }
+
+    pub fn get_2byte(&self, scs_id: u32, address: u32) -> Result<u16> {
+        let (data, end) = self.data(scs_id, address, 2)?;
+        Ok(end.make_word(data[0], data[1]))
+    }
+
+    pub fn get_4byte(&self, scs_id: u32, address: u32) -> Result<u32> {
+        let (data, end) = self.data(scs_id, address, 4)?;
+        Ok(make_dword(end, [data[0], data[1], data[2], data[3]]))
+    }
+
+    // 两字节的有符号数据（符号-数值编码），sign_bit 为符号位：位置、速度为 15，负载为 10
+    pub fn get_signed(&self, scs_id: u32, address: u32, sign_bit: u32) -> Result<i32> {
+        let word = self.get_2byte(scs_id, address)?;
+        Ok(sign_magnitude_to_host(word as i32, sign_bit as i32))
+    }
+
+    // 按长度（1、2、4字节）取出数据，不可用时返回 None
+    pub fn get_data(&self, scs_id: u32, address: u32, data_length: u32) -> Option<u32> {
+        match data_length {
+            1 => self.get_1byte(scs_id, address).ok().map(u32::from),
+            2 => self.get_2byte(scs_id, address).ok().map(u32::from),
+            4 => self.get_4byte(scs_id, address).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        scscl::SCSCL_GOAL_TIME_L,
+        sim::{sim_bus, ServoChain, SimFamily, SimServo, VirtualBus},
+        sms_sts::{SMS_STS_GOAL_POSITION_L, SMS_STS_GOAL_TIME_L, SMS_STS_PRESENT_TEMPERATURE},
+    };
+
+    #[test]
+    fn add_param_rejects_replies_longer_than_a_status_packet() {
+        let ph = ProtocolPacketHandler::new(sim_bus(SimFamily::SmsSts, &[]), Endian::SmallEndian);
+        let mut group = GroupBulkRead::new(ph);
+
+        let max = (RXPACKET_MAX_LEN - MIN_PACKET_LEN) as u32;
+        assert!(group.add_param(1, 0, max).is_ok());
+        assert!(matches!(group.add_param(2, 0, max + 1), Err(FtServoError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn reads_each_servo_range_and_skips_a_missing_servo() {
+        let mut chain = ServoChain::new();
+        chain.add_servo(SimServo::new(1, SimFamily::SmsSts));
+        chain.add_servo(SimServo::new(2, SimFamily::SmsSts));
+        chain.add_servo(SimServo::new(5, SimFamily::Scscl));
+        chain.servo_mut(2).unwrap().write_word(SMS_STS_GOAL_TIME_L, 300);
+        chain.servo_mut(5).unwrap().write_word(SCSCL_GOAL_TIME_L, 500);
+        let ph = ProtocolPacketHandler::new(VirtualBus::new(chain), Endian::SmallEndian);
+        let mut group = GroupBulkRead::new(ph);
+
+        group.add_param(1, SMS_STS_PRESENT_TEMPERATURE as u32, 1).unwrap();
+        group.add_param(9, SMS_STS_PRESENT_TEMPERATURE as u32, 1).unwrap();
+        group.add_param(2, SMS_STS_GOAL_POSITION_L as u32, 4).unwrap();
+        group.add_param_with_endian(5, SCSCL_GOAL_TIME_L as u32, 2, Endian::BigEndian).unwrap();
+        group.tx_rx_packet().unwrap();
+
+        assert!(!group.last_result());
+        assert_eq!(group.get_1byte(1, SMS_STS_PRESENT_TEMPERATURE as u32).unwrap(), 30);
+        assert_eq!(group.get_2byte(2, SMS_STS_GOAL_TIME_L as u32).unwrap(), 300);
+        assert_eq!(group.get_2byte(5, SCSCL_GOAL_TIME_L as u32).unwrap(), 500);
+        assert!(matches!(group.get_status(9), Err(FtServoError::Timeout { id: 9, .. })));
+        assert!(!group.is_available(9, SMS_STS_PRESENT_TEMPERATURE as u32, 1).0);
+        // 每个舵机只能取出自己的读取范围
+        assert!(!group.is_available(1, SMS_STS_GOAL_TIME_L as u32, 2).0);
+    }
+}
@@ -1,64 +1,62 @@
-use std::{
-    collections::HashMap,
-    io::{Error, ErrorKind},
-};
+use std::collections::{hash_map::Entry, HashMap};
 
-use crate::{protocol_packet_handler::ProtocolPacketHandler, scservo_def::COMM};
+use crate::{
+    port_handler::PortHandler,
+    protocol_packet_handler::{make_dword, sign_magnitude_to_host, ProtocolPacketHandler, SyncReadReply},
+    scservo_def::{ServoStatus, COMM, INST},
+    transport::Transport,
+    FtServoError, Result,
+};
 
 #[derive(Debug)]
-pub struct GroupSyncRead {
-    pub ph: ProtocolPacketHandler,
+pub struct GroupSyncRead<T: Transport = PortHandler> {
+    pub ph: ProtocolPacketHandler<T>,
     start_address: u32,
     data_length: u32,
 
     last_result: bool,
-    is_param_changed: bool,
-    param: Vec<u32>,
-    data_dict: HashMap<u32, Vec<u32>>,
+    // 按添加顺序排列的舵机ID
+    param: Vec<u8>,
+    // 每个舵机最近一次的读取结果，发送后尚未接收时为 RxWaiting
+    data_dict: HashMap<u32, SyncReadReply>,
 }
 
-impl GroupSyncRead {
-    pub fn new(ph: ProtocolPacketHandler, start_address: u32, data_length: u32) -> Self {
+impl<T: Transport> GroupSyncRead<T> {
+    pub fn new(ph: ProtocolPacketHandler<T>, start_address: u32, data_length: u32) -> Self {
         Self {
             ph,
             start_address,
             data_length,
             last_result: false,
-            is_param_changed: false,
             param: Vec::new(),
             data_dict: HashMap::new(),
         }
     }
 
-    pub fn make_param(&mut self) {
-        if !self.data_dict.is_empty() {
-            self.param = self.data_dict.keys().cloned().collect();
-        }
-    }
-
-    pub fn add_param(&mut self, scs_id: u32) -> Result<(), Error> {
-        if self.data_dict.contains_key(&scs_id) {
-            Err(Error::new(
-                ErrorKind::AlreadyExists,
-                "SCS ID already exists",
-            ))
-        } else {
-            self.data_dict
-                .insert(scs_id, vec![0; self.data_length as usize]);
-            self.param.push(scs_id);
-            self.is_param_changed = true;
-            Ok(())
+    pub fn add_param(&mut self, scs_id: u32) -> Result<()> {
+        match self.data_dict.entry(scs_id) {
+            Entry::Occupied(_) => Err(FtServoError::InvalidParameter(format!(
+                "SCS ID {} already exists",
+                scs_id
+            ))),
+            Entry::Vacant(entry) => {
+                entry.insert(Err(COMM::RxWaiting));
+                self.param.push(scs_id as u8);
+                Ok(())
+            }
         }
     }
 
-    pub fn remove_param(&mut self, scs_id: u32) -> Result<(), Error> {
+    pub fn remove_param(&mut self, scs_id: u32) -> Result<()> {
         if self.data_dict.contains_key(&scs_id) {
             self.data_dict.remove(&scs_id);
-            self.param.retain(|&x| x != scs_id);
-            self.is_param_changed = true;
+            self.param.retain(|&x| x as u32 != scs_id);
             Ok(())
         } else {
-            Err(Error::new(ErrorKind::NotFound, "param is not found"))
+            Err(FtServoError::InvalidParameter(format!(
+                "SCS ID {} is not found",
+                scs_id
+            )))
         }
     }
 
@@ -67,129 +65,229 @@ impl GroupSyncRead {
         self.param.clear();
     }
 
-    pub fn tx_packet(&mut self) -> COMM {
+    pub fn tx_packet(&mut self) -> Result<()> {
         if self.data_dict.is_empty() {
-            return COMM::NotAvailable;
+            return Err(FtServoError::InvalidParameter("no servo ids added".to_string()));
         }
 
-        if self.is_param_changed || self.param.is_empty() {
-            self.make_param();
-            self.is_param_changed = false;
+        self.last_result = false;
+        for reply in self.data_dict.values_mut() {
+            *reply = Err(COMM::RxWaiting);
         }
-
-        // 修复：传递正确的参数给sync_read_tx
-        self.ph.sync_read_tx(self.start_address, self.data_length, self.param.clone())
+        self.ph.sync_read_tx(self.start_address, self.data_length, &self.param)
     }
-    
-    pub fn rx_packet(&mut self) -> COMM {
-        self.last_result = true;
+
+    // 接收应答，个别舵机没有应答不会影响其他舵机的数据
+    // 每个舵机的结果通过 get_status 和 get_* 获取，last_result 表示是否全部应答
+    pub fn rx_packet(&mut self) -> Result<()> {
+        self.last_result = false;
 
         if self.data_dict.is_empty() {
-            return COMM::NotAvailable;
-        }
-
-        // 修复：传递正确的参数给sync_read_rx
-        let expected_ids: Vec<u32> = self.data_dict.keys().cloned().collect();
-        let (mut result, rxpacket) = self.ph.sync_read_rx(&expected_ids, self.data_length);
-
-        if rxpacket.len() >= self.data_length as usize + 6 {
-            let data_dict_keys: Vec<u32> = self.data_dict.keys().cloned().collect();
-            for scs_id in data_dict_keys {
-                let (data, comm) = self.read_rx(&rxpacket, scs_id, self.data_length);
-                self.data_dict.insert(scs_id, data);
-                result = comm;
-                match result {
-                    COMM::Success => {}
-                    _ => self.last_result = false,
-                }
-            }
-        } else {
-            self.last_result = false
+            return Err(FtServoError::InvalidParameter("no servo ids added".to_string()));
         }
+
+        let expected_ids: Vec<u32> = self.param.iter().map(|&id| id as u32).collect();
+        let replies = self.ph.sync_read_rx(&expected_ids, self.data_length);
+
+        self.last_result = replies.iter().all(|reply| reply.is_ok());
+        self.data_dict.extend(expected_ids.into_iter().zip(replies));
+        Ok(())
+    }
+
+    // 发送和接收作为一次事务，共享总线上其他线程的通信不会插入其间
+    pub fn tx_rx_packet(&mut self) -> Result<()> {
+        self.ph.begin_transaction();
+        let result = self.tx_packet().and_then(|()| self.rx_packet());
+        self.ph.end_transaction();
         result
     }
 
-    pub fn tx_rx_packet(&mut self) -> COMM {
-        let tx_result = self.tx_packet();
-        match tx_result {
-            COMM::Success => self.rx_packet(),
-            _ => tx_result,
+    // 上一次接收中所有舵机是否都应答
+    pub fn last_result(&self) -> bool {
+        self.last_result
+    }
+
+    // 数据是否可用，同时返回该舵机应答中的状态
+    pub fn is_available(&self, scs_id: u32, address: u32, data_length: u32) -> (bool, ServoStatus) {
+        match self.data(scs_id, address, data_length) {
+            Ok(_) => (true, self.get_status(scs_id).unwrap_or_default()),
+            Err(_) => (false, ServoStatus::empty()),
         }
     }
 
-    pub fn read_rx(&self, rxpacket: &[u32], scs_id: u32, data_length: u32) -> (Vec<u32>, COMM) {
-        let mut data: Vec<u32> = Vec::new();
-        let rx_length = rxpacket.len();
-        let mut rx_index = 0;
+    // 舵机应答中的状态；没有应答或应答损坏时返回对应的通信错误
+    pub fn get_status(&self, scs_id: u32) -> Result<ServoStatus> {
+        self.reply(scs_id).map(|(status, _)| status)
+    }
 
-        while (rx_index + 6 + data_length) as usize <= rx_length {
-            let mut headpacket = vec![0x00, 0x00, 0x00];
-            while rx_index < rx_length as u32 {
-                headpacket[2] = headpacket[1];
-                headpacket[1] = headpacket[0];
-                headpacket[0] = rxpacket[rx_index as usize];
-                rx_index += 1;
-                if (headpacket[2] == 0xFF) && (headpacket[1] == 0xFF) && (headpacket[0] == scs_id) {
-                    break;
-                }
-            }
-            if (rx_index + 3 + data_length) as usize > rx_length {
-                break;
-            }
-            if rxpacket[rx_index as usize] != data_length + 2 {
-                rx_index += 1;
-                continue;
+    fn reply(&self, scs_id: u32) -> Result<(ServoStatus, &[u8])> {
+        match self.data_dict.get(&scs_id) {
+            Some(Ok((status, data))) => Ok((*status, data)),
+            Some(Err(result)) => Err(FtServoError::communication(scs_id as u8, INST::SyncRead, *result)),
+            None => Err(FtServoError::InvalidParameter(format!(
+                "SCS ID {} is not found",
+                scs_id
+            ))),
+        }
+    }
+
+    // 取出指定地址开始的数据，检查地址范围和该舵机的读取结果
+    fn data(&self, scs_id: u32, address: u32, data_length: u32) -> Result<&[u8]> {
+        if address < self.start_address
+            || address + data_length > self.start_address + self.data_length
+        {
+            return Err(FtServoError::InvalidParameter(format!(
+                "address {} length {} is outside the read range",
+                address, data_length
+            )));
+        }
+
+        let (_, data) = self.reply(scs_id)?;
+        let offset = (address - self.start_address) as usize;
+        Ok(&data[offset..offset + data_length as usize])
+    }
+
+    pub fn get_1byte(&self, scs_id: u32, address: u32) -> Result<u8> {
+        Ok(self.data(scs_id, address, 1)?[0])
+    }
+
+    pub fn get_2byte(&self, scs_id: u32, address: u32) -> Result<u16> {
+        let data = self.data(scs_id, address, 2)?;
+        Ok(self.ph.scs_getend().make_word(data[0], data[1]))
+    }
+
+    pub fn get_4byte(&self, scs_id: u32, address: u32) -> Result<u32> {
+        let data = self.data(scs_id, address, 4)?;
+        Ok(make_dword(&self.ph.scs_getend(), [data[0], data[1], data[2], data[3]]))
+    }
+
+    // 两字节的有符号数据（符号-数值编码），sign_bit 为符号位：位置、速度为 15，负载为 10
+    pub fn get_signed(&self, scs_id: u32, address: u32, sign_bit: u32) -> Result<i32> {
+        let word = self.get_2byte(scs_id, address)?;
+        Ok(sign_magnitude_to_host(word as i32, sign_bit as i32))
+    }
+
+    // 按长度（1、2、4字节）取出数据，不可用时返回 None
+    pub fn get_data(&self, scs_id: u32, address: u32, data_length: u32) -> Option<u32> {
+        match data_length {
+            1 => self.get_1byte(scs_id, address).ok().map(u32::from),
+            2 => self.get_2byte(scs_id, address).ok().map(u32::from),
+            4 => self.get_4byte(scs_id, address).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        io::{self, ErrorKind},
+        time::Instant,
+    };
+
+    use super::*;
+    use crate::{
+        protocol_packet_handler::Endian,
+        sim::{sim_bus, sim_chain, ServoChain, SimFamily},
+        sms_sts::{SMS_STS_GOAL_POSITION_L, SMS_STS_GOAL_TIME_L},
+    };
+
+    // 按与请求相反的顺序返回应答的舵机链
+    struct Reversed {
+        chain: ServoChain,
+        rx: VecDeque<u8>,
+    }
+
+    impl Transport for Reversed {
+        fn write_port(&mut self, packet: &[u8]) -> io::Result<usize> {
+            for reply in self.chain.process(packet).into_iter().rev() {
+                self.rx.extend(reply);
             }
-            rx_index += 1;
-            let error = rxpacket[rx_index as usize];
-            let mut cal_sum = scs_id + data_length + 2 + error;
-            data.push(error);
-            data.extend_from_slice(&rxpacket[rx_index as usize..(rx_index + data_length) as usize]);
-            for _ in 0..data_length {
-                cal_sum += rxpacket[rx_index as usize];
-                rx_index += 1;
+            Ok(packet.len())
+        }
+
+        fn read_port_until(&mut self, buf: &mut [u8], _deadline: Instant) -> io::Result<usize> {
+            if self.rx.is_empty() {
+                return Err(io::Error::new(ErrorKind::TimedOut, "no status packet"));
             }
-            cal_sum = !cal_sum & 0xFF;
-            if cal_sum != rxpacket[rx_index as usize] {
-                return (Vec::new(), COMM::RxCorrupt);
+            let count = buf.len().min(self.rx.len());
+            for (dst, src) in buf.iter_mut().zip(self.rx.drain(..count)) {
+                *dst = src;
             }
-            return (data, COMM::Success);
+            Ok(count)
         }
-        (Vec::new(), COMM::RxCorrupt)
-    }
-    
-    pub fn is_available(&self, scs_id: u32, address: u32, data_length: u32) -> (bool, u32) {
-        if !self.data_dict.contains_key(&scs_id) {
-            return (false, 0);
+
+        fn clear_port(&mut self) -> io::Result<()> {
+            self.rx.clear();
+            Ok(())
         }
 
-        if (address < self.start_address)
-            || (self.start_address + self.data_length - data_length < address)
-        {
-            return (false, 0);
+        fn get_bytes_available(&self) -> io::Result<u32> {
+            Ok(self.rx.len() as u32)
         }
 
-        let data = self.data_dict.get(&scs_id);
+        fn get_baudrate(&self) -> u32 {
+            1000000
+        }
+    }
 
-        match data {
-            Some(data) => {
-                if data.len() < data_length as usize + 1 {
-                    (false, 0)
-                } else {
-                    (true, data[0])
-                }
-            }
-            None => (false, 0),
+    // 读取目标位置和时间，每个舵机的目标时间为 ID * 100
+    fn goal_group<T: Transport>(transport: T, ids: &[u32]) -> GroupSyncRead<T> {
+        let ph = ProtocolPacketHandler::new(transport, Endian::SmallEndian);
+        let mut group = GroupSyncRead::new(ph, SMS_STS_GOAL_POSITION_L as u32, 4);
+        for &id in ids {
+            group.add_param(id).unwrap();
         }
+        group
     }
 
-    pub fn get_data(&self, scs_id: u32, address: u32, data_length: u32) -> Option<u32> {
-        let index = (address - self.start_address + 1) as usize;
+    fn set_goal_times(chain: &mut ServoChain, ids: &[u8]) {
+        for &id in ids {
+            chain.servo_mut(id).unwrap().write_word(SMS_STS_GOAL_TIME_L, id as u16 * 100);
+        }
+    }
 
-        if data_length == 1 {
-            self.data_dict.get(&scs_id).and_then(|data| data.get(index).copied())
-        } else {
-            None
+    #[test]
+    fn replies_out_of_order_are_matched_by_id() {
+        let mut chain = sim_chain(SimFamily::SmsSts, &[1, 2, 3]);
+        set_goal_times(&mut chain, &[1, 2, 3]);
+        let mut group = goal_group(Reversed { chain, rx: VecDeque::new() }, &[1, 2, 3]);
+
+        group.tx_rx_packet().unwrap();
+
+        assert!(group.last_result());
+        for id in 1..=3 {
+            assert_eq!(group.get_2byte(id, SMS_STS_GOAL_TIME_L as u32).unwrap(), id as u16 * 100);
         }
     }
+
+    #[test]
+    fn missing_servo_does_not_hide_the_others() {
+        let mut bus = sim_bus(SimFamily::SmsSts, &[1, 2]);
+        set_goal_times(bus.chain_mut(), &[1, 2]);
+        let mut group = goal_group(bus, &[1, 9, 2]);
+
+        group.tx_rx_packet().unwrap();
+
+        assert!(!group.last_result());
+        assert_eq!(group.is_available(9, SMS_STS_GOAL_TIME_L as u32, 2), (false, ServoStatus::empty()));
+        assert!(matches!(group.get_status(9), Err(FtServoError::Timeout { id: 9, .. })));
+        assert_eq!(group.get_data(1, SMS_STS_GOAL_TIME_L as u32, 2), Some(100));
+        assert_eq!(group.get_data(2, SMS_STS_GOAL_TIME_L as u32, 2), Some(200));
+    }
+
+    #[test]
+    fn get_data_outside_the_read_range_is_none() {
+        let mut group = goal_group(sim_bus(SimFamily::SmsSts, &[1]), &[1]);
+        group.tx_rx_packet().unwrap();
+
+        let start = SMS_STS_GOAL_POSITION_L as u32;
+        assert!(group.get_data(1, start, 4).is_some());
+        assert_eq!(group.get_data(1, start - 1, 2), None);
+        assert_eq!(group.get_data(1, start + 3, 2), None);
+        assert_eq!(group.get_data(1, start, 8), None);
+        assert!(!group.is_available(1, start + 4, 1).0);
+        assert!(matches!(group.get_1byte(1, start + 4), Err(FtServoError::InvalidParameter(_))));
+    }
 }
@@ -1,122 +1,163 @@
-use std::{
-    collections::HashMap,
-    io::{Error, ErrorKind},
+use crate::{
+    packet::{MIN_PACKET_LEN, TXPACKET_MAX_LEN},
+    port_handler::PortHandler,
+    protocol_packet_handler::ProtocolPacketHandler,
+    transport::Transport,
+    FtServoError, Result,
 };
 
-use crate::{protocol_packet_handler::ProtocolPacketHandler, scservo_def::COMM};
-
 #[derive(Debug)]
-pub struct GroupSyncWrite {
-    pub ph: ProtocolPacketHandler,
+pub struct GroupSyncWrite<T: Transport = PortHandler> {
+    pub ph: ProtocolPacketHandler<T>,
     pub start_address: u32,
     data_length: u32,
 
-    is_param_changed: bool,
-    param: Vec<u32>,
-    data_dict: HashMap<u32, Vec<u32>>,
+    // 指令参数（起始地址和长度之后的部分），按添加顺序排列的 [ID, 数据...]
+    // 修改数据时原地覆盖，发送时直接使用，不需要重新拼接
+    param: Vec<u8>,
 }
 
-impl GroupSyncWrite {
-    pub fn new(ph: ProtocolPacketHandler, start_address: u32, data_length: u32) -> Self {
+impl<T: Transport> GroupSyncWrite<T> {
+    pub fn new(ph: ProtocolPacketHandler<T>, start_address: u32, data_length: u32) -> Self {
         Self {
             ph,
             start_address,
             data_length,
-            is_param_changed: false,
             param: Vec::new(),
-            data_dict: HashMap::new(),
         }
     }
 
-    pub fn make_param(&mut self) {
-        self.param.clear();
-        
-        if self.data_dict.is_empty() {
-            return;
-        }
+    // 每个舵机在参数中占用的字节数
+    fn entry_length(&self) -> usize {
+        self.data_length as usize + 1
+    }
 
-        for (&scs_id, data) in &self.data_dict {
-            if data.is_empty() {
-                return;
-            }
+    // 舵机数据在参数中的位置
+    fn position(&self, scs_id: u32) -> Option<usize> {
+        self.param
+            .chunks_exact(self.entry_length())
+            .position(|entry| entry[0] as u32 == scs_id)
+            .map(|index| index * self.entry_length())
+    }
 
-            self.param.push(scs_id);
-            self.param.extend(data.iter());
+    fn check_data(&self, data: &[u8]) -> Result<()> {
+        if data.len() != self.data_length as usize {
+            return Err(FtServoError::InvalidParameter(format!(
+                "data length {} does not match {}",
+                data.len(),
+                self.data_length
+            )));
         }
+        Ok(())
     }
 
-    pub fn add_param(&mut self, scs_id: u32, data: Vec<u32>) -> Result<(), Error> {
-        if self.data_dict.contains_key(&scs_id) {
-            return Err(Error::new(
-                ErrorKind::AlreadyExists,
-                "scs_id already exists",
-            ));
+    pub fn add_param(&mut self, scs_id: u32, data: &[u8]) -> Result<()> {
+        if self.position(scs_id).is_some() {
+            return Err(FtServoError::InvalidParameter(format!(
+                "scs_id {} already exists",
+                scs_id
+            )));
         }
+        self.check_data(data)?;
 
-        if data.len() > self.data_length as usize {
-            return Err(Error::new(
-                ErrorKind::InvalidInput,
-                "data length is too long",
+        // 参数前还有起始地址和长度两个字节
+        if MIN_PACKET_LEN + 2 + self.param.len() + self.entry_length() > TXPACKET_MAX_LEN {
+            return Err(FtServoError::InvalidParameter(
+                "too many servos for one sync write packet".to_string(),
             ));
         }
 
-        self.data_dict.insert(scs_id, data);
-        self.is_param_changed = true;
+        self.param.push(scs_id as u8);
+        self.param.extend_from_slice(data);
         Ok(())
     }
 
-    pub fn remove_param(&mut self, scs_id: u32) -> Result<(), Error> {
-        if self.data_dict.remove(&scs_id).is_none() {
-            return Err(Error::new(
-                ErrorKind::NotFound,
-                "can not find in the data dict",
-            ));
-        }
-        self.is_param_changed = true;
+    pub fn remove_param(&mut self, scs_id: u32) -> Result<()> {
+        let position = self.position(scs_id).ok_or_else(|| {
+            FtServoError::InvalidParameter(format!("can not find scs_id {}", scs_id))
+        })?;
+        let end = position + self.entry_length();
+        self.param.drain(position..end);
         Ok(())
     }
 
-    pub fn change_param(&mut self, scs_id: u32, data: Vec<u32>) -> Result<(), Error> {
-        if !self.data_dict.contains_key(&scs_id) {
-            return Err(Error::new(
-                ErrorKind::NotFound,
-                "can not find scs_id in the data dict",
-            ));
-        }
-
-        if data.len() > self.data_length as usize {
-            return Err(Error::new(
-                ErrorKind::InvalidInput,
-                "data length is too long",
-            ));
-        }
+    pub fn change_param(&mut self, scs_id: u32, data: &[u8]) -> Result<()> {
+        let position = self.position(scs_id).ok_or_else(|| {
+            FtServoError::InvalidParameter(format!("can not find scs_id {}", scs_id))
+        })?;
+        self.check_data(data)?;
 
-        self.data_dict.insert(scs_id, data);
-        self.is_param_changed = true;
+        let end = position + self.entry_length();
+        self.param[position + 1..end].copy_from_slice(data);
         Ok(())
     }
 
     pub fn clear_param(&mut self) {
-        self.data_dict.clear();
         self.param.clear();
-        self.is_param_changed = true;
     }
 
-    pub fn tx_packet(&mut self) -> COMM {
-        if self.data_dict.is_empty() {
-            return COMM::NotAvailable;
+    pub fn tx_packet(&mut self) -> Result<()> {
+        if self.param.is_empty() {
+            return Err(FtServoError::InvalidParameter("no servo data added".to_string()));
         }
 
-        if self.is_param_changed {
-            self.make_param();
-            self.is_param_changed = false;
-        }
-        
         self.ph.sync_write_tx_only(
             self.start_address,
             self.data_length,
-            self.param.clone(),
-            (self.data_dict.len() * (1 + self.data_length as usize)) as u32,
+            &self.param,
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        packet::checksum,
+        protocol_packet_handler::Endian,
+        scservo_def::INST,
+        sim::{sim_bus, Recorder, SimFamily},
+        sms_sts::SMS_STS_GOAL_POSITION_L,
+    };
+
+    #[test]
+    fn tx_packet_writes_every_servo_in_one_frame() {
+        let ph = ProtocolPacketHandler::new(sim_bus(SimFamily::SmsSts, &[1, 2]), Endian::SmallEndian);
+        let mut group = GroupSyncWrite::new(ph, SMS_STS_GOAL_POSITION_L as u32, 2);
+
+        group.add_param(1, &[0x00, 0x08]).unwrap();
+        group.add_param(2, &[0x00, 0x04]).unwrap();
+        group.change_param(1, &[0x00, 0x0C]).unwrap();
+        group.tx_packet().unwrap();
+
+        let chain = group.ph.get_port_handler().chain();
+        assert_eq!(chain.servo(1).unwrap().read_word(SMS_STS_GOAL_POSITION_L), 3072);
+        assert_eq!(chain.servo(2).unwrap().read_word(SMS_STS_GOAL_POSITION_L), 1024);
+    }
+
+    #[test]
+    fn tx_packet_keeps_the_insertion_order() {
+        let bus = Recorder::new(sim_bus(SimFamily::SmsSts, &[1, 2, 3]));
+        let ph = ProtocolPacketHandler::new(bus, Endian::SmallEndian);
+        let mut group = GroupSyncWrite::new(ph, SMS_STS_GOAL_POSITION_L as u32, 2);
+
+        group.add_param(3, &[0x00, 0x02]).unwrap();
+        group.add_param(1, &[0x00, 0x04]).unwrap();
+        group.add_param(2, &[0x00, 0x06]).unwrap();
+        group.remove_param(1).unwrap();
+        group.add_param(1, &[0x00, 0x08]).unwrap();
+        group.tx_packet().unwrap();
+
+        let recorder = group.ph.get_port_handler();
+        let params = [SMS_STS_GOAL_POSITION_L, 2, 3, 0x00, 0x02, 2, 0x00, 0x06, 1, 0x00, 0x08];
+        let mut expected = vec![0xFF, 0xFF, 0xFE, params.len() as u8 + 2, INST::SyncWrite as u8];
+        expected.extend_from_slice(&params);
+        expected.push(checksum(&expected[2..]));
+        assert_eq!(recorder.writes, vec![expected]);
+
+        let chain = recorder.bus.chain();
+        assert_eq!(chain.servo(3).unwrap().read_word(SMS_STS_GOAL_POSITION_L), 512);
+        assert_eq!(chain.servo(2).unwrap().read_word(SMS_STS_GOAL_POSITION_L), 1536);
+        assert_eq!(chain.servo(1).unwrap().read_word(SMS_STS_GOAL_POSITION_L), 2048);
+    }
+}
@@ -1,35 +1,93 @@
+#[cfg(feature = "async")]
+pub mod async_client;
+pub mod bus;
+#[cfg(feature = "config")]
+pub mod config;
+mod exchange;
+pub mod group_bulk_read;
 pub mod group_sync_read;
 pub mod group_sync_write;
+pub mod model;
+pub mod packet;
 pub mod port_handler;
 pub mod protocol_packet_handler;
+pub mod register;
+pub mod scan;
 pub mod scscl;
 pub mod scservo_def;
+mod setup;
+pub mod sim;
 pub mod sms_sts;
+pub mod transport;
 
 // 重新导出主要接口
-pub use sms_sts::SmsSts;
+#[cfg(feature = "async")]
+pub use async_client::{AsyncPacketHandler, AsyncScscl, AsyncSmsSts};
+pub use bus::SharedBus;
+#[cfg(feature = "config")]
+pub use config::{BusConfig, Difference, ServoConfig};
+pub use sms_sts::{OperatingMode, SmsSts};
 pub use scscl::Scscl;
 pub use port_handler::PortHandler;
-pub use protocol_packet_handler::{ProtocolPacketHandler, Endian};
+pub use protocol_packet_handler::{ProtocolPacketHandler, Endian, PingInfo, SyncReadReply};
+pub use scan::{scan, ScanOptions, ScanResult};
 pub use group_sync_write::GroupSyncWrite;
 pub use group_sync_read::GroupSyncRead;
-pub use scservo_def::{COMM, INST, BROADCAST_ID, MAX_ID};
+pub use group_bulk_read::GroupBulkRead;
+pub use packet::{InstructionPacket, StatusPacket};
+pub use model::{Family, ServoModel};
+pub use register::{Access, Area, Register};
+pub use transport::Transport;
+pub use scservo_def::{COMM, INST, BROADCAST_ID, MAX_ID, ServoStatus};
 
 // 自定义错误类型
 #[derive(Debug, thiserror::Error)]
 pub enum FtServoError {
     #[error("Serial port error: {0}")]
     SerialPort(#[from] serialport::Error),
-    #[error("Communication error: {0:?}")]
-    Communication(COMM),
+    #[error("[ID:{id:03}] {instruction:?} communication error: {result:?}")]
+    Communication { id: u8, instruction: INST, result: COMM },
+    #[error("[ID:{id:03}] {instruction:?} hardware error: {status}")]
+    Hardware { id: u8, instruction: INST, status: ServoStatus },
     #[error("Invalid parameter: {0}")]
     InvalidParameter(String),
-    #[error("Timeout occurred")]
-    Timeout,
-    #[error("Checksum mismatch")]
-    ChecksumError,
+    #[error("[ID:{id:03}] {instruction:?} timed out waiting for status packet")]
+    Timeout { id: u8, instruction: INST },
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+    #[error("Config file error: {0}")]
+    Config(String),
+    #[error("[ID:{id:03}] EEPROM left unlocked: {source}")]
+    EepromUnlocked { id: u8, source: Box<FtServoError> },
+}
+
+impl FtServoError {
+    // 由通信结果构造错误，接收超时单独归为 Timeout
+    pub fn communication(id: u8, instruction: INST, result: COMM) -> Self {
+        match result {
+            COMM::RxTimeout => FtServoError::Timeout { id, instruction },
+            _ => FtServoError::Communication { id, instruction, result },
+        }
+    }
+
+    // 出错的舵机ID
+    pub fn servo_id(&self) -> Option<u8> {
+        match self {
+            FtServoError::Communication { id, .. }
+            | FtServoError::Hardware { id, .. }
+            | FtServoError::Timeout { id, .. }
+            | FtServoError::EepromUnlocked { id, .. } => Some(*id),
+            _ => None,
+        }
+    }
+
+    // 舵机报告的故障标志
+    pub fn status(&self) -> Option<ServoStatus> {
+        match self {
+            FtServoError::Hardware { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, FtServoError>;
@@ -46,3 +104,7 @@ pub fn create_sms_sts(port_handler: PortHandler) -> SmsSts {
 pub fn create_scscl(port_handler: PortHandler) -> Scscl {
     Scscl::new(port_handler)
 }
+
+pub fn create_shared_bus(port_handler: PortHandler) -> SharedBus {
+    SharedBus::new(port_handler)
+}
@@ -1,6 +1,6 @@
 use ftservo_sdk::{
     create_port_handler, create_sms_sts, 
-    COMM, Result
+    Result
 };
 use std::time::Duration;
 use std::thread;
@@ -21,12 +21,12 @@ fn main() -> Result<()> {
     // 测试ping
     let ping_result = sms_sts.ping(1);
     match ping_result {
-        COMM::Success => println!("[ID:001] ping成功"),
-        _ => println!("[ID:001] ping失败: {:?}", ping_result),
+        Ok(info) => println!("[ID:001] ping成功, 状态: {}", info.status),
+        Err(e) => println!("[ID:001] ping失败: {}", e),
     }
     
     // 使能扭矩
-    sms_sts.write_torque_enable(1, true);
+    sms_sts.write_torque_enable(1, true)?;
     
     // 写入位置
     let result = sms_sts.write_pos_ex(1, 2048, 1000, 2400);
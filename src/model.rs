@@ -0,0 +1,197 @@
+//! 舵机型号
+//!
+//! 型号号（控制表地址 3/4）决定舵机所属系列、位置分辨率和角度范围。`ServoModel::from_number`
+//! 查找已知型号，未知型号可以用 `ServoModel::generic` 按系列默认值处理。`SmsSts` 与 `Scscl`
+//! 的 `detect_model` 读取并缓存每个舵机的型号，角度读写按各自的型号换算。
+//!
+//! 控制器创建时不知道总线上有哪些舵机，因此不在连接时检测：某个舵机第一次进行角度读写时
+//! 读取其型号号并缓存，之后不再读取。需要提前确认型号时调用 `detect_model`，
+//! 已知型号时用 `set_model` 指定即可省去这次读取。
+
+use crate::{protocol_packet_handler::Endian, register::Register, scscl, sms_sts, FtServoError, Result};
+
+// 协议系列：决定控制表布局和字节序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "config", derive(serde::Serialize, serde::Deserialize))]
+pub enum Family {
+    SmsSts,
+    Scscl,
+}
+
+impl Family {
+    // SMS/STS 为小端，SCSCL 为大端
+    pub fn endian(&self) -> Endian {
+        match self {
+            Family::SmsSts => Endian::SmallEndian,
+            Family::Scscl => Endian::BigEndian,
+        }
+    }
+
+    // 该系列的控制表
+    pub fn registers(&self) -> &'static [Register] {
+        match self {
+            Family::SmsSts => sms_sts::regs::TABLE,
+            Family::Scscl => scscl::regs::TABLE,
+        }
+    }
+}
+
+// 修改ID、波特率和备份恢复需要特殊处理的寄存器
+pub(crate) struct FamilyRegs {
+    pub id: Register,
+    pub baud_rate: Register,
+    pub lock: Register,
+}
+
+pub(crate) fn family_regs(family: Family) -> FamilyRegs {
+    match family {
+        Family::SmsSts => FamilyRegs {
+            id: sms_sts::regs::ID,
+            baud_rate: sms_sts::regs::BAUD_RATE,
+            lock: sms_sts::regs::LOCK,
+        },
+        Family::Scscl => FamilyRegs {
+            id: scscl::regs::ID,
+            baud_rate: scscl::regs::BAUD_RATE,
+            lock: scscl::regs::LOCK,
+        },
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ServoModel {
+    pub name: &'static str,
+    // 控制表中的型号号
+    pub number: u16,
+    pub family: Family,
+    // 全行程的步数，位置取值为 0..resolution
+    pub resolution: u16,
+    // 全行程对应的角度（度）
+    pub angle_range: u16,
+    // 最大速度（步/秒）
+    pub max_speed: u16,
+}
+
+// 已知型号
+pub const STS3215: ServoModel = ServoModel::new("STS3215", 777, Family::SmsSts, 4096, 360, 3400);
+pub const STS3250: ServoModel = ServoModel::new("STS3250", 2825, Family::SmsSts, 4096, 360, 3400);
+pub const SM8512BL: ServoModel = ServoModel::new("SM8512BL", 11272, Family::SmsSts, 4096, 360, 3400);
+pub const SCS0009: ServoModel = ServoModel::new("SCS0009", 1284, Family::Scscl, 1024, 300, 1000);
+
+// STS3032、SMS40、SCS15 的型号号尚未确认，暂不列入，检测时按系列默认值处理，
+// 可以用控制器的 set_model 指定
+pub const MODELS: &[ServoModel] = &[STS3215, STS3250, SM8512BL, SCS0009];
+
+impl ServoModel {
+    pub const fn new(name: &'static str, number: u16, family: Family, resolution: u16, angle_range: u16, max_speed: u16) -> Self {
+        Self {
+            name,
+            number,
+            family,
+            resolution,
+            angle_range,
+            max_speed,
+        }
+    }
+
+    // 按型号号查找已知型号
+    pub fn from_number(number: u16) -> Option<ServoModel> {
+        MODELS.iter().find(|model| model.number == number).copied()
+    }
+
+    // 未知型号按系列默认值：SMS/STS 为 4096 步 360°、3400 步/秒，SCSCL 为 1024 步 300°、1000 步/秒
+    pub fn generic(number: u16, family: Family) -> ServoModel {
+        match family {
+            Family::SmsSts => ServoModel::new("unknown", number, family, 4096, 360, 3400),
+            Family::Scscl => ServoModel::new("unknown", number, family, 1024, 300, 1000),
+        }
+    }
+
+    // 按型号号查找，未知型号使用系列默认值
+    pub fn from_number_or_generic(number: u16, family: Family) -> ServoModel {
+        Self::from_number(number).unwrap_or_else(|| Self::generic(number, family))
+    }
+
+    pub fn is_known(&self) -> bool {
+        MODELS.contains(self)
+    }
+
+    // 最大位置（步）
+    pub fn max_position(&self) -> i32 {
+        self.resolution as i32 - 1
+    }
+
+    // 每度对应的步数
+    pub fn steps_per_degree(&self) -> f64 {
+        self.resolution as f64 / self.angle_range as f64
+    }
+
+    // 步数换算为角度，也适用于速度（步/秒 -> 度/秒）
+    pub fn steps_to_degrees(&self, steps: i32) -> f64 {
+        steps as f64 / self.steps_per_degree()
+    }
+
+    // 角度换算为步数（四舍五入），也适用于速度（度/秒 -> 步/秒）
+    pub fn degrees_to_steps(&self, degrees: f64) -> i32 {
+        (degrees * self.steps_per_degree()).round() as i32
+    }
+
+    // 检查位置是否在该型号的行程内
+    pub fn check_position(&self, position: i32) -> Result<()> {
+        if position < 0 || position > self.max_position() {
+            return Err(FtServoError::InvalidParameter(format!(
+                "position {} is out of range 0..={} for {}",
+                position,
+                self.max_position(),
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    // 检查速度（步/秒）是否超过该型号的最大速度
+    pub fn check_speed(&self, speed: i32) -> Result<()> {
+        if speed.abs() > self.max_speed as i32 {
+            return Err(FtServoError::InvalidParameter(format!(
+                "speed {} exceeds the maximum {} for {}",
+                speed, self.max_speed, self.name
+            )));
+        }
+        Ok(())
+    }
+
+    // 目标位置（度）和速度（度/秒）换算为步，超出行程或最大速度时返回错误
+    pub(crate) fn goal_steps(&self, degrees: f64, speed: f64) -> Result<(i32, u32)> {
+        let position = self.degrees_to_steps(degrees);
+        self.check_position(position)?;
+        let speed = self.degrees_to_steps(speed.abs());
+        self.check_speed(speed)?;
+        Ok((position, speed as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_models_are_found_by_number() {
+        assert_eq!(ServoModel::from_number(777), Some(STS3215));
+        assert_eq!(ServoModel::from_number(1284), Some(SCS0009));
+        assert_eq!(ServoModel::from_number(1), None);
+
+        let generic = ServoModel::from_number_or_generic(1, Family::Scscl);
+        assert!(!generic.is_known());
+        assert_eq!((generic.resolution, generic.max_speed), (1024, 1000));
+    }
+
+    #[test]
+    fn conversions_and_limits_follow_the_model() {
+        assert_eq!(STS3215.degrees_to_steps(90.0), 1024);
+        assert_eq!(SCS0009.degrees_to_steps(150.0), 512);
+        assert!(STS3215.check_position(4095).is_ok());
+        assert!(STS3215.check_position(4096).is_err());
+        assert!(STS3215.check_speed(-3400).is_ok());
+        assert!(STS3215.check_speed(3401).is_err());
+    }
+}
@@ -0,0 +1,515 @@
+//! 数据包编解码
+//!
+//! 指令包: `FF FF ID LEN INST PARAM... CHK`，状态包: `FF FF ID LEN ERR PARAM... CHK`，
+//! 其中 LEN = 参数长度 + 2，CHK = !(ID + LEN + INST/ERR + PARAM...)。
+//! 编解码只操作 `u8` 缓冲区，不依赖端口。
+
+use crate::scservo_def::{ServoStatus, COMM, INST};
+
+pub const TXPACKET_MAX_LEN: usize = 250;
+pub const RXPACKET_MAX_LEN: usize = 250;
+
+// 包头
+pub const HEADER: [u8; 2] = [0xFF, 0xFF];
+// 包头(2) + ID(1) + 长度(1) + 指令/错误(1) + 校验和(1)
+pub const MIN_PACKET_LEN: usize = 6;
+
+// 协议包字段位置
+const PKT_ID: usize = 2;
+const PKT_LENGTH: usize = 3;
+const PKT_INSTRUCTION: usize = 4;
+const PKT_PARAMETER0: usize = 5;
+
+// 编解码错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketError {
+    // 数据包超过最大长度
+    TooLong,
+    // 输出缓冲区不足
+    BufferTooSmall,
+    // 数据不完整
+    Truncated,
+    // 包头不是 FF FF
+    BadHeader,
+    // 长度字段与数据不符
+    BadLength,
+    // 校验和错误
+    BadChecksum,
+    // 未知指令
+    UnknownInstruction(u8),
+}
+
+impl PacketError {
+    // 转换为通信结果：编码错误为 TxError，解码错误为 RxCorrupt
+    pub fn comm(&self) -> COMM {
+        match self {
+            PacketError::TooLong | PacketError::BufferTooSmall => COMM::TxError,
+            _ => COMM::RxCorrupt,
+        }
+    }
+}
+
+impl std::fmt::Display for PacketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PacketError::TooLong => write!(f, "packet exceeds maximum length"),
+            PacketError::BufferTooSmall => write!(f, "buffer too small"),
+            PacketError::Truncated => write!(f, "packet truncated"),
+            PacketError::BadHeader => write!(f, "invalid packet header"),
+            PacketError::BadLength => write!(f, "invalid length field"),
+            PacketError::BadChecksum => write!(f, "checksum mismatch"),
+            PacketError::UnknownInstruction(inst) => write!(f, "unknown instruction 0x{:02X}", inst),
+        }
+    }
+}
+
+impl std::error::Error for PacketError {}
+
+// 计算校验和：ID 到最后一个参数求和取反
+pub fn checksum(bytes: &[u8]) -> u8 {
+    !bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b))
+}
+
+// 将 ID、指令/错误字节和依次排列的各段参数写入缓冲区
+fn encode_frame(id: u8, code: u8, parts: &[&[u8]], buf: &mut [u8], max_len: usize) -> Result<usize, PacketError> {
+    let param_length: usize = parts.iter().map(|part| part.len()).sum();
+    let total = param_length + MIN_PACKET_LEN;
+    if total > max_len {
+        return Err(PacketError::TooLong);
+    }
+    if buf.len() < total {
+        return Err(PacketError::BufferTooSmall);
+    }
+
+    buf[..2].copy_from_slice(&HEADER);
+    buf[PKT_ID] = id;
+    buf[PKT_LENGTH] = (param_length + 2) as u8;
+    buf[PKT_INSTRUCTION] = code;
+    let mut offset = PKT_PARAMETER0;
+    for part in parts {
+        buf[offset..offset + part.len()].copy_from_slice(part);
+        offset += part.len();
+    }
+    buf[total - 1] = checksum(&buf[PKT_ID..total - 1]);
+    Ok(total)
+}
+
+// 编码参数由多段组成的指令包（如同步写入的 起始地址 + 长度 + 各舵机数据），
+// 各段直接写入缓冲区，不需要先拼接参数
+pub fn encode_instruction(id: u8, instruction: INST, parts: &[&[u8]], buf: &mut [u8]) -> Result<usize, PacketError> {
+    encode_frame(id, instruction as u8, parts, buf, TXPACKET_MAX_LEN)
+}
+
+// 校验数据包结构，返回 (ID, 指令/错误字节, 参数)
+fn decode_frame(bytes: &[u8], max_len: usize) -> Result<(u8, u8, &[u8]), PacketError> {
+    if bytes.len() < MIN_PACKET_LEN {
+        return Err(PacketError::Truncated);
+    }
+    if bytes[..2] != HEADER {
+        return Err(PacketError::BadHeader);
+    }
+
+    let length = bytes[PKT_LENGTH] as usize;
+    let total = length + 4;
+    if length < 2 || total > max_len {
+        return Err(PacketError::BadLength);
+    }
+    if bytes.len() < total {
+        return Err(PacketError::Truncated);
+    }
+    if bytes.len() > total {
+        return Err(PacketError::BadLength);
+    }
+    if checksum(&bytes[PKT_ID..total - 1]) != bytes[total - 1] {
+        return Err(PacketError::BadChecksum);
+    }
+
+    Ok((bytes[PKT_ID], bytes[PKT_INSTRUCTION], &bytes[PKT_PARAMETER0..total - 1]))
+}
+
+// 指令包（主机 -> 舵机）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstructionPacket<'a> {
+    pub id: u8,
+    pub instruction: INST,
+    pub params: &'a [u8],
+}
+
+impl<'a> InstructionPacket<'a> {
+    pub fn new(id: u8, instruction: INST, params: &'a [u8]) -> Self {
+        Self {
+            id,
+            instruction,
+            params,
+        }
+    }
+
+    // 编码后的总长度
+    pub fn encoded_len(&self) -> usize {
+        self.params.len() + MIN_PACKET_LEN
+    }
+
+    // 编码到缓冲区，返回写入的字节数
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, PacketError> {
+        encode_frame(self.id, self.instruction as u8, &[self.params], buf, TXPACKET_MAX_LEN)
+    }
+
+    // 编码为新的 Vec
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PacketError> {
+        let mut buf = vec![0u8; self.encoded_len()];
+        self.encode(&mut buf)?;
+        Ok(buf)
+    }
+
+    // 从完整的数据包解码
+    pub fn decode(bytes: &'a [u8]) -> Result<Self, PacketError> {
+        let (id, code, params) = decode_frame(bytes, TXPACKET_MAX_LEN)?;
+        let instruction = INST::try_from(code).map_err(|_| PacketError::UnknownInstruction(code))?;
+        Ok(Self {
+            id,
+            instruction,
+            params,
+        })
+    }
+}
+
+// 状态包（舵机 -> 主机）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusPacket<'a> {
+    pub id: u8,
+    // 舵机状态字节
+    pub error: u8,
+    pub params: &'a [u8],
+}
+
+impl<'a> StatusPacket<'a> {
+    pub fn new(id: u8, error: u8, params: &'a [u8]) -> Self {
+        Self { id, error, params }
+    }
+
+    // 解析状态字节中的故障标志，保留未定义的位
+    pub fn status(&self) -> ServoStatus {
+        ServoStatus::from_bits_retain(self.error)
+    }
+
+    // 编码后的总长度
+    pub fn encoded_len(&self) -> usize {
+        self.params.len() + MIN_PACKET_LEN
+    }
+
+    // 编码到缓冲区，返回写入的字节数
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, PacketError> {
+        encode_frame(self.id, self.error, &[self.params], buf, RXPACKET_MAX_LEN)
+    }
+
+    // 编码为新的 Vec
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PacketError> {
+        let mut buf = vec![0u8; self.encoded_len()];
+        self.encode(&mut buf)?;
+        Ok(buf)
+    }
+
+    // 从完整的数据包解码
+    pub fn decode(bytes: &'a [u8]) -> Result<Self, PacketError> {
+        let (id, error, params) = decode_frame(bytes, RXPACKET_MAX_LEN)?;
+        Ok(Self { id, error, params })
+    }
+}
+
+// 数据开头是否为完整且校验正确的状态包
+fn is_frame(bytes: &[u8]) -> bool {
+    if bytes.len() < MIN_PACKET_LEN || bytes[..2] != HEADER || bytes[PKT_ID] == HEADER[0] {
+        return false;
+    }
+    let total = bytes[PKT_LENGTH] as usize + 4;
+    (MIN_PACKET_LEN..=RXPACKET_MAX_LEN).contains(&total)
+        && bytes.len() >= total
+        && checksum(&bytes[PKT_ID..total - 1]) == bytes[total - 1]
+}
+
+// 状态包流式解码器
+//
+// 接收任意分块的字节流：查找 FF FF 包头，跳过噪声和损坏的数据包，
+// 校验长度与校验和，一次读取中的多个数据包依次取出。
+#[derive(Debug, Clone, Default)]
+pub struct StatusDecoder {
+    buffer: Vec<u8>,
+    // 上一次取出的数据包长度，下次调用时从缓冲区移除
+    consumed: usize,
+    // 累计丢弃的字节数
+    discarded: usize,
+}
+
+impl StatusDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 追加接收到的字节
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.release();
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    // 清空缓冲区和统计
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.consumed = 0;
+        self.discarded = 0;
+    }
+
+    // 尚未解码的字节数
+    pub fn buffered(&self) -> usize {
+        self.buffer.len() - self.consumed
+    }
+
+    // 自上次 clear 以来丢弃的字节数
+    pub fn discarded(&self) -> usize {
+        self.discarded
+    }
+
+    // 缓冲区中是否已有完整的数据包
+    pub fn has_packet(&mut self) -> bool {
+        self.release();
+        self.sync()
+    }
+
+    // 取出下一个完整数据包的原始字节
+    pub fn next_frame(&mut self) -> Option<&[u8]> {
+        if !self.has_packet() {
+            return None;
+        }
+        self.consumed = self.buffer[PKT_LENGTH] as usize + 4;
+        Some(&self.buffer[..self.consumed])
+    }
+
+    // 取出下一个完整的状态包
+    pub fn next_packet(&mut self) -> Option<StatusPacket<'_>> {
+        let frame = self.next_frame()?;
+        StatusPacket::decode(frame).ok()
+    }
+
+    // 移除上一次取出的数据包
+    fn release(&mut self) {
+        if self.consumed > 0 {
+            self.buffer.drain(..self.consumed);
+            self.consumed = 0;
+        }
+    }
+
+    // 丢弃缓冲区开头的字节
+    fn discard(&mut self, count: usize) {
+        self.buffer.drain(..count);
+        self.discarded += count;
+    }
+
+    // 将缓冲区对齐到有效数据包开头，返回是否已有完整数据包
+    fn sync(&mut self) -> bool {
+        loop {
+            // 寻找包头
+            match self.buffer.windows(2).position(|w| w == HEADER) {
+                Some(0) => {}
+                Some(pos) => self.discard(pos),
+                None => {
+                    // 保留可能是包头一部分的最后一个 0xFF
+                    let keep = usize::from(self.buffer.last() == Some(&HEADER[0]));
+                    self.discard(self.buffer.len() - keep);
+                    return false;
+                }
+            }
+
+            if self.buffer.len() <= PKT_LENGTH {
+                return false;
+            }
+
+            // FF FF FF：第一个 0xFF 是噪声
+            if self.buffer[PKT_ID] == HEADER[0] {
+                self.discard(1);
+                continue;
+            }
+
+            let total = self.buffer[PKT_LENGTH] as usize + 4;
+            if !(MIN_PACKET_LEN..=RXPACKET_MAX_LEN).contains(&total) {
+                self.discard(1);
+                continue;
+            }
+            if self.buffer.len() < total {
+                // 长度字段很大的噪声包头：后面已经收到完整的有效数据包时不再等待
+                match (1..self.buffer.len()).find(|&pos| is_frame(&self.buffer[pos..])) {
+                    Some(pos) => {
+                        self.discard(pos);
+                        continue;
+                    }
+                    None => return false,
+                }
+            }
+
+            // 校验失败时只跳过包头的一个字节，数据包内部可能藏着真正的包头
+            if checksum(&self.buffer[PKT_ID..total - 1]) != self.buffer[total - 1] {
+                self.discard(1);
+                continue;
+            }
+            return true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Ping ID 1 的状态包: FF FF 01 02 00 FC
+    const PING_REPLY: [u8; 6] = [0xFF, 0xFF, 0x01, 0x02, 0x00, 0xFC];
+
+    #[test]
+    fn instruction_round_trip() {
+        let packet = InstructionPacket::new(1, INST::Write, &[42, 0x10, 0x27]);
+        let bytes = packet.to_bytes().unwrap();
+        assert_eq!(bytes, [0xFF, 0xFF, 0x01, 0x05, 0x03, 42, 0x10, 0x27, 0x95]);
+        assert_eq!(InstructionPacket::decode(&bytes).unwrap(), packet);
+    }
+
+    #[test]
+    fn status_round_trip() {
+        let packet = StatusPacket::new(1, 0, &[]);
+        assert_eq!(packet.to_bytes().unwrap(), PING_REPLY);
+
+        let packet = StatusPacket::new(7, ServoStatus::OVERHEAT.bits(), &[0x34, 0x12]);
+        let bytes = packet.to_bytes().unwrap();
+        let decoded = StatusPacket::decode(&bytes).unwrap();
+        assert_eq!(decoded, packet);
+        assert_eq!(decoded.status(), ServoStatus::OVERHEAT);
+    }
+
+    #[test]
+    fn encode_instruction_matches_concatenated_params() {
+        let mut buf = [0u8; TXPACKET_MAX_LEN];
+        let length = encode_instruction(0xFE, INST::SyncWrite, &[&[42, 2], &[1, 0x10, 0x27], &[2, 0x20, 0x4E]], &mut buf).unwrap();
+
+        let params = [42, 2, 1, 0x10, 0x27, 2, 0x20, 0x4E];
+        let expected = InstructionPacket::new(0xFE, INST::SyncWrite, &params).to_bytes().unwrap();
+        assert_eq!(&buf[..length], &expected[..]);
+
+        let long = [0u8; TXPACKET_MAX_LEN];
+        assert_eq!(encode_instruction(1, INST::Write, &[&[0], &long], &mut buf), Err(PacketError::TooLong));
+    }
+
+    #[test]
+    fn encode_rejects_oversized_packets() {
+        let params = [0u8; TXPACKET_MAX_LEN];
+        let packet = InstructionPacket::new(1, INST::Write, &params[..TXPACKET_MAX_LEN - MIN_PACKET_LEN + 1]);
+        assert_eq!(packet.encode(&mut [0u8; TXPACKET_MAX_LEN]), Err(PacketError::TooLong));
+
+        let packet = InstructionPacket::new(1, INST::Ping, &[]);
+        assert_eq!(packet.encode(&mut [0u8; 5]), Err(PacketError::BufferTooSmall));
+    }
+
+    #[test]
+    fn decode_rejects_bad_checksum() {
+        let mut bytes = PING_REPLY;
+        bytes[5] ^= 0x01;
+        assert_eq!(StatusPacket::decode(&bytes), Err(PacketError::BadChecksum));
+
+        // 数据被改动而校验和不变
+        let mut bytes = PING_REPLY;
+        bytes[4] = 0x20;
+        assert_eq!(StatusPacket::decode(&bytes), Err(PacketError::BadChecksum));
+    }
+
+    #[test]
+    fn decode_rejects_malformed_frames() {
+        assert_eq!(StatusPacket::decode(&PING_REPLY[..5]), Err(PacketError::Truncated));
+        assert_eq!(StatusPacket::decode(&[0xFE, 0xFF, 0x01, 0x02, 0x00, 0xFC]), Err(PacketError::BadHeader));
+        assert_eq!(StatusPacket::decode(&[0xFF, 0xFF, 0x01, 0x01, 0x00, 0xFD]), Err(PacketError::BadLength));
+
+        let mut long = PING_REPLY.to_vec();
+        long.push(0);
+        assert_eq!(StatusPacket::decode(&long), Err(PacketError::BadLength));
+
+        let bytes = [0xFF, 0xFF, 0x01, 0x02, 0x7F, checksum(&[0x01, 0x02, 0x7F])];
+        assert_eq!(InstructionPacket::decode(&bytes), Err(PacketError::UnknownInstruction(0x7F)));
+    }
+
+    #[test]
+    fn decoder_skips_noise_before_header() {
+        let mut decoder = StatusDecoder::new();
+        decoder.push(&[0x00, 0x13, 0xFF]);
+        decoder.push(&PING_REPLY);
+
+        assert_eq!(decoder.next_packet(), Some(StatusPacket::new(1, 0, &[])));
+        assert_eq!(decoder.discarded(), 3);
+        assert_eq!(decoder.next_packet(), None);
+    }
+
+    #[test]
+    fn decoder_joins_split_frames() {
+        let reply = StatusPacket::new(3, 0, &[0x00, 0x08]).to_bytes().unwrap();
+        let mut decoder = StatusDecoder::new();
+
+        for (i, &byte) in reply.iter().enumerate() {
+            assert!(!decoder.has_packet(), "packet complete after {} bytes", i);
+            decoder.push(&[byte]);
+        }
+        assert_eq!(decoder.next_packet(), Some(StatusPacket::new(3, 0, &[0x00, 0x08])));
+        assert_eq!(decoder.buffered(), 0);
+        assert_eq!(decoder.discarded(), 0);
+    }
+
+    #[test]
+    fn decoder_returns_back_to_back_frames() {
+        let mut bytes = PING_REPLY.to_vec();
+        bytes.extend(StatusPacket::new(2, 0, &[5]).to_bytes().unwrap());
+        let mut decoder = StatusDecoder::new();
+        decoder.push(&bytes);
+
+        assert_eq!(decoder.next_packet().map(|p| p.id), Some(1));
+        assert_eq!(decoder.next_packet().map(|p| p.id), Some(2));
+        assert_eq!(decoder.next_packet(), None);
+    }
+
+    #[test]
+    fn decoder_resyncs_after_corrupt_frame() {
+        // 校验和错误的数据包之后紧跟一个有效的数据包
+        let mut bytes = vec![0xFF, 0xFF, 0x05, 0x03, 0x00, 0x11, 0x00];
+        bytes.extend_from_slice(&PING_REPLY);
+        let mut decoder = StatusDecoder::new();
+        decoder.push(&bytes);
+
+        assert_eq!(decoder.next_packet(), Some(StatusPacket::new(1, 0, &[])));
+        assert_eq!(decoder.discarded(), 7);
+    }
+
+    #[test]
+    fn decoder_finds_header_inside_corrupt_frame() {
+        // 长度字段很大的噪声包头中藏着真正的数据包
+        let mut bytes = vec![0xFF, 0xFF, 0x09, 0x40];
+        bytes.extend_from_slice(&PING_REPLY);
+        let mut decoder = StatusDecoder::new();
+        decoder.push(&bytes);
+
+        assert_eq!(decoder.next_packet(), Some(StatusPacket::new(1, 0, &[])));
+        assert_eq!(decoder.discarded(), 4);
+    }
+
+    #[test]
+    fn decoder_does_not_wait_for_the_length_of_a_noise_header() {
+        let mut decoder = StatusDecoder::new();
+        decoder.push(&[0xFF, 0xFF, 0x09, 0xF0]);
+        decoder.push(&PING_REPLY[..3]);
+        assert!(!decoder.has_packet());
+
+        decoder.push(&PING_REPLY[3..]);
+        assert_eq!(decoder.next_packet(), Some(StatusPacket::new(1, 0, &[])));
+        assert_eq!(decoder.discarded(), 4);
+    }
+
+    #[test]
+    fn decoder_treats_extra_header_byte_as_noise() {
+        let mut decoder = StatusDecoder::new();
+        decoder.push(&[0xFF]);
+        decoder.push(&PING_REPLY);
+
+        assert_eq!(decoder.next_packet(), Some(StatusPacket::new(1, 0, &[])));
+        assert_eq!(decoder.discarded(), 1);
+    }
+}
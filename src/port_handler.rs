@@ -1,13 +1,20 @@
 use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
 use std::{
-    io::ErrorKind,
+    io::{self, ErrorKind},
     time::{Duration, Instant},
 };
 
+use crate::transport::Transport;
+
 // 默认设置
 const DEFAULT_BAUDRATE: u32 = 1000000;
 const LATENCY_TIMER: u32 = 50;
 
+// 支持的波特率
+pub const SUPPORTED_BAUDRATES: [u32; 12] = [
+    4800, 9600, 14400, 19200, 38400, 57600, 76800, 115200, 128000, 250000, 500000, 1000000,
+];
+
 // PortHandler 结构体
 #[derive(Debug)]
 pub struct PortHandler {
@@ -19,7 +26,6 @@ pub struct PortHandler {
     packet_timeout: Duration,
     tx_time_per_byte: Duration,
 
-    pub is_using: bool,
     // 使用 SerialPortBuilder
     ser: Option<Box<dyn SerialPort>>,
 }
@@ -34,7 +40,6 @@ impl PortHandler {
             packet_start_time: None,
             packet_timeout: Duration::default(),
             tx_time_per_byte: Duration::default(),
-            is_using: false,
             ser: None,
         }
     }
@@ -91,7 +96,7 @@ impl PortHandler {
     // 读取端口
     pub fn read_port(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
         if let Some(port) = &mut self.ser {
-            port.read(buf).map_err(|e| e.into())
+            port.read(buf)
         } else {
             Err(std::io::Error::new(
                 std::io::ErrorKind::NotFound,
@@ -193,16 +198,48 @@ impl PortHandler {
 
     // 获取标志波特率
     pub fn get_c_flag_baud(&self, baudrate: u32) -> Option<u32> {
-        let baudrate_list: Vec<u32> = vec![
-            4800, 9600, 14400, 19200, 38400, 57600, 115200, 128000, 250000, 500000, 1000000,
-        ];
-        
-        for &baud in &baudrate_list {
-            if baud == baudrate {
-                return Some(baud);
-            }
+        SUPPORTED_BAUDRATES.iter().find(|&&baud| baud == baudrate).copied()
+    }
+}
+
+impl Transport for PortHandler {
+    fn write_port(&mut self, packet: &[u8]) -> io::Result<usize> {
+        PortHandler::write_port(self, packet)
+    }
+
+    fn read_port_until(&mut self, buf: &mut [u8], deadline: Instant) -> io::Result<usize> {
+        let port = self.ser.as_mut().ok_or_else(|| {
+            io::Error::new(ErrorKind::NotConnected, "Port not open")
+        })?;
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(ErrorKind::TimedOut, "packet timeout"));
         }
-        None
+
+        // 串口读取超时不超过剩余时间
+        port.set_timeout(remaining)?;
+        port.read(buf)
+    }
+
+    fn clear_port(&mut self) -> io::Result<()> {
+        PortHandler::clear_port(self).map_err(io::Error::from)
+    }
+
+    fn get_bytes_available(&self) -> io::Result<u32> {
+        PortHandler::get_bytes_available(self).map_err(io::Error::from)
+    }
+
+    fn get_baudrate(&self) -> u32 {
+        self.baudrate
+    }
+
+    fn set_baudrate(&mut self, baudrate: u32) -> io::Result<()> {
+        PortHandler::set_baudrate(self, baudrate).map_err(io::Error::from)
+    }
+
+    fn get_tx_time_per_byte(&self) -> Duration {
+        self.tx_time_per_byte
     }
 }
 
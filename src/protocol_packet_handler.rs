@@ -1,31 +1,18 @@
 use crate::{
+    exchange::{Exchange, Request},
+    packet::{InstructionPacket, StatusPacket, MIN_PACKET_LEN, RXPACKET_MAX_LEN},
     port_handler::PortHandler,
-    scservo_def::{BROADCAST_ID, COMM, INST},
+    register::Register,
+    scservo_def::{ServoStatus, COMM, INST},
+    transport::Transport,
+    FtServoError,
 };
-use std::io::ErrorKind;
+use std::time::{Duration, Instant};
 
-const TXPACKET_MAX_LEN: usize = 250;
-const RXPACKET_MAX_LEN: usize = 250;
+pub(crate) const LATENCY_TIMER: u64 = 50;
 
-// 协议包常量
-const HEADER0: usize = 0;
-const HEADER1: usize = 1;
-const ID: usize = 2;
-const LENGTH: usize = 3;
-const INSTRUCTION: usize = 4;
-const ERROR: usize = 4;
-const PARAMETER0: usize = 5;
-
-#[repr(u8)]
-#[derive(Debug, Clone)]
-pub enum ErrorCode {
-    VoltageError = 1,
-    AngleError = 2,
-    OverheatError = 4,
-    OverElementError = 8,
-    OverloadError = 32,
-    Success = 0,
-}
+// 同步读取中单个舵机的应答：状态和数据，或通信失败的原因
+pub type SyncReadReply = Result<(ServoStatus, Vec<u8>), COMM>;
 
 #[derive(Debug, Clone)]
 #[repr(u8)]
@@ -34,6 +21,63 @@ pub enum Endian {
     SmallEndian,
 }
 
+impl Endian {
+    // 按字节序组合两个字节
+    pub fn make_word(&self, a: u8, b: u8) -> u16 {
+        match self {
+            Endian::SmallEndian => a as u16 | (b as u16) << 8,
+            Endian::BigEndian => b as u16 | (a as u16) << 8,
+        }
+    }
+
+    // 按字节序拆分为 [先发送的字节, 后发送的字节]
+    pub fn split_word(&self, w: u16) -> [u8; 2] {
+        match self {
+            Endian::SmallEndian => w.to_le_bytes(),
+            Endian::BigEndian => w.to_be_bytes(),
+        }
+    }
+}
+
+// 四字节数据：低字在前，每个字按舵机字节序
+pub(crate) fn make_dword(end: &Endian, data: [u8; 4]) -> u32 {
+    end.make_word(data[0], data[1]) as u32 | (end.make_word(data[2], data[3]) as u32) << 16
+}
+
+pub(crate) fn split_dword(end: &Endian, data: u32) -> [u8; 4] {
+    let [a, b] = end.split_word(data as u16);
+    let [c, d] = end.split_word((data >> 16) as u16);
+    [a, b, c, d]
+}
+
+// 符号-数值编码转换为有符号数，b 为符号位
+pub(crate) fn sign_magnitude_to_host(a: i32, b: i32) -> i32 {
+    if (a & (1 << b)) > 0 {
+        -(a & !(1 << b))
+    } else {
+        a
+    }
+}
+
+// 读取块数据中 offset 处的双字节有符号值
+pub(crate) fn signed_word(end: &Endian, data: &[u8], offset: usize, sign_bit: i32) -> i32 {
+    let word = end.make_word(data[offset], data[offset + 1]);
+    sign_magnitude_to_host(word as i32, sign_bit)
+}
+
+// 检查同步写入的参数长度
+pub(crate) fn check_sync_lengths(ids: usize, positions: usize, times: usize, speeds: usize) -> crate::Result<()> {
+    if ids != positions || positions != times || times != speeds {
+        return Err(FtServoError::InvalidParameter(
+            "ids, positions, times and speeds must have the same length".to_string(),
+        ));
+    }
+    if ids == 0 {
+        return Err(FtServoError::InvalidParameter("no servo ids given".to_string()));
+    }
+    Ok(())
+}
+
 impl std::fmt::Display for Endian {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -43,36 +87,56 @@ impl std::fmt::Display for Endian {
     }
 }
 
-#[derive(Debug)]
-pub struct ProtocolPacketHandler {
-    pub port_handler: PortHandler,
-    scs_end: Endian,
+// 型号寄存器地址，SMS/STS 与 SCSCL 相同
+pub(crate) const MODEL_ADDRESS: u8 = 3;
+
+// Ping 的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PingInfo {
+    pub id: u8,
+    // 应答中的舵机状态
+    pub status: ServoStatus,
+    // 型号号，仅在 ping_with_model 时读取
+    pub model: Option<u16>,
 }
 
-// 在现有代码基础上添加缺失的功能
+#[derive(Debug)]
+pub struct ProtocolPacketHandler<T: Transport = PortHandler> {
+    pub port_handler: T,
+    // 指令编码与应答校验，与异步客户端共用
+    exchange: Exchange,
+    // 当前数据包的接收截止时间
+    packet_deadline: Instant,
+    // 未读取的应答的最晚到达时间
+    stale_deadline: Instant,
+    // 接收超时中数据传输时间之外的等待时间
+    latency_timer: Duration,
+    // 事务嵌套深度，最外层开始和结束时与共享总线交接未读取的应答
+    depth: usize,
+}
 
-impl ProtocolPacketHandler {
-    pub fn new(port_handler: PortHandler, scs_end: Endian) -> Self {
+impl<T: Transport> ProtocolPacketHandler<T> {
+    pub fn new(port_handler: T, scs_end: Endian) -> Self {
         Self {
-            port_handler: PortHandler::new(&port_handler.get_port_name()),
-            scs_end,
+            port_handler,
+            exchange: Exchange::new(scs_end),
+            packet_deadline: Instant::now(),
+            stale_deadline: Instant::now(),
+            latency_timer: Duration::from_millis(LATENCY_TIMER),
+            depth: 0,
         }
     }
 
     pub fn scs_getend(&self) -> Endian {
-        self.scs_end.clone()
+        self.exchange.scs_end.clone()
     }
 
     pub fn scs_setend(&mut self, end: Endian) {
-        self.scs_end = end;
+        self.exchange.scs_end = end;
     }
 
     pub fn scs_tohost(&self, a: i32, b: i32) -> i32 {
-        if (a & (1 << b)) > 0 {
-            -(a & !(1 << b))
-        } else {
-            a
-        }
+        sign_magnitude_to_host(a, b)
     }
 
     pub fn scs_toscs(&self, a: i32, b: i32) -> i32 {
@@ -84,7 +148,7 @@ impl ProtocolPacketHandler {
     }
 
     pub fn scs_makeword(&self, a: i32, b: i32) -> i32 {
-        match self.scs_end {
+        match self.exchange.scs_end {
             Endian::SmallEndian => (a & 0xFF) | ((b & 0xFF) << 8),
             Endian::BigEndian => (b & 0xFF) | ((a & 0xFF) << 8),
         }
@@ -103,14 +167,14 @@ impl ProtocolPacketHandler {
     }
 
     pub fn scs_lobyte(&self, w: i32) -> i32 {
-        match self.scs_end {
+        match self.exchange.scs_end {
             Endian::SmallEndian => w & 0xFF,
             Endian::BigEndian => (w >> 8) & 0xFF,
         }
     }
 
     pub fn scs_hibyte(&self, w: i32) -> i32 {
-        match self.scs_end {
+        match self.exchange.scs_end {
             Endian::SmallEndian => (w >> 8) & 0xFF,
             Endian::BigEndian => w & 0xFF,
         }
@@ -134,319 +198,431 @@ impl ProtocolPacketHandler {
         }
     }
 
-    pub fn get_rx_packet_error(&self, error: ErrorCode) -> Result<(), serialport::Error> {
-        match error {
-            ErrorCode::VoltageError => Err(serialport::Error::new(
-                serialport::ErrorKind::InvalidInput,
-                "[ServoStatus] Input voltage error!",
-            )),
-            ErrorCode::AngleError => Err(serialport::Error::new(
-                serialport::ErrorKind::Io(ErrorKind::InvalidData),
-                "[ServoStatus] Angle error!",
-            )),
-            ErrorCode::OverheatError => Err(serialport::Error::new(
-                serialport::ErrorKind::Io(ErrorKind::InvalidData),
-                "[ServoStatus] Overheat error!",
-            )),
-            ErrorCode::OverElementError => Err(serialport::Error::new(
-                serialport::ErrorKind::Io(ErrorKind::InvalidData),
-                "[ServoStatus] Over element error!",
-            )),
-            ErrorCode::OverloadError => Err(serialport::Error::new(
-                serialport::ErrorKind::Io(ErrorKind::InvalidData),
-                "[ServoStatus] Overload error!",
-            )),
-            ErrorCode::Success => Ok(()),
-        }
+    // 列出状态字节中所有置位的故障
+    pub fn get_rx_packet_error(&self, status: ServoStatus) -> String {
+        let messages = [
+            (ServoStatus::VOLTAGE, "[ServoStatus] Input voltage error!"),
+            (ServoStatus::ANGLE, "[ServoStatus] Angle error!"),
+            (ServoStatus::OVERHEAT, "[ServoStatus] Overheat error!"),
+            (ServoStatus::OVERCURRENT, "[ServoStatus] Over element error!"),
+            (ServoStatus::OVERLOAD, "[ServoStatus] Overload error!"),
+        ];
+        messages
+            .iter()
+            .filter(|(flag, _)| status.contains(*flag))
+            .map(|(_, message)| *message)
+            .collect::<Vec<_>>()
+            .join(" ")
     }
 
-    fn calculate_checksum(&self, packet: &[u8]) -> u8 {
-        let mut checksum = 0u8;
-        for &byte in &packet[2..] {
-            checksum = checksum.wrapping_add(byte);
-        }
-        !checksum
+    // 最近一次收到的状态包中的舵机状态
+    pub fn last_status(&self) -> ServoStatus {
+        self.exchange.last_status
     }
 
-    pub fn tx_packet(&mut self, tx_packet: &mut Vec<u32>) -> COMM {
-        let mut checksum = 0;
-        let total_packet_length = tx_packet[LENGTH] + 4;
+    // 单播的写入、寄存器写入和动作指令是否等待并校验状态包（默认开启）
+    // 关闭后只发送不等待应答，适合高频控制循环；下一次读取前会先丢弃这些指令的应答
+    pub fn set_write_ack(&mut self, enabled: bool) {
+        self.exchange.write_ack = enabled;
+    }
 
-        if self.port_handler.is_using {
-            return COMM::PortBusy;
-        }
+    pub fn write_ack(&self) -> bool {
+        self.exchange.write_ack
+    }
 
-        self.port_handler.is_using = true;
-        if total_packet_length as usize > TXPACKET_MAX_LEN {
-            self.port_handler.is_using = false;
-            return COMM::TxError;
-        }
+    // 应答中的舵机状态带有故障标志时是否返回 Hardware 错误（默认关闭，读取仍返回数据，
+    // 状态通过写入的返回值或 last_status 取得），Ping 不受影响
+    pub fn set_strict_status(&mut self, enabled: bool) {
+        self.exchange.strict_status = enabled;
+    }
 
-        tx_packet[HEADER0] = 0xff;
-        tx_packet[HEADER1] = 0xff;
+    pub fn strict_status(&self) -> bool {
+        self.exchange.strict_status
+    }
 
-        for idx in 2..(total_packet_length - 1) as usize {
-            checksum += tx_packet[idx];
+    // 开始一次跨多个指令包的事务（如同步读取的发送与接收），期间共享总线不会被其他句柄使用
+    pub fn begin_transaction(&mut self) {
+        self.port_handler.begin_transaction();
+        if self.depth == 0 {
+            if let Some((count, deadline)) = self.port_handler.take_deferred_replies() {
+                self.exchange.add_stale(count);
+                self.stale_deadline = self.stale_deadline.max(deadline);
+            }
         }
-        tx_packet[total_packet_length as usize - 1] = !checksum & 0xff;
+        self.depth += 1;
+    }
 
-        match self.port_handler.clear_port() {
-            Ok(_) => {}
-            Err(e) => eprintln!("Error clear the port {}", e),
+    // 结束事务
+    pub fn end_transaction(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0
+            && self.exchange.has_stale()
+            && self.port_handler.defer_replies(self.exchange.stale(), self.stale_deadline)
+        {
+            self.exchange.forget_stale();
         }
+        self.port_handler.end_transaction();
+    }
 
-        // 修复：将u32数组转换为u8数组进行发送
-        let tx_data: Vec<u8> = tx_packet[0..total_packet_length as usize]
-            .iter()
-            .map(|&x| x as u8)
-            .collect();
+    // 设置接收超时中数据传输时间之外的等待时间（默认 50ms），扫描总线时可以缩短
+    pub fn set_latency_timer(&mut self, latency: Duration) {
+        self.latency_timer = latency;
+    }
 
-        self.port_handler.set_packet_timeout(total_packet_length);
+    pub fn get_latency_timer(&self) -> Duration {
+        self.latency_timer
+    }
 
-        match self.port_handler.write_port(&tx_data) {
-            Ok(written) => {
-                if written != total_packet_length as usize {
-                    self.port_handler.is_using = false;
-                    return COMM::TxFail;
+    // 按数据包长度设置接收超时
+    pub fn set_packet_timeout(&mut self, packet_length: u32) {
+        let tx_time_per_byte = self.port_handler.get_tx_time_per_byte();
+        self.packet_deadline = Instant::now()
+            + tx_time_per_byte * packet_length
+            + tx_time_per_byte * 3
+            + self.latency_timer;
+    }
+
+    // 以毫秒为单位设置接收超时
+    pub fn set_packet_timeout_millis(&mut self, msec: u64) {
+        self.packet_deadline = Instant::now() + Duration::from_millis(msec);
+    }
+
+    // 是否已超时
+    pub fn is_packet_timeout(&self) -> bool {
+        Instant::now() >= self.packet_deadline
+    }
+
+    // 丢弃之前没有等待应答的指令留下的状态包
+    // wait 为 true 时等待这些状态包到达（最多到截止时间），否则只处理已经到达的数据
+    fn discard_stale(&mut self, wait: bool) {
+        let mut chunk = [0u8; RXPACKET_MAX_LEN];
+        self.exchange.decoder.clear();
+
+        while self.exchange.has_stale() {
+            let deadline = if wait {
+                self.stale_deadline
+            } else {
+                let available = self.port_handler.get_bytes_available().unwrap_or(0);
+                if available == 0 {
+                    break;
                 }
+                Instant::now() + self.port_handler.get_tx_time_per_byte() * available + Duration::from_millis(1)
+            };
+
+            match self.port_handler.read_port_until(&mut chunk, deadline) {
+                Ok(bytes_read) if bytes_read > 0 => self.exchange.discard(&chunk[..bytes_read]),
+                _ => break,
             }
-            Err(_) => {
-                self.port_handler.is_using = false;
-                return COMM::TxFail;
-            }
+        }
+        if wait || Instant::now() >= self.stale_deadline {
+            self.exchange.forget_stale();
         }
 
-        self.port_handler.is_using = false;
-        COMM::Success
-    }
-
-    pub fn rx_packet(&mut self) -> (Vec<u32>, COMM) {
-        let mut rx_packet = vec![0u32; RXPACKET_MAX_LEN];
-        let mut rx_length = 0;
-        let mut wait_length = 6; // 最小包长度
-
-        loop {
-            let mut buffer = [0u8; 1];
-            match self.port_handler.read_port(&mut buffer) {
-                Ok(bytes_read) => {
-                    if bytes_read > 0 {
-                        rx_packet[rx_length] = buffer[0] as u32;
-                        rx_length += 1;
-
-                        // 检查包头
-                        if rx_length >= 4 {
-                            if rx_packet[0] == 0xFF && rx_packet[1] == 0xFF {
-                                wait_length = rx_packet[3] as usize + 4; // 长度字段 + 包头和校验和
-                            }
-                        }
-
-                        // 检查是否接收完整包
-                        if rx_length >= wait_length && wait_length > 6 {
-                            break;
-                        }
-
-                        if rx_length >= RXPACKET_MAX_LEN {
-                            return (vec![], COMM::RxCorrupt);
-                        }
-                    }
-                }
-                Err(_) => {
-                    if self.port_handler.is_packet_timeout() {
-                        return (vec![], COMM::RxTimeout);
-                    }
-                }
-            }
+        // 清除失败时端口已不可用，接下来的发送会返回 TxFail，这里忽略错误
+        let _ = self.port_handler.clear_port();
+        self.exchange.decoder.clear();
+    }
+
+    pub fn tx_packet(&mut self, packet: &InstructionPacket) -> COMM {
+        match self.exchange.encode(packet.id, packet.instruction, &[packet.params]) {
+            Ok(length) => self.tx_frame(packet.id, length),
+            Err(result) => result,
         }
+    }
 
-        // 验证校验和
-        if rx_length >= 4 {
-            let mut checksum = 0u32;
-            for i in 2..rx_length - 1 {
-                checksum += rx_packet[i];
-            }
-            checksum = (!checksum) & 0xFF;
+    // 发送已编码在发送缓冲区中的指令包
+    fn tx_frame(&mut self, id: u8, total_packet_length: usize) -> COMM {
+        self.begin_transaction();
+        self.discard_stale(false);
+
+        self.set_packet_timeout(total_packet_length as u32);
+
+        let result = match self.port_handler.write_port(self.exchange.frame(total_packet_length)) {
+            Ok(written) if written == total_packet_length => COMM::Success,
+            _ => COMM::TxFail,
+        };
+        // 单播指令都会得到应答，在读取它之前记为待丢弃
+        if result == COMM::Success && self.exchange.sent(id) {
+            self.stale_deadline = self.packet_deadline;
+        }
+        self.end_transaction();
+
+        result
+    }
+
+    // 接收下一个状态包，跳过包头前的噪声和损坏的数据包
+    pub fn rx_packet(&mut self) -> Result<StatusPacket<'_>, COMM> {
+        self.begin_transaction();
+        let received = self.wait_packet();
+        // 在事务结束、交接未读取的应答之前记录
+        if received {
+            self.exchange.reply_received();
+        } else {
+            self.exchange.forget_stale();
+        }
+        self.end_transaction();
+
+        if !received {
+            return Err(self.exchange.rx_failure());
+        }
+        self.exchange.take_packet()
+    }
+
+    // 读取数据直到解码器中有完整数据包或超时
+    fn wait_packet(&mut self) -> bool {
+        let mut chunk = [0u8; RXPACKET_MAX_LEN];
 
-            if checksum != rx_packet[rx_length - 1] {
-                return (vec![], COMM::RxCorrupt);
+        while !self.exchange.decoder.has_packet() {
+            match self.port_handler.read_port_until(&mut chunk, self.packet_deadline) {
+                Ok(bytes_read) if bytes_read > 0 => self.exchange.decoder.push(&chunk[..bytes_read]),
+                _ => {
+                    if self.is_packet_timeout() {
+                        return false;
+                    }
+                }
             }
         }
+        true
+    }
 
-        (rx_packet[0..rx_length].to_vec(), COMM::Success)
+    // 发送并接收应答，整个过程作为一次事务占用总线
+    pub fn tx_rx_packet(&mut self, packet: &InstructionPacket) -> Result<StatusPacket<'_>, COMM> {
+        self.tx_rx(packet.id, packet.instruction, &[packet.params])
     }
 
-    pub fn tx_rx_packet(&mut self, tx_packet: &mut Vec<u32>) -> (Vec<u32>, COMM) {
-        let tx_result = self.tx_packet(tx_packet);
+    fn tx_rx(&mut self, id: u8, instruction: INST, parts: &[&[u8]]) -> Result<StatusPacket<'_>, COMM> {
+        self.begin_transaction();
+        self.discard_stale(true);
+        let tx_result = match self.exchange.encode(id, instruction, parts) {
+            Ok(length) => self.tx_frame(id, length),
+            Err(result) => result,
+        };
+        let received = tx_result == COMM::Success && self.wait_packet();
+        // 应答已收到或已超时
+        self.exchange.forget_stale();
+        self.end_transaction();
+
         if tx_result != COMM::Success {
-            return (vec![], tx_result);
+            return Err(tx_result);
+        }
+        if !received {
+            return Err(self.exchange.rx_failure());
         }
+        self.exchange.take_reply(id)
+    }
 
-        self.rx_packet()
-    }
-
-    pub fn ping(&mut self, scs_id: u32) -> COMM {
-        let mut tx_packet = vec![0u32; 6];
-        tx_packet[ID] = scs_id;
-        tx_packet[LENGTH] = 2;
-        tx_packet[INSTRUCTION] = INST::Ping as u32;
-        
-        self.tx_packet(&mut tx_packet)
-    }
-
-    pub fn action(&mut self, scs_id: u32) -> COMM {
-        let mut tx_packet = vec![0u32; 6];
-        tx_packet[ID] = scs_id;
-        tx_packet[LENGTH] = 2;
-        tx_packet[INSTRUCTION] = INST::Action as u32;
-        
-        self.tx_packet(&mut tx_packet)
-    }
-
-    pub fn read_1byte_tx_rx(&mut self, scs_id: u32, address: u32) -> (Vec<u32>, COMM) {
-        let mut tx_packet = vec![0u32; 8];
-        tx_packet[ID] = scs_id;
-        tx_packet[LENGTH] = 4;
-        tx_packet[INSTRUCTION] = INST::Read as u32;
-        tx_packet[PARAMETER0] = address;
-        tx_packet[PARAMETER0 + 1] = 1; // 读取1字节
-        
-        self.tx_rx_packet(&mut tx_packet)
-    }
-
-    pub fn read_2byte_tx_rx(&mut self, scs_id: u32, address: u32) -> (Vec<u32>, COMM) {
-        let mut tx_packet = vec![0u32; 8];
-        tx_packet[ID] = scs_id;
-        tx_packet[LENGTH] = 4;
-        tx_packet[INSTRUCTION] = INST::Read as u32;
-        tx_packet[PARAMETER0] = address;
-        tx_packet[PARAMETER0 + 1] = 2; // 读取2字节
-        
-        self.tx_rx_packet(&mut tx_packet)
-    }
-
-    pub fn write_1byte_tx_rx(&mut self, scs_id: u32, address: u32, data: u8) -> COMM {
-        let mut tx_packet = vec![0u32; 8];
-        tx_packet[ID] = scs_id;
-        tx_packet[LENGTH] = 4;
-        tx_packet[INSTRUCTION] = INST::Write as u32;
-        tx_packet[PARAMETER0] = address;
-        tx_packet[PARAMETER0 + 1] = data as u32;
-        
-        self.tx_packet(&mut tx_packet)
-    }
-
-    pub fn write_2byte_tx_rx(&mut self, scs_id: u32, address: u32, data: u16) -> COMM {
-        let mut tx_packet = vec![0u32; 9];
-        tx_packet[ID] = scs_id;
-        tx_packet[LENGTH] = 5;
-        tx_packet[INSTRUCTION] = INST::Write as u32;
-        tx_packet[PARAMETER0] = address;
-        tx_packet[PARAMETER0 + 1] = self.scs_lobyte(data as i32) as u32;
-        tx_packet[PARAMETER0 + 2] = self.scs_hibyte(data as i32) as u32;
-        
-        self.tx_packet(&mut tx_packet)
-    }
-
-    pub fn sync_write_tx_only(&mut self, start_address: u32, data_length: u32, param: Vec<u32>, param_length: u32) -> COMM {
-        let mut tx_packet = vec![0u32; param_length as usize + 8];
-        tx_packet[ID] = BROADCAST_ID as u32;
-        tx_packet[LENGTH] = param_length + 4;
-        tx_packet[INSTRUCTION] = INST::SyncWrite as u32;
-        tx_packet[PARAMETER0] = start_address;
-        tx_packet[PARAMETER0 + 1] = data_length;
-        
-        for (i, &value) in param.iter().enumerate() {
-            tx_packet[PARAMETER0 + 2 + i] = value;
+    // 发送指令，不等待应答
+    fn send(&mut self, request: &Request) -> crate::Result<()> {
+        let result = match self.exchange.encode(request.id, request.instruction, &request.parts()) {
+            Ok(length) => self.tx_frame(request.id, length),
+            Err(result) => result,
+        };
+        match result {
+            COMM::Success => Ok(()),
+            result => Err(request.error(result)),
         }
-        
-        self.tx_packet(&mut tx_packet)
-    }
-
-    // 修复 sync_read_tx 实现
-    pub fn sync_read_tx(&mut self, start_address: u32, data_length: u32, param: Vec<u32>) -> COMM {
-        let param_length = param.len() as u32;
-        let mut tx_packet = vec![0u32; param_length as usize + 8];
-        
-        tx_packet[ID] = BROADCAST_ID as u32;
-        tx_packet[LENGTH] = param_length + 4;
-        tx_packet[INSTRUCTION] = INST::SyncRead as u32;
-        tx_packet[PARAMETER0] = start_address;
-        tx_packet[PARAMETER0 + 1] = data_length;
-        
-        for (i, &value) in param.iter().enumerate() {
-            tx_packet[PARAMETER0 + 2 + i] = value;
+    }
+
+    // 发送指令，需要应答时接收并校验状态包，状态字节记录在 last_status 中，
+    // 故障由调用者判断，开启 strict_status 时返回 Hardware
+    // 广播或关闭了写入应答时只发送，返回不带数据、状态为空的应答
+    fn request(&mut self, request: &Request) -> crate::Result<StatusPacket<'_>> {
+        if !self.exchange.expects_reply(request) {
+            self.send(request)?;
+            return Ok(StatusPacket::new(request.id, 0, &[]));
         }
-        
-        self.tx_packet(&mut tx_packet)
-    }
-
-    // 修复 sync_read_rx 实现
-    pub fn sync_read_rx(&mut self, expected_ids: &[u32], data_length: u32) -> (COMM, Vec<u32>) {
-        let mut all_data = Vec::new();
-        
-        for &scs_id in expected_ids {
-            let (rx_data, result) = self.rx_packet();
-            if result != COMM::Success {
-                return (result, vec![]);
-            }
-            
-            // 验证ID匹配
-            if rx_data.len() > 2 && rx_data[2] == scs_id {
-                all_data.extend_from_slice(&rx_data);
+        let strict = self.exchange.strict_status;
+        let packet = self
+            .tx_rx(request.id, request.instruction, &request.parts())
+            .map_err(|result| request.error(result))?;
+        request.check(packet, strict)
+    }
+
+    // 发送 Ping 并等待应答，舵机不存在时返回 Timeout
+    pub fn ping(&mut self, scs_id: u32) -> crate::Result<PingInfo> {
+        let packet = self.request(&Request::ping(scs_id)?)?;
+        Ok(PingInfo { id: packet.id, status: packet.status(), model: None })
+    }
+
+    // Ping 后读取型号号（地址 3/4），舵机报告故障时也会读取
+    pub fn ping_with_model(&mut self, scs_id: u32) -> crate::Result<PingInfo> {
+        let mut info = self.ping(scs_id)?;
+        info.model = Some(self.read_2byte_tx_rx(scs_id, MODEL_ADDRESS as u32)?);
+        Ok(info)
+    }
+
+    pub fn action(&mut self, scs_id: u32) -> crate::Result<ServoStatus> {
+        Ok(self.request(&Request::action(scs_id))?.status())
+    }
+
+    // 读取指定长度的数据，返回应答包
+    fn read_tx_rx_packet(&mut self, scs_id: u32, address: u32, length: u32) -> crate::Result<StatusPacket<'_>> {
+        self.request(&Request::read(scs_id, address, length)?)
+    }
+
+    // 从任意地址读取连续的 length 个字节，一次收发完成
+    pub fn read_tx_rx(&mut self, scs_id: u32, address: u32, length: u32) -> crate::Result<Vec<u8>> {
+        let packet = self.read_tx_rx_packet(scs_id, address, length)?;
+        Ok(packet.params.to_vec())
+    }
+
+    pub fn read_1byte_tx_rx(&mut self, scs_id: u32, address: u32) -> crate::Result<u8> {
+        let packet = self.read_tx_rx_packet(scs_id, address, 1)?;
+        Ok(packet.params[0])
+    }
+
+    pub fn read_2byte_tx_rx(&mut self, scs_id: u32, address: u32) -> crate::Result<u16> {
+        let end = self.scs_getend();
+        let packet = self.read_tx_rx_packet(scs_id, address, 2)?;
+        Ok(end.make_word(packet.params[0], packet.params[1]))
+    }
+
+    pub fn read_4byte_tx_rx(&mut self, scs_id: u32, address: u32) -> crate::Result<u32> {
+        let end = self.scs_getend();
+        let packet = self.read_tx_rx_packet(scs_id, address, 4)?;
+        let data = packet.params;
+        Ok(make_dword(&end, [data[0], data[1], data[2], data[3]]))
+    }
+
+    // 按寄存器描述读取并解码
+    pub fn read_reg(&mut self, scs_id: u32, reg: Register) -> crate::Result<i32> {
+        let end = self.scs_getend();
+        let packet = self.read_tx_rx_packet(scs_id, reg.address as u32, reg.size as u32)?;
+        Ok(reg.decode(&end, packet.params))
+    }
+
+    // 按寄存器描述编码并写入，只读寄存器或超出范围的值返回 InvalidParameter
+    pub fn write_reg(&mut self, scs_id: u32, reg: Register, value: i32) -> crate::Result<ServoStatus> {
+        let data = reg.encode(&self.exchange.scs_end, value)?;
+        self.write_tx_rx(scs_id, reg.address as u32, &data)
+    }
+
+    // 从任意地址写入连续的数据，单播时等待状态包并返回舵机状态
+    pub fn write_tx_rx(&mut self, scs_id: u32, address: u32, data: &[u8]) -> crate::Result<ServoStatus> {
+        Ok(self.request(&Request::write(scs_id, INST::Write, address, data)?)?.status())
+    }
+
+    pub fn write_1byte_tx_rx(&mut self, scs_id: u32, address: u32, data: u8) -> crate::Result<ServoStatus> {
+        self.write_tx_rx(scs_id, address, &[data])
+    }
+
+    pub fn write_2byte_tx_rx(&mut self, scs_id: u32, address: u32, data: u16) -> crate::Result<ServoStatus> {
+        let bytes = self.exchange.scs_end.split_word(data);
+        self.write_tx_rx(scs_id, address, &bytes)
+    }
+
+    pub fn write_4byte_tx_rx(&mut self, scs_id: u32, address: u32, data: u32) -> crate::Result<ServoStatus> {
+        let bytes = split_dword(&self.exchange.scs_end, data);
+        self.write_tx_rx(scs_id, address, &bytes)
+    }
+
+    // 只发送写入指令，不等待应答
+    pub fn write_tx_only(&mut self, scs_id: u32, address: u32, data: &[u8]) -> crate::Result<()> {
+        self.send(&Request::write(scs_id, INST::Write, address, data)?)
+    }
+
+    pub fn write_1byte_tx_only(&mut self, scs_id: u32, address: u32, data: u8) -> crate::Result<()> {
+        self.write_tx_only(scs_id, address, &[data])
+    }
+
+    pub fn write_2byte_tx_only(&mut self, scs_id: u32, address: u32, data: u16) -> crate::Result<()> {
+        let bytes = self.exchange.scs_end.split_word(data);
+        self.write_tx_only(scs_id, address, &bytes)
+    }
+
+    pub fn sync_write_tx_only(&mut self, start_address: u32, data_length: u32, param: &[u8]) -> crate::Result<()> {
+        let prefix = [start_address as u8, data_length as u8];
+        self.send(&Request::broadcast(INST::SyncWrite, &prefix, param))
+    }
+
+    pub fn sync_read_tx(&mut self, start_address: u32, data_length: u32, param: &[u8]) -> crate::Result<()> {
+        let prefix = [start_address as u8, data_length as u8];
+        self.begin_transaction();
+        self.discard_stale(true);
+        let result = self.send(&Request::broadcast(INST::SyncRead, &prefix, param));
+        self.end_transaction();
+        result
+    }
+
+    // 接收同步读取的应答，舵机可以按任意顺序应答
+    // 返回与 expected_ids 一一对应的结果：未应答的为 RxTimeout，数据长度不符的为 RxCorrupt
+    pub fn sync_read_rx(&mut self, expected_ids: &[u32], data_length: u32) -> Vec<SyncReadReply> {
+        let expected: Vec<(u32, u32)> = expected_ids.iter().map(|&id| (id, data_length)).collect();
+        self.read_replies(&expected)
+    }
+
+    // 接收一组 (ID, 数据长度) 的应答，每个舵机的长度可以不同
+    fn read_replies(&mut self, expected: &[(u32, u32)]) -> Vec<SyncReadReply> {
+        self.begin_transaction();
+        let replies = self.read_reply_frames(expected);
+        self.end_transaction();
+        replies
+    }
+
+    fn read_reply_frames(&mut self, expected: &[(u32, u32)]) -> Vec<SyncReadReply> {
+        let mut replies = vec![Err(COMM::RxTimeout); expected.len()];
+        let mut remaining = expected.len();
+
+        // 所有应答依次到达所需的时间
+        let total_length: u32 = expected.iter().map(|&(_, length)| MIN_PACKET_LEN as u32 + length).sum();
+        self.set_packet_timeout(total_length);
+
+        while remaining > 0 && self.wait_packet() {
+            let Some(packet) = self.exchange.decoder.next_packet() else {
+                break;
+            };
+            // 忽略不在列表中的ID和重复的应答
+            let index = match expected.iter().position(|&(id, _)| id == packet.id as u32) {
+                Some(index) if replies[index] == Err(COMM::RxTimeout) => index,
+                _ => continue,
+            };
+
+            replies[index] = if packet.params.len() == expected[index].1 as usize {
+                Ok((packet.status(), packet.params.to_vec()))
             } else {
-                return (COMM::RxCorrupt, vec![]);
-            }
+                Err(COMM::RxCorrupt)
+            };
+            self.exchange.last_status = packet.status();
+            remaining -= 1;
         }
-        
-        (COMM::Success, all_data)
-    }
-
-    // 添加寄存器写入功能
-    pub fn reg_write_1byte(&mut self, scs_id: u32, address: u32, data: u8) -> COMM {
-        let mut tx_packet = vec![0u32; 8];
-        tx_packet[ID] = scs_id;
-        tx_packet[LENGTH] = 4;
-        tx_packet[INSTRUCTION] = INST::RegWrite as u32;
-        tx_packet[PARAMETER0] = address;
-        tx_packet[PARAMETER0 + 1] = data as u32;
-        
-        self.tx_packet(&mut tx_packet)
-    }
-
-    pub fn reg_write_2byte(&mut self, scs_id: u32, address: u32, data: u16) -> COMM {
-        let mut tx_packet = vec![0u32; 9];
-        tx_packet[ID] = scs_id;
-        tx_packet[LENGTH] = 5;
-        tx_packet[INSTRUCTION] = INST::RegWrite as u32;
-        tx_packet[PARAMETER0] = address;
-        tx_packet[PARAMETER0 + 1] = self.scs_lobyte(data as i32) as u32;
-        tx_packet[PARAMETER0 + 2] = self.scs_hibyte(data as i32) as u32;
-        
-        self.tx_packet(&mut tx_packet)
-    }
-
-    // 添加批量读取功能
-    pub fn bulk_read_tx(&mut self, param: Vec<u32>) -> COMM {
-        let param_length = param.len() as u32;
-        let mut tx_packet = vec![0u32; param_length as usize + 6];
-        
-        tx_packet[ID] = BROADCAST_ID as u32;
-        tx_packet[LENGTH] = param_length + 2;
-        tx_packet[INSTRUCTION] = INST::SyncRead as u32;
-        
-        for (i, &value) in param.iter().enumerate() {
-            tx_packet[PARAMETER0 + i] = value;
-        }
-        
-        self.tx_packet(&mut tx_packet)
+        replies
+    }
+
+    // 寄存器写入，等待动作指令后生效
+    pub fn reg_write_tx_rx(&mut self, scs_id: u32, address: u32, data: &[u8]) -> crate::Result<ServoStatus> {
+        Ok(self.request(&Request::write(scs_id, INST::RegWrite, address, data)?)?.status())
+    }
+
+    pub fn reg_write_1byte(&mut self, scs_id: u32, address: u32, data: u8) -> crate::Result<ServoStatus> {
+        self.reg_write_tx_rx(scs_id, address, &[data])
+    }
+
+    pub fn reg_write_2byte(&mut self, scs_id: u32, address: u32, data: u16) -> crate::Result<ServoStatus> {
+        let bytes = self.exchange.scs_end.split_word(data);
+        self.reg_write_tx_rx(scs_id, address, &bytes)
+    }
+
+    // 发送批量读取指令，param 为每个舵机的 [长度, ID, 起始地址]，指令参数以 0x00 开头
+    pub fn bulk_read_tx(&mut self, param: &[u8]) -> crate::Result<()> {
+        self.begin_transaction();
+        self.discard_stale(true);
+        let result = self.send(&Request::broadcast(INST::BulkRead, &[0x00], param));
+        self.end_transaction();
+        result
+    }
+
+    // 接收批量读取的应答，expected 为每个舵机的 (ID, 数据长度)，结果与其一一对应
+    pub fn bulk_read_rx(&mut self, expected: &[(u32, u32)]) -> Vec<SyncReadReply> {
+        self.read_replies(expected)
     }
 
     // 获取端口处理器的可变引用
-    pub fn get_port_handler_mut(&mut self) -> &mut PortHandler {
+    pub fn get_port_handler_mut(&mut self) -> &mut T {
         &mut self.port_handler
     }
 
     // 获取端口处理器的不可变引用
-    pub fn get_port_handler(&self) -> &PortHandler {
+    pub fn get_port_handler(&self) -> &T {
         &self.port_handler
     }
 }
@@ -0,0 +1,179 @@
+//! 控制表寄存器描述
+//!
+//! `Register` 记录寄存器的地址、宽度、符号位、存储区、读写权限、单位和取值范围，
+//! 各系列的控制表见 `sms_sts::regs` 与 `scscl::regs`。控制器的 `read_reg`/`write_reg`
+//! 按描述校验并编解码，字节序由控制器决定。
+
+use crate::{
+    protocol_packet_handler::{sign_magnitude_to_host, Endian},
+    FtServoError, Result,
+};
+
+// 寄存器所在的存储区：EEPROM 掉电保存，写入前需要解锁；SRAM 掉电丢失
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Area {
+    Eeprom,
+    Sram,
+}
+
+// 读写权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Access {
+    ReadOnly,
+    ReadWrite,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Register {
+    pub name: &'static str,
+    pub address: u8,
+    // 字节数：1 或 2
+    pub size: u8,
+    // 符号-数值编码的符号位，无符号寄存器为 None
+    pub sign_bit: Option<u8>,
+    pub area: Area,
+    pub access: Access,
+    // 原始值的单位，如 "step"、"0.1V"，无单位时为空
+    pub unit: &'static str,
+    pub min: i32,
+    pub max: i32,
+}
+
+impl Register {
+    // 可读写的 SRAM 无符号寄存器，取值范围为该宽度的全部无符号值
+    pub const fn new(name: &'static str, address: u8, size: u8) -> Self {
+        Self {
+            name,
+            address,
+            size,
+            sign_bit: None,
+            area: Area::Sram,
+            access: Access::ReadWrite,
+            unit: "",
+            min: 0,
+            max: if size == 1 { 0xFF } else { 0xFFFF },
+        }
+    }
+
+    // 符号-数值编码，取值范围为 ±(2^sign_bit - 1)
+    pub const fn signed(mut self, sign_bit: u8) -> Self {
+        self.sign_bit = Some(sign_bit);
+        self.max = (1 << sign_bit) - 1;
+        self.min = -self.max;
+        self
+    }
+
+    pub const fn eeprom(mut self) -> Self {
+        self.area = Area::Eeprom;
+        self
+    }
+
+    pub const fn read_only(mut self) -> Self {
+        self.access = Access::ReadOnly;
+        self
+    }
+
+    pub const fn unit(mut self, unit: &'static str) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    pub const fn range(mut self, min: i32, max: i32) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.access == Access::ReadWrite
+    }
+
+    // 检查写入权限和取值范围
+    pub fn check_write(&self, value: i32) -> Result<()> {
+        if !self.is_writable() {
+            return Err(FtServoError::InvalidParameter(format!("register {} is read-only", self.name)));
+        }
+        if value < self.min || value > self.max {
+            return Err(FtServoError::InvalidParameter(format!(
+                "value {} is out of range {}..={} for register {}",
+                value, self.min, self.max, self.name
+            )));
+        }
+        Ok(())
+    }
+
+    // 将读取到的原始字节解码为数值
+    pub fn decode(&self, end: &Endian, data: &[u8]) -> i32 {
+        let raw = match self.size {
+            1 => data[0] as i32,
+            _ => end.make_word(data[0], data[1]) as i32,
+        };
+        match self.sign_bit {
+            Some(bit) => sign_magnitude_to_host(raw, bit as i32),
+            None => raw,
+        }
+    }
+
+    // 将数值编码为写入的字节，先检查写入权限和取值范围
+    pub fn encode(&self, end: &Endian, value: i32) -> Result<Vec<u8>> {
+        self.check_write(value)?;
+
+        let raw = match self.sign_bit {
+            Some(bit) if value < 0 => (-value) as u16 | 1 << bit,
+            _ => value as u16,
+        };
+        Ok(match self.size {
+            1 => vec![raw as u8],
+            _ => end.split_word(raw).to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORD: Register = Register::new("WORD", 42, 2);
+    const SIGNED: Register = Register::new("SIGNED", 42, 2).signed(15);
+    const LOAD: Register = Register::new("LOAD", 60, 2).signed(10).read_only();
+
+    #[test]
+    fn word_follows_the_byte_order() {
+        assert_eq!(WORD.encode(&Endian::SmallEndian, 0x1234).unwrap(), [0x34, 0x12]);
+        assert_eq!(WORD.encode(&Endian::BigEndian, 0x1234).unwrap(), [0x12, 0x34]);
+        assert_eq!(WORD.decode(&Endian::SmallEndian, &[0x34, 0x12]), 0x1234);
+        assert_eq!(WORD.decode(&Endian::BigEndian, &[0x12, 0x34]), 0x1234);
+
+        let byte = Register::new("BYTE", 5, 1);
+        assert_eq!(byte.encode(&Endian::SmallEndian, 200).unwrap(), [200]);
+        assert_eq!(byte.decode(&Endian::SmallEndian, &[200]), 200);
+    }
+
+    #[test]
+    fn negative_values_set_the_sign_bit() {
+        assert_eq!(SIGNED.encode(&Endian::SmallEndian, -100).unwrap(), [100, 0x80]);
+        assert_eq!(SIGNED.encode(&Endian::SmallEndian, 100).unwrap(), [100, 0x00]);
+        assert_eq!(SIGNED.decode(&Endian::SmallEndian, &[100, 0x80]), -100);
+        assert_eq!(LOAD.decode(&Endian::SmallEndian, &[0x64, 0x04]), -100);
+        assert_eq!(LOAD.decode(&Endian::SmallEndian, &[0x64, 0x00]), 100);
+    }
+
+    #[test]
+    fn encode_rejects_values_out_of_range() {
+        assert_eq!((SIGNED.min, SIGNED.max), (-32767, 32767));
+        assert!(SIGNED.encode(&Endian::SmallEndian, 32767).is_ok());
+        assert!(matches!(SIGNED.encode(&Endian::SmallEndian, 32768), Err(FtServoError::InvalidParameter(_))));
+        assert!(matches!(SIGNED.encode(&Endian::SmallEndian, -32768), Err(FtServoError::InvalidParameter(_))));
+
+        let mode = Register::new("MODE", 33, 1).range(0, 3);
+        assert!(mode.encode(&Endian::SmallEndian, 3).is_ok());
+        assert!(matches!(mode.encode(&Endian::SmallEndian, 4), Err(FtServoError::InvalidParameter(_))));
+        assert!(matches!(WORD.encode(&Endian::SmallEndian, -1), Err(FtServoError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn encode_rejects_read_only_registers() {
+        assert!(!LOAD.is_writable());
+        assert!(matches!(LOAD.encode(&Endian::SmallEndian, 0), Err(FtServoError::InvalidParameter(_))));
+    }
+}
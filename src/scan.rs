@@ -0,0 +1,163 @@
+//! 总线扫描
+//!
+//! 依次切换到 `SUPPORTED_BAUDRATES` 中的波特率，对每个ID发送 Ping，找到的舵机再读取
+//! 固件版本（地址 0/1）和型号号（地址 3/4）。扫描结束后恢复端口原来的波特率。
+//! 扫描只依赖 `Transport`，串口、`SharedBus` 和虚拟总线都可以使用。
+
+use std::{ops::RangeInclusive, time::Duration};
+
+use crate::{
+    packet::InstructionPacket,
+    port_handler::SUPPORTED_BAUDRATES,
+    protocol_packet_handler::{Endian, ProtocolPacketHandler},
+    model::ServoModel,
+    scservo_def::{ServoStatus, INST, MAX_ID},
+    transport::Transport,
+    FtServoError, Result,
+};
+
+// 固件版本起始地址，读取 地址 0..=4：固件主/次版本、保留、型号号
+const INFO_ADDRESS: u8 = 0;
+const INFO_LEN: u8 = 5;
+
+// 读取舵机信息的尝试次数
+const INFO_RETRIES: usize = 3;
+
+// 扫描参数
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    // 依次尝试的波特率
+    pub baudrates: Vec<u32>,
+    pub ids: RangeInclusive<u8>,
+    // 每个ID等待应答的时间（在数据传输时间之外）
+    pub timeout: Duration,
+    // 解析型号号使用的字节序：SMS/STS 为小端，SCSCL 为大端
+    pub end: Endian,
+}
+
+impl Default for ScanOptions {
+    // 所有ID、所有支持的波特率（从高到低），每个ID等待 10ms
+    fn default() -> Self {
+        Self {
+            baudrates: SUPPORTED_BAUDRATES.iter().rev().copied().collect(),
+            ids: 0..=MAX_ID,
+            timeout: Duration::from_millis(10),
+            end: Endian::SmallEndian,
+        }
+    }
+}
+
+// 找到的舵机
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanResult {
+    pub baudrate: u32,
+    pub id: u8,
+    // Ping 应答中的舵机状态
+    pub status: ServoStatus,
+    // 型号号和固件版本（主, 次），读取失败时为 None
+    pub model: Option<u16>,
+    pub firmware: Option<(u8, u8)>,
+}
+
+impl ScanResult {
+    // 按型号号查找已知型号
+    pub fn servo_model(&self) -> Option<ServoModel> {
+        self.model.and_then(ServoModel::from_number)
+    }
+}
+
+// 在端口当前的波特率下扫描，适用于任何 Transport
+pub fn scan_ids<T: Transport>(ph: &mut ProtocolPacketHandler<T>, ids: RangeInclusive<u8>) -> Vec<ScanResult> {
+    let baudrate = ph.get_port_handler().get_baudrate();
+    let mut found = Vec::new();
+
+    for id in ids {
+        let status = match ph.ping(id as u32) {
+            Ok(info) => info.status,
+            Err(_) => continue,
+        };
+
+        let info = (0..INFO_RETRIES).find_map(|_| read_info(ph, id));
+        found.push(ScanResult {
+            baudrate,
+            id,
+            status,
+            model: info.map(|(model, _)| model),
+            firmware: info.map(|(_, firmware)| firmware),
+        });
+    }
+    found
+}
+
+// 读取型号号和固件版本；舵机报告故障时数据仍然有效
+fn read_info<T: Transport>(ph: &mut ProtocolPacketHandler<T>, id: u8) -> Option<(u16, (u8, u8))> {
+    let end = ph.scs_getend();
+    let params = [INFO_ADDRESS, INFO_LEN];
+    let packet = ph.tx_rx_packet(&InstructionPacket::new(id, INST::Read, &params)).ok()?;
+    match *packet.params {
+        [major, minor, _, model_a, model_b] => Some((end.make_word(model_a, model_b), (major, minor))),
+        _ => None,
+    }
+}
+
+// 遍历波特率扫描整条总线，结果按扫描顺序排列；适用于能切换波特率的任何 Transport
+pub fn scan<T: Transport>(port_handler: &mut T, options: &ScanOptions) -> Result<Vec<ScanResult>> {
+    if options.baudrates.is_empty() {
+        return Err(FtServoError::InvalidParameter("no baud rates to scan".to_string()));
+    }
+
+    let original_baudrate = port_handler.get_baudrate();
+    let mut found = Vec::new();
+    let mut result = Ok(());
+
+    for &baudrate in &options.baudrates {
+        if port_handler.get_baudrate() != baudrate {
+            if let Err(e) = port_handler.set_baudrate(baudrate) {
+                result = Err(e.into());
+                break;
+            }
+        }
+
+        let mut ph = ProtocolPacketHandler::new(&mut *port_handler, options.end.clone());
+        ph.set_latency_timer(options.timeout);
+        found.extend(scan_ids(&mut ph, options.ids.clone()));
+    }
+
+    // 恢复原来的波特率
+    if port_handler.get_baudrate() != original_baudrate {
+        port_handler.set_baudrate(original_baudrate)?;
+    }
+    result.map(|()| found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        scservo_def::BAUD_115200,
+        sim::{ServoChain, SimFamily, SimServo, VirtualBus},
+        sms_sts::SMS_STS_BAUD_RATE,
+    };
+
+    #[test]
+    fn scan_finds_servos_at_every_baud_rate() {
+        let mut chain = ServoChain::new();
+        chain.add_servo(SimServo::new(1, SimFamily::SmsSts));
+        let mut slow = SimServo::new(4, SimFamily::SmsSts);
+        slow.write_byte(SMS_STS_BAUD_RATE, BAUD_115200);
+        chain.add_servo(slow);
+        let mut bus = VirtualBus::new(chain);
+
+        let options = ScanOptions {
+            baudrates: vec![1_000_000, 115_200],
+            ids: 0..=5,
+            ..ScanOptions::default()
+        };
+        let found = scan(&mut bus, &options).unwrap();
+
+        let servos: Vec<(u32, u8)> = found.iter().map(|servo| (servo.baudrate, servo.id)).collect();
+        assert_eq!(servos, [(1_000_000, 1), (115_200, 4)]);
+        assert_eq!(found[0].servo_model().map(|model| model.name), Some("STS3215"));
+        assert_eq!(bus.get_baudrate(), 1_000_000);
+    }
+}
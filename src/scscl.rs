@@ -1,10 +1,19 @@
-use std::time::Duration;
+use std::collections::HashMap;
 
+#[cfg(feature = "config")]
+use crate::config::{self, BusConfig, Difference, ServoConfig};
 use crate::{
+    group_bulk_read::GroupBulkRead,
+    group_sync_read::GroupSyncRead,
     group_sync_write::GroupSyncWrite,
+    model::{Family, ServoModel},
     port_handler::PortHandler,
-    protocol_packet_handler::{Endian, ProtocolPacketHandler},
-    scservo_def::COMM,
+    protocol_packet_handler::{check_sync_lengths, signed_word, Endian, PingInfo, ProtocolPacketHandler},
+    register::Register,
+    scservo_def::ServoStatus,
+    setup,
+    transport::Transport,
+    Result,
 };
 
 // 波特率定义
@@ -59,262 +68,455 @@ pub const SCSCL_MOVING: u8 = 66;
 pub const SCSCL_PRESENT_CURRENT_L: u8 = 69;
 pub const SCSCL_PRESENT_CURRENT_H: u8 = 70;
 
+// 控制表
+pub mod regs {
+    use super::*;
+    use crate::{register::Register, scservo_def::MAX_ID};
+
+    // -------EPROM(只读)--------
+    pub const MODEL: Register = Register::new("MODEL", SCSCL_MODEL_L, 2).eeprom().read_only();
+
+    // -------EPROM(读写)--------
+    pub const ID: Register = Register::new("ID", SCSCL_ID, 1).eeprom().range(0, MAX_ID as i32);
+    pub const BAUD_RATE: Register = Register::new("BAUD_RATE", SCSCL_BAUD_RATE, 1).eeprom().range(0, 7);
+    pub const MIN_ANGLE_LIMIT: Register = Register::new("MIN_ANGLE_LIMIT", SCSCL_MIN_ANGLE_LIMIT_L, 2).eeprom().unit("step").range(0, 1023);
+    pub const MAX_ANGLE_LIMIT: Register = Register::new("MAX_ANGLE_LIMIT", SCSCL_MAX_ANGLE_LIMIT_L, 2).eeprom().unit("step").range(0, 1023);
+    pub const CW_DEAD: Register = Register::new("CW_DEAD", SCSCL_CW_DEAD, 1).eeprom().unit("step").range(0, 32);
+    pub const CCW_DEAD: Register = Register::new("CCW_DEAD", SCSCL_CCW_DEAD, 1).eeprom().unit("step").range(0, 32);
+    pub const OFS: Register = Register::new("OFS", SCSCL_OFS_L, 2).eeprom().signed(11).unit("step");
+    pub const MODE: Register = Register::new("MODE", SCSCL_MODE, 1).eeprom().range(0, 1);
+
+    // -------SRAM(读写)--------
+    pub const TORQUE_ENABLE: Register = Register::new("TORQUE_ENABLE", SCSCL_TORQUE_ENABLE, 1).range(0, 1);
+    pub const GOAL_POSITION: Register = Register::new("GOAL_POSITION", SCSCL_GOAL_POSITION_L, 2).unit("step").range(0, 1023);
+    pub const GOAL_TIME: Register = Register::new("GOAL_TIME", SCSCL_GOAL_TIME_L, 2).unit("ms");
+    pub const GOAL_SPEED: Register = Register::new("GOAL_SPEED", SCSCL_GOAL_SPEED_L, 2).signed(15).unit("step/s");
+    pub const LOCK: Register = Register::new("LOCK", SCSCL_LOCK, 1).range(0, 1);
+
+    // -------SRAM(只读)--------
+    pub const PRESENT_POSITION: Register = Register::new("PRESENT_POSITION", SCSCL_PRESENT_POSITION_L, 2).signed(15).read_only().unit("step");
+    pub const PRESENT_SPEED: Register = Register::new("PRESENT_SPEED", SCSCL_PRESENT_SPEED_L, 2).signed(15).read_only().unit("step/s");
+    pub const PRESENT_LOAD: Register = Register::new("PRESENT_LOAD", SCSCL_PRESENT_LOAD_L, 2).signed(10).read_only().unit("0.1%");
+    pub const PRESENT_VOLTAGE: Register = Register::new("PRESENT_VOLTAGE", SCSCL_PRESENT_VOLTAGE, 1).read_only().unit("0.1V");
+    pub const PRESENT_TEMPERATURE: Register = Register::new("PRESENT_TEMPERATURE", SCSCL_PRESENT_TEMPERATURE, 1).read_only().unit("°C");
+    pub const MOVING: Register = Register::new("MOVING", SCSCL_MOVING, 1).read_only();
+    pub const PRESENT_CURRENT: Register = Register::new("PRESENT_CURRENT", SCSCL_PRESENT_CURRENT_L, 2).signed(15).read_only();
+
+    // 所有寄存器，按地址排列
+    pub const TABLE: &[Register] = &[
+        MODEL, ID, BAUD_RATE, MIN_ANGLE_LIMIT, MAX_ANGLE_LIMIT, CW_DEAD, CCW_DEAD, OFS, MODE,
+        TORQUE_ENABLE, GOAL_POSITION, GOAL_TIME, GOAL_SPEED, LOCK,
+        PRESENT_POSITION, PRESENT_SPEED, PRESENT_LOAD, PRESENT_VOLTAGE, PRESENT_TEMPERATURE, MOVING, PRESENT_CURRENT,
+    ];
+}
+
+// 目标位置起始的位置控制数据长度：位置(2) + 时间(2) + 速度(2)
+pub(crate) const SCSCL_POS_LEN: u32 = 6;
+
+// 位置控制的同步写入参数：ID + 位置/时间/速度（大端）
+pub(crate) fn pos_param(scs_id: u32, position: i32, time: u32, speed: u32) -> [u8; 1 + SCSCL_POS_LEN as usize] {
+    let end = Endian::BigEndian;
+    let [pos_l, pos_h] = end.split_word(position as u16);
+    let [time_l, time_h] = end.split_word(time as u16);
+    let [speed_l, speed_h] = end.split_word(speed as u16);
+    [scs_id as u8, pos_l, pos_h, time_l, time_h, speed_l, speed_h]
+}
+
 #[derive(Debug)]
-pub struct Scscl {
-    ph: ProtocolPacketHandler,
-    group_sync_write: GroupSyncWrite,
+pub struct Scscl<T: Transport = PortHandler> {
+    ph: ProtocolPacketHandler<T>,
+    // 已检测的舵机型号
+    models: HashMap<u8, ServoModel>,
 }
 
-impl Scscl {
-    pub fn new(port_handler: PortHandler) -> Self {
-        let ph = ProtocolPacketHandler::new(port_handler, Endian::BigEndian);
-        let group_sync_write = GroupSyncWrite::new(
-            ProtocolPacketHandler::new(
-                PortHandler::new(&ph.port_handler.get_port_name()),
-                Endian::BigEndian,
-            ),
-            SCSCL_ACC as u32,
-            6,
-        );
-        
+impl<T: Transport> Scscl<T> {
+    pub fn new(port_handler: T) -> Self {
         Self {
-            ph,
-            group_sync_write,
+            ph: ProtocolPacketHandler::new(port_handler, Endian::BigEndian),
+            models: HashMap::new(),
         }
     }
 
-    // 写入位置、时间和速度
-    pub fn write_pos(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> COMM {
-        let mut data = vec![0u32; 6];
-        data[0] = 0; // ACC
-        data[1] = self.ph.scs_lobyte(position) as u32;
-        data[2] = self.ph.scs_hibyte(position) as u32;
-        data[3] = self.ph.scs_lobyte(time as i32) as u32;
-        data[4] = self.ph.scs_hibyte(time as i32) as u32;
-        data[5] = self.ph.scs_lobyte(speed as i32) as u32;
-        
-        match self.group_sync_write.add_param(scs_id, data) {
-            Ok(_) => self.group_sync_write.tx_packet(),
-            Err(_) => COMM::TxError,
+    // 获取端口处理器的可变引用
+    pub fn get_port_handler_mut(&mut self) -> &mut T {
+        &mut self.ph.port_handler
+    }
+
+    // 获取端口处理器的不可变引用
+    pub fn get_port_handler(&self) -> &T {
+        &self.ph.port_handler
+    }
+
+    // 最近一次应答中的舵机状态
+    pub fn last_status(&self) -> ServoStatus {
+        self.ph.last_status()
+    }
+
+    // 按控制表描述读取寄存器，如 read_reg(1, regs::PRESENT_POSITION)
+    pub fn read_reg(&mut self, scs_id: u32, reg: Register) -> Result<i32> {
+        self.ph.read_reg(scs_id, reg)
+    }
+
+    // 按控制表描述写入寄存器，检查读写权限和取值范围
+    pub fn write_reg(&mut self, scs_id: u32, reg: Register, value: i32) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, reg, value)
+    }
+
+    // 写入是否等待舵机应答，关闭后写入方法返回空状态
+    pub fn set_write_ack(&mut self, enabled: bool) {
+        self.ph.set_write_ack(enabled);
+    }
+
+    // 舵机报告故障时是否返回 Hardware 错误
+    pub fn set_strict_status(&mut self, enabled: bool) {
+        self.ph.set_strict_status(enabled);
+    }
+
+    // 读取型号号并缓存该舵机的型号，未知型号按 SCSCL 系列默认值处理
+    pub fn detect_model(&mut self, scs_id: u32) -> Result<ServoModel> {
+        let number = self.read_model(scs_id)?;
+        let model = ServoModel::from_number_or_generic(number, Family::Scscl);
+        self.models.insert(scs_id as u8, model);
+        Ok(model)
+    }
+
+    // 已检测或指定的型号
+    pub fn model(&self, scs_id: u32) -> Option<ServoModel> {
+        self.models.get(&(scs_id as u8)).copied()
+    }
+
+    // 指定舵机型号，不读取舵机
+    pub fn set_model(&mut self, scs_id: u32, model: ServoModel) {
+        self.models.insert(scs_id as u8, model);
+    }
+
+    // 读取 EEPROM 中所有可写寄存器
+    #[cfg(feature = "config")]
+    pub fn backup_config(&mut self, scs_id: u32) -> Result<ServoConfig> {
+        config::backup(&mut self.ph, Family::Scscl, scs_id)
+    }
+
+    // 依次备份多个舵机
+    #[cfg(feature = "config")]
+    pub fn backup_configs(&mut self, scs_ids: &[u32]) -> Result<BusConfig> {
+        config::backup_all(&mut self.ph, Family::Scscl, scs_ids)
+    }
+
+    // 将配置写入舵机，自动解锁和锁定 EEPROM；配置中的ID不同时最后修改ID
+    // 波特率不写入，与舵机当前值不同时作为差异返回，需要用 change_baud 修改
+    #[cfg(feature = "config")]
+    pub fn restore_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        let skipped = config::restore(&mut self.ph, Family::Scscl, scs_id, config)?;
+        if let Some(new_id) = config.id() {
+            self.move_model(scs_id, new_id);
         }
+        Ok(skipped)
     }
 
-    // 读取当前位置
-    pub fn read_pos(&mut self, scs_id: u32) -> Result<i32, COMM> {
-        let (data, result) = self.ph.read_2byte_tx_rx(scs_id, SCSCL_PRESENT_POSITION_L as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 7 {
-                    let pos = self.ph.scs_makeword(data[5] as i32, data[6] as i32);
-                    Ok(self.ph.scs_tohost(pos, 15))
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
+    // 按配置中记录的ID恢复每个舵机，返回各舵机没有写入的差异
+    #[cfg(feature = "config")]
+    pub fn restore_configs(&mut self, config: &BusConfig) -> Result<Vec<(u32, Difference)>> {
+        config::restore_all(&mut self.ph, Family::Scscl, config)
+    }
+
+    // 读取舵机当前的 EEPROM，返回与配置不一致的寄存器
+    #[cfg(feature = "config")]
+    pub fn verify_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        config::verify(&mut self.ph, Family::Scscl, scs_id, config)
+    }
+
+    // 只写入与配置不一致的寄存器（ID 和波特率除外），返回写入的差异
+    #[cfg(feature = "config")]
+    pub fn apply_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        config::apply(&mut self.ph, Family::Scscl, scs_id, config)
+    }
+
+    // 修改ID后，已检测的型号随ID迁移
+    fn move_model(&mut self, old_id: u32, new_id: u32) {
+        if let Some(model) = self.models.remove(&(old_id as u8)) {
+            self.models.insert(new_id as u8, model);
         }
     }
 
-    // 读取当前速度
-    pub fn read_speed(&mut self, scs_id: u32) -> Result<i32, COMM> {
-        let (data, result) = self.ph.read_2byte_tx_rx(scs_id, SCSCL_PRESENT_SPEED_L as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 7 {
-                    let speed = self.ph.scs_makeword(data[5] as i32, data[6] as i32);
-                    Ok(self.ph.scs_tohost(speed, 15))
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
+    // 缓存的型号，该舵机第一次角度读写时读取型号号并缓存
+    fn model_or_detect(&mut self, scs_id: u32) -> Result<ServoModel> {
+        match self.model(scs_id) {
+            Some(model) => Ok(model),
+            None => self.detect_model(scs_id),
         }
     }
 
+    // 读取当前位置（度），按型号换算
+    pub fn read_pos_degrees(&mut self, scs_id: u32) -> Result<f64> {
+        let model = self.model_or_detect(scs_id)?;
+        Ok(model.steps_to_degrees(self.read_pos(scs_id)?))
+    }
+
+    // 读取当前速度（度/秒），按型号换算
+    pub fn read_speed_degrees(&mut self, scs_id: u32) -> Result<f64> {
+        let model = self.model_or_detect(scs_id)?;
+        Ok(model.steps_to_degrees(self.read_speed(scs_id)?))
+    }
+
+    // 写入目标位置（度）和速度（度/秒），位置超出型号行程或速度超过型号最大速度时返回错误
+    pub fn write_pos_degrees(&mut self, scs_id: u32, degrees: f64, time: u32, speed: f64) -> Result<()> {
+        let (position, speed) = self.model_or_detect(scs_id)?.goal_steps(degrees, speed)?;
+        self.write_pos(scs_id, position, time, speed)
+    }
+
+    // 组装同步写入的参数：ID + ACC/位置/时间/速度
+    fn push_pos_param(&self, param: &mut Vec<u8>, scs_id: u32, position: i32, time: u32, speed: u32) {
+        param.extend_from_slice(&pos_param(scs_id, position, time, speed));
+    }
+
+    // 写入位置、时间和速度
+    pub fn write_pos(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> Result<()> {
+        let mut param = Vec::with_capacity(1 + SCSCL_POS_LEN as usize);
+        self.push_pos_param(&mut param, scs_id, position, time, speed);
+
+        self.ph.sync_write_tx_only(SCSCL_GOAL_POSITION_L as u32, SCSCL_POS_LEN, &param)
+    }
+
+    // 读取当前位置
+    pub fn read_pos(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, SCSCL_PRESENT_POSITION_L as u32)?;
+        Ok(self.ph.scs_tohost(data as i32, 15))
+    }
+
+    // 读取当前速度
+    pub fn read_speed(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, SCSCL_PRESENT_SPEED_L as u32)?;
+        Ok(self.ph.scs_tohost(data as i32, 15))
+    }
+
     // 读取当前负载
-    pub fn read_load(&mut self, scs_id: u32) -> Result<i32, COMM> {
-        let (data, result) = self.ph.read_2byte_tx_rx(scs_id, SCSCL_PRESENT_LOAD_L as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 7 {
-                    let load = self.ph.scs_makeword(data[5] as i32, data[6] as i32);
-                    Ok(self.ph.scs_tohost(load, 10))
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
-        }
+    pub fn read_load(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, SCSCL_PRESENT_LOAD_L as u32)?;
+        Ok(self.ph.scs_tohost(data as i32, 10))
     }
 
     // 读取电压
-    pub fn read_voltage(&mut self, scs_id: u32) -> Result<u8, COMM> {
-        let (data, result) = self.ph.read_1byte_tx_rx(scs_id, SCSCL_PRESENT_VOLTAGE as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 6 {
-                    Ok(data[5] as u8)
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
-        }
+    pub fn read_voltage(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, SCSCL_PRESENT_VOLTAGE as u32)
     }
 
     // 读取温度
-    pub fn read_temperature(&mut self, scs_id: u32) -> Result<u8, COMM> {
-        let (data, result) = self.ph.read_1byte_tx_rx(scs_id, SCSCL_PRESENT_TEMPERATURE as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 6 {
-                    Ok(data[5] as u8)
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
-        }
+    pub fn read_temperature(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, SCSCL_PRESENT_TEMPERATURE as u32)
+    }
+
+    // 读取块数据中 offset 处的双字节有符号值
+    fn signed_word(&self, data: &[u8], offset: usize, sign_bit: i32) -> i32 {
+        signed_word(&self.ph.scs_getend(), data, offset, sign_bit)
+    }
+
+    // 同时读取位置和速度（一次读取连续的4个字节）
+    pub fn read_pos_speed(&mut self, scs_id: u32) -> Result<(i32, i32)> {
+        let data = self.ph.read_tx_rx(scs_id, SCSCL_PRESENT_POSITION_L as u32, 4)?;
+        Ok((self.signed_word(&data, 0, 15), self.signed_word(&data, 2, 15)))
+    }
+
+    // 同时读取位置、速度和负载（一次读取连续的6个字节）
+    pub fn read_pos_speed_load(&mut self, scs_id: u32) -> Result<(i32, i32, i32)> {
+        let data = self.ph.read_tx_rx(scs_id, SCSCL_PRESENT_POSITION_L as u32, 6)?;
+        Ok((
+            self.signed_word(&data, 0, 15),
+            self.signed_word(&data, 2, 15),
+            self.signed_word(&data, 4, 10),
+        ))
+    }
+
+    // 同时读取电压和温度
+    pub fn read_voltage_temperature(&mut self, scs_id: u32) -> Result<(u8, u8)> {
+        let data = self.ph.read_tx_rx(scs_id, SCSCL_PRESENT_VOLTAGE as u32, 2)?;
+        Ok((data[0], data[1]))
     }
 
     // 读取是否在运动
-    pub fn read_moving(&mut self, scs_id: u32) -> Result<bool, COMM> {
-        let (data, result) = self.ph.read_1byte_tx_rx(scs_id, SCSCL_MOVING as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 6 {
-                    Ok(data[5] != 0)
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
-        }
+    pub fn read_moving(&mut self, scs_id: u32) -> Result<bool> {
+        let data = self.ph.read_1byte_tx_rx(scs_id, SCSCL_MOVING as u32)?;
+        Ok(data != 0)
     }
 
     // 读取电流
-    pub fn read_current(&mut self, scs_id: u32) -> Result<i32, COMM> {
-        let (data, result) = self.ph.read_2byte_tx_rx(scs_id, SCSCL_PRESENT_CURRENT_L as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 7 {
-                    let current = self.ph.scs_makeword(data[5] as i32, data[6] as i32);
-                    Ok(self.ph.scs_tohost(current, 15))
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
-        }
+    pub fn read_current(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, SCSCL_PRESENT_CURRENT_L as u32)?;
+        Ok(self.ph.scs_tohost(data as i32, 15))
     }
 
     // 同步写入多个舵机位置
-    pub fn sync_write_pos(&mut self, scs_ids: Vec<u32>, positions: Vec<i32>, times: Vec<u32>, speeds: Vec<u32>) -> COMM {
-        if scs_ids.len() != positions.len() || positions.len() != times.len() || times.len() != speeds.len() {
-            return COMM::TxError;
-        }
+    pub fn sync_write_pos(&mut self, scs_ids: Vec<u32>, positions: Vec<i32>, times: Vec<u32>, speeds: Vec<u32>) -> Result<()> {
+        check_sync_lengths(scs_ids.len(), positions.len(), times.len(), speeds.len())?;
 
-        self.group_sync_write.clear_param();
-        
+        let mut param = Vec::with_capacity(scs_ids.len() * (1 + SCSCL_POS_LEN as usize));
         for i in 0..scs_ids.len() {
-            let mut data = vec![0u32; 6];
-            data[0] = 0; // ACC
-            data[1] = self.ph.scs_lobyte(positions[i]) as u32;
-            data[2] = self.ph.scs_hibyte(positions[i]) as u32;
-            data[3] = self.ph.scs_lobyte(times[i] as i32) as u32;
-            data[4] = self.ph.scs_hibyte(times[i] as i32) as u32;
-            data[5] = self.ph.scs_lobyte(speeds[i] as i32) as u32;
-            
-            if let Err(_) = self.group_sync_write.add_param(scs_ids[i], data) {
-                return COMM::TxError;
-            }
+            self.push_pos_param(&mut param, scs_ids[i], positions[i], times[i], speeds[i]);
         }
-        
-        self.group_sync_write.tx_packet()
+
+        self.ph.sync_write_tx_only(SCSCL_GOAL_POSITION_L as u32, SCSCL_POS_LEN, &param)
     }
 
     // 设置舵机模式
-    pub fn wheel_mode(&mut self, scs_id: u32, mode: u8) -> COMM {
+    pub fn wheel_mode(&mut self, scs_id: u32, mode: u8) -> Result<ServoStatus> {
         self.ph.write_1byte_tx_rx(scs_id, SCSCL_MODE as u32, mode)
     }
 
     // 设置扭矩使能
-    pub fn write_torque_enable(&mut self, scs_id: u32, enable: bool) -> COMM {
+    pub fn write_torque_enable(&mut self, scs_id: u32, enable: bool) -> Result<ServoStatus> {
         self.ph.write_1byte_tx_rx(scs_id, SCSCL_TORQUE_ENABLE as u32, if enable { 1 } else { 0 })
     }
 
     // 设置角度限制
-    pub fn write_angle_limit(&mut self, scs_id: u32, min_angle: i32, max_angle: i32) -> COMM {
-        let result1 = self.ph.write_2byte_tx_rx(scs_id, SCSCL_MIN_ANGLE_LIMIT_L as u32, min_angle as u16);
-        if result1 != COMM::Success {
-            return result1;
-        }
-        self.ph.write_2byte_tx_rx(scs_id, SCSCL_MAX_ANGLE_LIMIT_L as u32, max_angle as u16)
+    pub fn write_angle_limit(&mut self, scs_id: u32, min_angle: i32, max_angle: i32) -> Result<ServoStatus> {
+        let status = self.ph.write_2byte_tx_rx(scs_id, SCSCL_MIN_ANGLE_LIMIT_L as u32, min_angle as u16)?;
+        Ok(status | self.ph.write_2byte_tx_rx(scs_id, SCSCL_MAX_ANGLE_LIMIT_L as u32, max_angle as u16)?)
     }
 
     // 设置死区
-    pub fn write_dead_zone(&mut self, scs_id: u32, cw_dead: u8, ccw_dead: u8) -> COMM {
-        let result1 = self.ph.write_1byte_tx_rx(scs_id, SCSCL_CW_DEAD as u32, cw_dead);
-        if result1 != COMM::Success {
-            return result1;
-        }
-        self.ph.write_1byte_tx_rx(scs_id, SCSCL_CCW_DEAD as u32, ccw_dead)
+    pub fn write_dead_zone(&mut self, scs_id: u32, cw_dead: u8, ccw_dead: u8) -> Result<ServoStatus> {
+        let status = self.ph.write_1byte_tx_rx(scs_id, SCSCL_CW_DEAD as u32, cw_dead)?;
+        Ok(status | self.ph.write_1byte_tx_rx(scs_id, SCSCL_CCW_DEAD as u32, ccw_dead)?)
     }
 
-    // 设置偏移量
-    pub fn write_offset(&mut self, scs_id: u32, offset: i32) -> COMM {
-        self.ph.write_2byte_tx_rx(scs_id, SCSCL_OFS_L as u32, offset as u16)
+    // 设置偏移量，按 regs::OFS 的符号位编码
+    pub fn write_offset(&mut self, scs_id: u32, offset: i32) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, regs::OFS, offset)
     }
 
     // 锁定EPROM
-    pub fn lock_eprom(&mut self, scs_id: u32) -> COMM {
+    pub fn lock_eprom(&mut self, scs_id: u32) -> Result<ServoStatus> {
         self.ph.write_1byte_tx_rx(scs_id, SCSCL_LOCK as u32, 1)
     }
 
     // 解锁EPROM
-    pub fn unlock_eprom(&mut self, scs_id: u32) -> COMM {
+    pub fn unlock_eprom(&mut self, scs_id: u32) -> Result<ServoStatus> {
         self.ph.write_1byte_tx_rx(scs_id, SCSCL_LOCK as u32, 0)
     }
 
-    // 设置波特率
-    pub fn write_baudrate(&mut self, scs_id: u32, baudrate: u8) -> COMM {
-        self.ph.write_1byte_tx_rx(scs_id, SCSCL_BAUD_RATE as u32, baudrate)
+    // 设置波特率寄存器（SCSCL_1M 等），只写入寄存器，完整流程见 change_baud
+    // 舵机写入后以新波特率应答，只发送不等待
+    pub fn write_baudrate(&mut self, scs_id: u32, baudrate: u8) -> Result<()> {
+        self.ph.write_1byte_tx_only(scs_id, SCSCL_BAUD_RATE as u32, baudrate)
     }
 
-    // 设置ID
-    pub fn write_id(&mut self, scs_id: u32, new_id: u8) -> COMM {
-        self.ph.write_1byte_tx_rx(scs_id, SCSCL_ID as u32, new_id)
+    // 设置ID，只写入寄存器（需先解锁 EEPROM），完整流程见 change_id
+    // 舵机写入后以新ID应答，只发送不等待
+    pub fn write_id(&mut self, scs_id: u32, new_id: u8) -> Result<()> {
+        self.ph.write_1byte_tx_only(scs_id, SCSCL_ID as u32, new_id)?;
+        self.move_model(scs_id, new_id as u32);
+        Ok(())
+    }
+
+    // 安全修改ID：确认目标ID空闲，自动解锁和锁定 EEPROM，最后在新ID上 Ping 确认
+    pub fn change_id(&mut self, old_id: u32, new_id: u32) -> Result<()> {
+        setup::change_id(&mut self.ph, Family::Scscl, old_id, new_id)?;
+        self.move_model(old_id, new_id);
+        Ok(())
+    }
+
+    // 修改波特率（如 115200）：舵机切换后本地端口随之切换，在新波特率下锁定 EEPROM 并确认
+    // 总线上的其他舵机仍使用原波特率，需要一起修改时使用 change_baud_all
+    pub fn change_baud(&mut self, scs_id: u32, baudrate: u32) -> Result<()> {
+        setup::change_baud(&mut self.ph, Family::Scscl, &[scs_id], baudrate)
+    }
+
+    // 同时修改多个舵机的波特率，端口只切换一次
+    pub fn change_baud_all(&mut self, scs_ids: &[u32], baudrate: u32) -> Result<()> {
+        setup::change_baud(&mut self.ph, Family::Scscl, scs_ids, baudrate)
     }
 
     // 读取模型号
-    pub fn read_model(&mut self, scs_id: u32) -> Result<u16, COMM> {
-        let (data, result) = self.ph.read_2byte_tx_rx(scs_id, SCSCL_MODEL_L as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 7 {
-                    let model = self.ph.scs_makeword(data[5] as i32, data[6] as i32) as u16;
-                    Ok(model)
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
-        }
+    pub fn read_model(&mut self, scs_id: u32) -> Result<u16> {
+        self.ph.read_2byte_tx_rx(scs_id, SCSCL_MODEL_L as u32)
     }
 
-    // ping测试
-    pub fn ping(&mut self, scs_id: u32) -> COMM {
+    // ping测试，等待应答
+    pub fn ping(&mut self, scs_id: u32) -> Result<PingInfo> {
         self.ph.ping(scs_id)
     }
 
+    // Ping舵机并读取型号号
+    pub fn ping_with_model(&mut self, scs_id: u32) -> Result<PingInfo> {
+        self.ph.ping_with_model(scs_id)
+    }
+
     // 寄存器写入
-    pub fn reg_write_pos(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> COMM {
-        // 实现寄存器写入位置控制
-        self.ph.write_2byte_tx_rx(scs_id, SCSCL_GOAL_POSITION_L as u32, position as u16)
+    // 一条 RegWrite 写入位置/时间/速度，舵机在 reg_action 后才开始运动
+    pub fn reg_write_pos(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> Result<ServoStatus> {
+        let param = pos_param(scs_id, position, time, speed);
+        self.ph.reg_write_tx_rx(scs_id, SCSCL_GOAL_POSITION_L as u32, &param[1..])
     }
 
-    // 执行寄存器写入的动作
-    pub fn reg_action(&mut self, scs_id: u32) -> COMM {
+    // 执行寄存器写入的动作，scs_id 为 BROADCAST_ID 时所有舵机同时开始
+    pub fn reg_action(&mut self, scs_id: u32) -> Result<ServoStatus> {
         self.ph.action(scs_id)
     }
 }
+
+// 可克隆的总线（如 SharedBus）上，group 读写与控制器共用同一个端口
+impl<T: Transport + Clone> Scscl<T> {
+    // 在同一条总线上创建同步写入
+    pub fn group_sync_write(&self, start_address: u32, data_length: u32) -> GroupSyncWrite<T> {
+        let ph = ProtocolPacketHandler::new(self.ph.port_handler.clone(), self.ph.scs_getend());
+        GroupSyncWrite::new(ph, start_address, data_length)
+    }
+
+    // 在同一条总线上创建同步读取
+    pub fn group_sync_read(&self, start_address: u32, data_length: u32) -> GroupSyncRead<T> {
+        let ph = ProtocolPacketHandler::new(self.ph.port_handler.clone(), self.ph.scs_getend());
+        GroupSyncRead::new(ph, start_address, data_length)
+    }
+
+    // 在同一条总线上创建批量读取
+    pub fn group_bulk_read(&self) -> GroupBulkRead<T> {
+        let ph = ProtocolPacketHandler::new(self.ph.port_handler.clone(), self.ph.scs_getend());
+        GroupBulkRead::new(ph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::sim::{sim_bus, SimClock, SimFamily};
+
+    #[test]
+    fn write_offset_matches_the_ofs_register() {
+        let mut scscl = Scscl::new(sim_bus(SimFamily::Scscl, &[1]));
+        scscl.write_offset(1, -50).unwrap();
+        assert_eq!(scscl.read_reg(1, regs::OFS).unwrap(), -50);
+    }
+
+    #[test]
+    fn reg_write_pos_waits_for_reg_action() {
+        let mut scscl = Scscl::new(sim_bus(SimFamily::Scscl, &[1]));
+        scscl.get_port_handler_mut().set_clock(SimClock::Manual);
+        scscl.write_torque_enable(1, true).unwrap();
+
+        scscl.reg_write_pos(1, 800, 0, 500).unwrap();
+        scscl.get_port_handler_mut().advance(Duration::from_secs(1));
+        assert_eq!(scscl.read_reg(1, regs::GOAL_POSITION).unwrap(), 512);
+        assert_eq!(scscl.get_port_handler().chain().servo(1).unwrap().position(), 512.0);
+
+        scscl.reg_action(1).unwrap();
+        assert_eq!(scscl.read_reg(1, regs::GOAL_POSITION).unwrap(), 800);
+        scscl.get_port_handler_mut().advance(Duration::from_millis(100));
+        assert!(scscl.get_port_handler().chain().servo(1).unwrap().position() > 512.0);
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn config_backup_keeps_the_offset() {
+        let mut scscl = Scscl::new(sim_bus(SimFamily::Scscl, &[1, 2]));
+        scscl.write_offset(1, -50).unwrap();
+
+        let config = scscl.backup_config(1).unwrap();
+        assert_eq!(config.get("OFS"), Some(-50));
+
+        let mut replacement = config.clone();
+        replacement.set("ID", 2);
+        scscl.restore_config(2, &replacement).unwrap();
+        assert_eq!(scscl.read_reg(2, regs::OFS).unwrap(), -50);
+    }
+}
@@ -4,7 +4,7 @@ pub const SCS_END: u8 = 0;
 
 // Instruction for SCS Protocol
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum INST {
     Ping = 1,
     Read = 2,
@@ -13,6 +13,25 @@ pub enum INST {
     Action = 5,
     SyncWrite = 131, // 0x83
     SyncRead = 130,  // 0x82
+    BulkRead = 146,  // 0x92
+}
+
+impl TryFrom<u8> for INST {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(INST::Ping),
+            2 => Ok(INST::Read),
+            3 => Ok(INST::Write),
+            4 => Ok(INST::RegWrite),
+            5 => Ok(INST::Action),
+            131 => Ok(INST::SyncWrite),
+            130 => Ok(INST::SyncRead),
+            146 => Ok(INST::BulkRead),
+            _ => Err(value),
+        }
+    }
 }
 
 // Communication Result
@@ -30,16 +49,49 @@ pub enum COMM {
     NotAvailable = -9,
 }
 
-// 错误代码定义
-#[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum ErrorCode {
-    Success = 0,
-    VoltageError = 1,
-    AngleError = 2,
-    OverheatError = 4,
-    OverElementError = 8,
-    OverloadError = 32,
+// 舵机状态字节（状态包第 4 字节），为位掩码，多个故障可同时置位
+bitflags::bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct ServoStatus: u8 {
+        // 输入电压超出范围
+        const VOLTAGE = 1 << 0;
+        // 角度传感器错误
+        const ANGLE = 1 << 1;
+        // 过热
+        const OVERHEAT = 1 << 2;
+        // 过流
+        const OVERCURRENT = 1 << 3;
+        // 过载
+        const OVERLOAD = 1 << 5;
+    }
+}
+
+impl std::fmt::Display for ServoStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "ok");
+        }
+
+        let names = [
+            (ServoStatus::VOLTAGE, "input voltage"),
+            (ServoStatus::ANGLE, "angle sensor"),
+            (ServoStatus::OVERHEAT, "overheat"),
+            (ServoStatus::OVERCURRENT, "over-current"),
+            (ServoStatus::OVERLOAD, "overload"),
+        ];
+        let mut faults: Vec<String> = names
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| name.to_string())
+            .collect();
+
+        // 未定义的位
+        let unknown = self.bits() & !ServoStatus::all().bits();
+        if unknown != 0 {
+            faults.push(format!("unknown 0x{:02X}", unknown));
+        }
+        write!(f, "{}", faults.join(", "))
+    }
 }
 
 // 舵机模式定义
@@ -55,3 +107,11 @@ pub const BAUD_115200: u8 = 4;
 pub const BAUD_76800: u8 = 5;
 pub const BAUD_57600: u8 = 6;
 pub const BAUD_38400: u8 = 7;
+
+// 波特率寄存器的取值对应的波特率，下标即寄存器值
+pub const BAUD_RATES: [u32; 8] = [1_000_000, 500_000, 250_000, 128_000, 115_200, 76_800, 57_600, 38_400];
+
+// 波特率对应的寄存器值
+pub fn baud_rate_code(baudrate: u32) -> Option<u8> {
+    BAUD_RATES.iter().position(|&rate| rate == baudrate).map(|code| code as u8)
+}
@@ -0,0 +1,330 @@
+//! 修改舵机ID与波特率
+//!
+//! `set_id`、`set_baudrate` 等方法只写入一个寄存器。这里的流程先确认舵机在线、目标ID空闲，
+//! 然后解锁 EEPROM、写入、重新锁定，最后在新的ID或波特率下 Ping 确认。修改波特率时
+//! 本地端口随舵机一起切换。
+
+use crate::{
+    model::{family_regs, Family, FamilyRegs},
+    protocol_packet_handler::{PingInfo, ProtocolPacketHandler},
+    scservo_def::{baud_rate_code, MAX_ID},
+    transport::Transport,
+    FtServoError, Result,
+};
+
+// 目标ID上是否有舵机应答；应答损坏（如多个舵机同一ID）时返回错误
+pub(crate) fn id_in_use<T: Transport>(ph: &mut ProtocolPacketHandler<T>, scs_id: u32) -> Result<bool> {
+    answered(ph.ping(scs_id))
+}
+
+// Ping 超时表示没有舵机使用该ID
+fn answered(ping: Result<PingInfo>) -> Result<bool> {
+    match ping {
+        Ok(_) => Ok(true),
+        Err(FtServoError::Timeout { .. }) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+pub(crate) fn id_taken(new_id: u32) -> FtServoError {
+    FtServoError::InvalidParameter(format!("ID {} is already in use", new_id))
+}
+
+fn check_new_id(new_id: u32) -> Result<()> {
+    if new_id > MAX_ID as u32 {
+        return Err(FtServoError::InvalidParameter(format!(
+            "new ID {} is out of range 0..={}",
+            new_id, MAX_ID
+        )));
+    }
+    Ok(())
+}
+
+// 波特率对应的寄存器值
+fn baud_code(scs_ids: &[u32], baudrate: u32) -> Result<u8> {
+    let code = baud_rate_code(baudrate)
+        .ok_or_else(|| FtServoError::InvalidParameter(format!("unsupported baud rate {}", baudrate)))?;
+    if scs_ids.is_empty() {
+        return Err(FtServoError::InvalidParameter("no servo ids given".to_string()));
+    }
+    Ok(code)
+}
+
+// 重新锁定失败时，原来的错误包装为 EepromUnlocked
+fn relock_error<E>(scs_id: u32, relocked: std::result::Result<E, FtServoError>, error: FtServoError) -> FtServoError {
+    match relocked {
+        Ok(_) => error,
+        Err(_) => FtServoError::EepromUnlocked {
+            id: scs_id as u8,
+            source: Box::new(error),
+        },
+    }
+}
+
+// 无法访问、仍处于解锁状态的舵机依次包装为 EepromUnlocked
+fn unlocked(scs_ids: &[u32], error: FtServoError) -> FtServoError {
+    scs_ids.iter().rev().fold(error, |error, &scs_id| FtServoError::EepromUnlocked {
+        id: scs_id as u8,
+        source: Box::new(error),
+    })
+}
+
+pub(crate) fn change_id<T: Transport>(
+    ph: &mut ProtocolPacketHandler<T>,
+    family: Family,
+    old_id: u32,
+    new_id: u32,
+) -> Result<()> {
+    check_new_id(new_id)?;
+
+    ph.ping(old_id)?;
+    if old_id == new_id {
+        return Ok(());
+    }
+    if id_in_use(ph, new_id)? {
+        return Err(id_taken(new_id));
+    }
+
+    let regs = family_regs(family);
+    ph.write_reg(old_id, regs.lock, 0)?;
+    write_id_and_lock(ph, &regs, old_id, new_id)?;
+    ph.ping(new_id)?;
+    Ok(())
+}
+
+// 在已解锁的舵机上写入新ID，然后在新ID上锁定 EEPROM
+// 舵机写入后以新ID应答，只发送不等待；写入ID失败时在原ID上、锁定失败时在新ID上重新锁定
+pub(crate) fn write_id_and_lock<T: Transport>(
+    ph: &mut ProtocolPacketHandler<T>,
+    regs: &FamilyRegs,
+    old_id: u32,
+    new_id: u32,
+) -> Result<()> {
+    if new_id != old_id {
+        if let Err(e) = ph.write_tx_only(old_id, regs.id.address as u32, &[new_id as u8]) {
+            return Err(relock(ph, regs, old_id, e));
+        }
+    }
+    match ph.write_reg(new_id, regs.lock, 1) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(relock(ph, regs, new_id, e)),
+    }
+}
+
+// 出错后尝试重新锁定 EEPROM，成功时返回原来的错误，仍未锁定时返回 EepromUnlocked
+pub(crate) fn relock<T: Transport>(
+    ph: &mut ProtocolPacketHandler<T>,
+    regs: &FamilyRegs,
+    scs_id: u32,
+    error: FtServoError,
+) -> FtServoError {
+    relock_error(scs_id, ph.write_reg(scs_id, regs.lock, 1), error)
+}
+
+// 逐个重新锁定，未能锁定的舵机依次包装为 EepromUnlocked
+fn relock_all<T: Transport>(
+    ph: &mut ProtocolPacketHandler<T>,
+    regs: &FamilyRegs,
+    scs_ids: &[u32],
+    mut error: FtServoError,
+) -> FtServoError {
+    for &scs_id in scs_ids.iter().rev() {
+        error = relock(ph, regs, scs_id, error);
+    }
+    error
+}
+
+// 部分舵机已切换波特率时出错：它们只能在新波特率下访问，切换端口后重新锁定
+// 端口随之留在新波特率，其余舵机仍在原波特率
+fn relock_switched<T: Transport>(
+    ph: &mut ProtocolPacketHandler<T>,
+    regs: &FamilyRegs,
+    written: &[u32],
+    baudrate: u32,
+    error: FtServoError,
+) -> FtServoError {
+    if written.is_empty() {
+        return error;
+    }
+    if ph.port_handler.set_baudrate(baudrate).is_err() {
+        return unlocked(written, error);
+    }
+    let _ = ph.port_handler.clear_port();
+    relock_all(ph, regs, written, error)
+}
+
+// 所有舵机先在当前波特率下写入，再切换端口，在新波特率下锁定并确认
+pub(crate) fn change_baud<T: Transport>(
+    ph: &mut ProtocolPacketHandler<T>,
+    family: Family,
+    scs_ids: &[u32],
+    baudrate: u32,
+) -> Result<()> {
+    let code = baud_code(scs_ids, baudrate)?;
+
+    for &scs_id in scs_ids {
+        ph.ping(scs_id)?;
+    }
+    if ph.port_handler.get_baudrate() == baudrate {
+        return Ok(());
+    }
+
+    let regs = family_regs(family);
+    for (index, &scs_id) in scs_ids.iter().enumerate() {
+        // 舵机写入后立即切换波特率，应答无法在原波特率下接收，只发送不等待
+        let result = ph
+            .write_reg(scs_id, regs.lock, 0)
+            .and_then(|_| ph.write_tx_only(scs_id, regs.baud_rate.address as u32, &[code]));
+        if let Err(e) = result {
+            let e = relock(ph, &regs, scs_id, e);
+            return Err(relock_switched(ph, &regs, &scs_ids[..index], baudrate, e));
+        }
+    }
+
+    if let Err(e) = ph.port_handler.set_baudrate(baudrate) {
+        return Err(unlocked(scs_ids, e.into()));
+    }
+    if let Err(e) = ph.port_handler.clear_port() {
+        return Err(relock_all(ph, &regs, scs_ids, e.into()));
+    }
+    for (index, &scs_id) in scs_ids.iter().enumerate() {
+        if let Err(e) = ph.write_reg(scs_id, regs.lock, 1) {
+            return Err(relock_all(ph, &regs, &scs_ids[index..], e));
+        }
+    }
+    for &scs_id in scs_ids {
+        ph.ping(scs_id)?;
+    }
+    Ok(())
+}
+
+// 异步客户端的相同流程，检查与错误处理与上面共用
+#[cfg(feature = "async")]
+pub(crate) mod asynchronous {
+    use tokio::io::{AsyncRead, AsyncWrite};
+
+    use super::*;
+    use crate::async_client::AsyncPacketHandler;
+
+    pub(crate) async fn id_in_use<S: AsyncRead + AsyncWrite + Unpin>(ph: &mut AsyncPacketHandler<S>, scs_id: u32) -> Result<bool> {
+        answered(ph.ping(scs_id).await)
+    }
+
+    pub(crate) async fn change_id<S: AsyncRead + AsyncWrite + Unpin>(
+        ph: &mut AsyncPacketHandler<S>,
+        family: Family,
+        old_id: u32,
+        new_id: u32,
+    ) -> Result<()> {
+        check_new_id(new_id)?;
+
+        ph.ping(old_id).await?;
+        if old_id == new_id {
+            return Ok(());
+        }
+        if id_in_use(ph, new_id).await? {
+            return Err(id_taken(new_id));
+        }
+
+        let regs = family_regs(family);
+        ph.write_reg(old_id, regs.lock, 0).await?;
+        write_id_and_lock(ph, &regs, old_id, new_id).await?;
+        ph.ping(new_id).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn write_id_and_lock<S: AsyncRead + AsyncWrite + Unpin>(
+        ph: &mut AsyncPacketHandler<S>,
+        regs: &FamilyRegs,
+        old_id: u32,
+        new_id: u32,
+    ) -> Result<()> {
+        if new_id != old_id {
+            if let Err(e) = ph.write_tx_only(old_id, regs.id.address as u32, &[new_id as u8]).await {
+                return Err(relock(ph, regs, old_id, e).await);
+            }
+        }
+        match ph.write_reg(new_id, regs.lock, 1).await {
+            Ok(_) => Ok(()),
+            Err(e) => Err(relock(ph, regs, new_id, e).await),
+        }
+    }
+
+    pub(crate) async fn relock<S: AsyncRead + AsyncWrite + Unpin>(
+        ph: &mut AsyncPacketHandler<S>,
+        regs: &FamilyRegs,
+        scs_id: u32,
+        error: FtServoError,
+    ) -> FtServoError {
+        relock_error(scs_id, ph.write_reg(scs_id, regs.lock, 1).await, error)
+    }
+
+    async fn relock_all<S: AsyncRead + AsyncWrite + Unpin>(
+        ph: &mut AsyncPacketHandler<S>,
+        regs: &FamilyRegs,
+        scs_ids: &[u32],
+        mut error: FtServoError,
+    ) -> FtServoError {
+        for &scs_id in scs_ids.iter().rev() {
+            error = relock(ph, regs, scs_id, error).await;
+        }
+        error
+    }
+
+    // switch 在所有舵机写入后切换字节流的波特率
+    pub(crate) async fn change_baud<S, F>(
+        ph: &mut AsyncPacketHandler<S>,
+        family: Family,
+        scs_ids: &[u32],
+        baudrate: u32,
+        switch: F,
+    ) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+        F: FnOnce(&mut S, u32) -> Result<()>,
+    {
+        let code = baud_code(scs_ids, baudrate)?;
+
+        for &scs_id in scs_ids {
+            ph.ping(scs_id).await?;
+        }
+        if ph.get_baudrate() == baudrate {
+            return Ok(());
+        }
+
+        let regs = family_regs(family);
+        for (index, &scs_id) in scs_ids.iter().enumerate() {
+            let mut result = ph.write_reg(scs_id, regs.lock, 0).await.map(|_| ());
+            if result.is_ok() {
+                result = ph.write_tx_only(scs_id, regs.baud_rate.address as u32, &[code]).await;
+            }
+            if let Err(e) = result {
+                let e = relock(ph, &regs, scs_id, e).await;
+                let written = &scs_ids[..index];
+                if written.is_empty() {
+                    return Err(e);
+                }
+                // 已写入的舵机只能在新波特率下访问，切换后重新锁定
+                if switch(ph.get_stream_mut(), baudrate).is_err() {
+                    return Err(unlocked(written, e));
+                }
+                ph.set_baudrate(baudrate);
+                return Err(relock_all(ph, &regs, written, e).await);
+            }
+        }
+
+        if let Err(e) = switch(ph.get_stream_mut(), baudrate) {
+            return Err(unlocked(scs_ids, e));
+        }
+        ph.set_baudrate(baudrate);
+        for (index, &scs_id) in scs_ids.iter().enumerate() {
+            if let Err(e) = ph.write_reg(scs_id, regs.lock, 1).await {
+                return Err(relock_all(ph, &regs, &scs_ids[index..], e).await);
+            }
+        }
+        for &scs_id in scs_ids {
+            ph.ping(scs_id).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1013 @@
+//! 虚拟舵机总线，用于在没有硬件的情况下测试
+//!
+//! `ServoChain` 在字节层面模拟一串 SMS/STS 与 SCSCL 舵机，每个系列按自己的控制表，
+//! `VirtualBus` 将其包装为 `Transport`，可直接交给 `SmsSts`、`Scscl` 与 group 读写使用。启用 `async` feature 后，
+//! `ServoChain::serve_async` 可以在 `tokio::io::duplex` 等异步字节流的另一端模拟舵机。
+
+use std::{
+    collections::VecDeque,
+    io::{self, ErrorKind},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    packet::{InstructionPacket, StatusPacket, HEADER},
+    scscl,
+    scservo_def::{BAUD_RATES, BROADCAST_ID, INST},
+    sms_sts,
+    transport::Transport,
+};
+
+// 控制表大小
+const TABLE_SIZE: usize = 256;
+
+// 默认型号号
+const DEFAULT_SMS_STS_MODEL: u16 = 777;
+const DEFAULT_SCSCL_MODEL: u16 = 1284;
+
+// 运动仿真的积分步长
+const SIM_STEP: Duration = Duration::from_millis(1);
+// 环境温度（°C）
+const AMBIENT_TEMPERATURE: f64 = 30.0;
+// 温度变化时间常数（秒）
+const THERMAL_TIME_CONSTANT: f64 = 60.0;
+
+// 舵机系列，决定控制表字节序与位置分辨率
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimFamily {
+    SmsSts,
+    Scscl,
+}
+
+impl SimFamily {
+    // 位置最大值
+    pub fn max_position(&self) -> u16 {
+        match self {
+            SimFamily::SmsSts => 4095,
+            SimFamily::Scscl => 1023,
+        }
+    }
+
+    // GOAL_SPEED 为 0 时使用的最大速度（步/秒）
+    pub fn max_speed(&self) -> f64 {
+        match self {
+            SimFamily::SmsSts => 3400.0,
+            SimFamily::Scscl => 1000.0,
+        }
+    }
+
+    fn regs(&self) -> &'static SimRegs {
+        match self {
+            SimFamily::SmsSts => &SMS_STS_REGS,
+            SimFamily::Scscl => &SCSCL_REGS,
+        }
+    }
+}
+
+// 仿真用到的寄存器地址，取自各系列的控制表
+#[derive(Debug)]
+struct SimRegs {
+    model: u8,
+    id: u8,
+    baud_rate: u8,
+    min_angle_limit: u8,
+    max_angle_limit: u8,
+    mode: u8,
+    torque_enable: u8,
+    // SCSCL 没有加速度寄存器，按设定速度立即启停
+    acc: Option<u8>,
+    goal_position: u8,
+    goal_time: u8,
+    goal_speed: u8,
+    lock: u8,
+    present_position: u8,
+    present_speed: u8,
+    present_load: u8,
+    present_voltage: u8,
+    present_temperature: u8,
+    moving: u8,
+    present_current: u8,
+    // 系列特有寄存器的出厂值：(地址, 字节数, 值)
+    defaults: &'static [(u8, u8, u16)],
+}
+
+const SMS_STS_REGS: SimRegs = SimRegs {
+    model: sms_sts::SMS_STS_MODEL_L,
+    id: sms_sts::SMS_STS_ID,
+    baud_rate: sms_sts::SMS_STS_BAUD_RATE,
+    min_angle_limit: sms_sts::SMS_STS_MIN_ANGLE_LIMIT_L,
+    max_angle_limit: sms_sts::SMS_STS_MAX_ANGLE_LIMIT_L,
+    mode: sms_sts::SMS_STS_MODE,
+    torque_enable: sms_sts::SMS_STS_TORQUE_ENABLE,
+    acc: Some(sms_sts::SMS_STS_ACC),
+    goal_position: sms_sts::SMS_STS_GOAL_POSITION_L,
+    goal_time: sms_sts::SMS_STS_GOAL_TIME_L,
+    goal_speed: sms_sts::SMS_STS_GOAL_SPEED_L,
+    lock: sms_sts::SMS_STS_LOCK,
+    present_position: sms_sts::SMS_STS_PRESENT_POSITION_L,
+    present_speed: sms_sts::SMS_STS_PRESENT_SPEED_L,
+    present_load: sms_sts::SMS_STS_PRESENT_LOAD_L,
+    present_voltage: sms_sts::SMS_STS_PRESENT_VOLTAGE,
+    present_temperature: sms_sts::SMS_STS_PRESENT_TEMPERATURE,
+    moving: sms_sts::SMS_STS_MOVING,
+    present_current: sms_sts::SMS_STS_PRESENT_CURRENT_L,
+    // 保护参数、扭矩限制与 PID 系数
+    defaults: &[
+        (sms_sts::SMS_STS_MAX_TEMPERATURE_LIMIT, 1, 70),
+        (sms_sts::SMS_STS_MAX_INPUT_VOLTAGE, 1, 140),
+        (sms_sts::SMS_STS_MIN_INPUT_VOLTAGE, 1, 40),
+        (sms_sts::SMS_STS_MAX_TORQUE_L, 2, 1000),
+        (sms_sts::SMS_STS_TORQUE_LIMIT_L, 2, 1000),
+        (sms_sts::SMS_STS_P_COEFFICIENT, 1, 32),
+        (sms_sts::SMS_STS_D_COEFFICIENT, 1, 32),
+    ],
+};
+
+const SCSCL_REGS: SimRegs = SimRegs {
+    model: scscl::SCSCL_MODEL_L,
+    id: scscl::SCSCL_ID,
+    baud_rate: scscl::SCSCL_BAUD_RATE,
+    min_angle_limit: scscl::SCSCL_MIN_ANGLE_LIMIT_L,
+    max_angle_limit: scscl::SCSCL_MAX_ANGLE_LIMIT_L,
+    mode: scscl::SCSCL_MODE,
+    torque_enable: scscl::SCSCL_TORQUE_ENABLE,
+    acc: None,
+    goal_position: scscl::SCSCL_GOAL_POSITION_L,
+    goal_time: scscl::SCSCL_GOAL_TIME_L,
+    goal_speed: scscl::SCSCL_GOAL_SPEED_L,
+    lock: scscl::SCSCL_LOCK,
+    present_position: scscl::SCSCL_PRESENT_POSITION_L,
+    present_speed: scscl::SCSCL_PRESENT_SPEED_L,
+    present_load: scscl::SCSCL_PRESENT_LOAD_L,
+    present_voltage: scscl::SCSCL_PRESENT_VOLTAGE,
+    present_temperature: scscl::SCSCL_PRESENT_TEMPERATURE,
+    moving: scscl::SCSCL_MOVING,
+    present_current: scscl::SCSCL_PRESENT_CURRENT_L,
+    defaults: &[],
+};
+
+// 符号位表示的数值转为整数
+fn from_sign_magnitude(value: u16, sign_bit: u32) -> i32 {
+    let magnitude = (value & !(1 << sign_bit)) as i32;
+    if value & (1 << sign_bit) != 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+// 整数转为符号位表示
+fn to_sign_magnitude(value: i32, sign_bit: u32) -> u16 {
+    let limit = (1i32 << sign_bit) - 1;
+    let magnitude = value.unsigned_abs().min(limit as u32) as u16;
+    if value < 0 {
+        magnitude | (1 << sign_bit)
+    } else {
+        magnitude
+    }
+}
+
+// 单个模拟舵机
+#[derive(Debug, Clone)]
+pub struct SimServo {
+    family: SimFamily,
+    table: [u8; TABLE_SIZE],
+    // 舵机状态字节（电压、角度、过热、过流、过载）
+    status: u8,
+    // RegWrite 暂存的写入，等待 Action
+    reg_write: Option<(u8, Vec<u8>)>,
+    // 运动状态：位置（步）、速度（步/秒）、温度（°C）
+    position: f64,
+    velocity: f64,
+    temperature: f64,
+    // 最近一次目标位置，以及由 GOAL_TIME 推算出的运动速度
+    last_goal: f64,
+    time_speed: f64,
+}
+
+impl SimServo {
+    pub fn new(id: u8, family: SimFamily) -> Self {
+        let mut servo = Self {
+            family,
+            table: [0; TABLE_SIZE],
+            status: 0,
+            reg_write: None,
+            position: 0.0,
+            velocity: 0.0,
+            temperature: AMBIENT_TEMPERATURE,
+            last_goal: 0.0,
+            time_speed: 0.0,
+        };
+
+        let model = match family {
+            SimFamily::SmsSts => DEFAULT_SMS_STS_MODEL,
+            SimFamily::Scscl => DEFAULT_SCSCL_MODEL,
+        };
+        let center = family.max_position() / 2 + 1;
+        let regs = family.regs();
+
+        servo.write_word(regs.model, model);
+        servo.table[regs.id as usize] = id;
+        servo.table[regs.baud_rate as usize] = 0;
+        servo.write_word(regs.min_angle_limit, 0);
+        servo.write_word(regs.max_angle_limit, family.max_position());
+        for &(address, size, value) in regs.defaults {
+            match size {
+                1 => servo.table[address as usize] = value as u8,
+                _ => servo.write_word(address, value),
+            }
+        }
+        servo.write_word(regs.goal_position, center);
+        servo.table[regs.lock as usize] = 1;
+        servo.table[regs.present_voltage as usize] = 120;
+        servo.set_position(center as i32);
+        servo
+    }
+
+    // 设置型号号
+    pub fn with_model(mut self, model: u16) -> Self {
+        self.write_word(self.family.regs().model, model);
+        self
+    }
+
+    pub fn id(&self) -> u8 {
+        self.table[self.family.regs().id as usize]
+    }
+
+    // 波特率寄存器设置的波特率，寄存器值无效时为 None
+    pub fn baudrate(&self) -> Option<u32> {
+        BAUD_RATES.get(self.table[self.family.regs().baud_rate as usize] as usize).copied()
+    }
+
+    pub fn family(&self) -> SimFamily {
+        self.family
+    }
+
+    // 获取状态字节
+    pub fn status(&self) -> u8 {
+        self.status
+    }
+
+    // 设置状态字节，用于模拟硬件故障
+    pub fn set_status(&mut self, status: u8) {
+        self.status = status;
+    }
+
+    // 读取控制表
+    pub fn read(&self, address: u8, length: u8) -> &[u8] {
+        let start = address as usize;
+        let end = (start + length as usize).min(TABLE_SIZE);
+        &self.table[start..end]
+    }
+
+    // 写入控制表
+    pub fn write(&mut self, address: u8, data: &[u8]) {
+        let start = address as usize;
+        let end = (start + data.len()).min(TABLE_SIZE);
+        self.table[start..end].copy_from_slice(&data[..end - start]);
+    }
+
+    pub fn read_byte(&self, address: u8) -> u8 {
+        self.table[address as usize]
+    }
+
+    pub fn write_byte(&mut self, address: u8, value: u8) {
+        self.table[address as usize] = value;
+    }
+
+    // 按系列字节序读取两字节，控制表之外的字节读作 0
+    pub fn read_word(&self, address: u8) -> u16 {
+        let a = self.table[address as usize];
+        let b = self.table.get(address as usize + 1).copied().unwrap_or(0);
+        match self.family {
+            SimFamily::SmsSts => u16::from_le_bytes([a, b]),
+            SimFamily::Scscl => u16::from_be_bytes([a, b]),
+        }
+    }
+
+    // 按系列字节序写入两字节
+    pub fn write_word(&mut self, address: u8, value: u16) {
+        let bytes = match self.family {
+            SimFamily::SmsSts => value.to_le_bytes(),
+            SimFamily::Scscl => value.to_be_bytes(),
+        };
+        self.write(address, &bytes);
+    }
+
+    // 直接设置当前位置（同时作为目标位置），用于初始化测试场景
+    pub fn set_position(&mut self, position: i32) {
+        self.position = position as f64;
+        self.velocity = 0.0;
+        self.last_goal = self.position;
+        self.write_word(self.family.regs().goal_position, to_sign_magnitude(position, 15));
+        self.update_feedback(0.0);
+    }
+
+    // 当前位置（步）
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    // 当前速度（步/秒）
+    pub fn velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    // 按时间推进运动仿真
+    pub fn advance(&mut self, dt: Duration) {
+        let mut remaining = dt;
+        while !remaining.is_zero() {
+            let step = remaining.min(SIM_STEP);
+            self.step(step.as_secs_f64());
+            remaining -= step;
+        }
+    }
+
+    // 单步积分
+    fn step(&mut self, dt: f64) {
+        let regs = self.family.regs();
+        let torque_enabled = self.table[regs.torque_enable as usize] != 0;
+        let acc = regs.acc.map_or(0.0, |acc| self.table[acc as usize] as f64 * 100.0);
+        let goal_speed = from_sign_magnitude(self.read_word(regs.goal_speed), 15) as f64;
+        let previous_velocity = self.velocity;
+
+        if !torque_enabled {
+            self.velocity = 0.0;
+        } else if self.table[regs.mode as usize] == 1 {
+            // 轮式模式：GOAL_SPEED 为带符号目标速度
+            self.velocity = approach(self.velocity, goal_speed, acc, dt);
+            let turn = self.family.max_position() as f64 + 1.0;
+            self.position = (self.position + self.velocity * dt).rem_euclid(turn);
+        } else {
+            let goal = self.goal_position();
+            let distance = goal - self.position;
+
+            // 新目标：按 GOAL_TIME 推算本次运动的速度
+            if goal != self.last_goal {
+                let goal_time = self.read_word(regs.goal_time) as f64 / 1000.0;
+                self.time_speed = if goal_time > 0.0 {
+                    (distance.abs() / goal_time).max(1.0)
+                } else {
+                    0.0
+                };
+                self.last_goal = goal;
+            }
+
+            // 目标速度：GOAL_SPEED 优先，其次由 GOAL_TIME 推算，都为 0 时使用最大速度
+            let max_speed = if goal_speed != 0.0 {
+                goal_speed.abs()
+            } else if self.time_speed > 0.0 {
+                self.time_speed
+            } else {
+                self.family.max_speed()
+            };
+
+            // 梯形速度曲线：接近目标时按加速度减速
+            let mut target_speed = max_speed;
+            if acc > 0.0 {
+                target_speed = target_speed.min((2.0 * acc * distance.abs()).sqrt());
+            }
+            let target_velocity = target_speed.copysign(distance);
+            self.velocity = approach(self.velocity, target_velocity, acc, dt);
+
+            let travel = self.velocity * dt;
+            if distance.abs() <= travel.abs() || distance.abs() < 0.5 {
+                self.position = goal;
+                self.velocity = 0.0;
+            } else {
+                self.position += travel;
+            }
+        }
+
+        let acceleration = (self.velocity - previous_velocity) / dt;
+        self.update_feedback(acceleration);
+        self.update_temperature(dt);
+    }
+
+    // 目标位置，按角度限制截断（限制均为 0 时为多圈模式，不截断）
+    fn goal_position(&self) -> f64 {
+        let regs = self.family.regs();
+        let goal = from_sign_magnitude(self.read_word(regs.goal_position), 15);
+        let min = self.read_word(regs.min_angle_limit) as i32;
+        let max = self.read_word(regs.max_angle_limit) as i32;
+        if max > min {
+            goal.clamp(min, max) as f64
+        } else {
+            goal as f64
+        }
+    }
+
+    // 更新反馈寄存器：位置、速度、负载、电流、温度、运动标志
+    fn update_feedback(&mut self, acceleration: f64) {
+        let regs = self.family.regs();
+        let max_speed = self.family.max_speed();
+        let moving = self.velocity != 0.0;
+
+        // 负载（0.1%）：维持转动的负载加上加速所需的负载
+        let load = if moving {
+            self.velocity.signum() * 100.0 + self.velocity / max_speed * 200.0 + acceleration / max_speed * 20.0
+        } else {
+            0.0
+        };
+        // 电流：静态电流加上与负载成正比的部分
+        let current = 5.0 + load.abs() * 0.5;
+
+        self.write_word(regs.present_position, to_sign_magnitude(self.position.round() as i32, 15));
+        self.write_word(regs.present_speed, to_sign_magnitude(self.velocity.round() as i32, 15));
+        self.write_word(regs.present_load, to_sign_magnitude(load.round() as i32, 10));
+        self.write_word(regs.present_current, to_sign_magnitude(current.round() as i32, 15));
+        self.table[regs.present_temperature as usize] = self.temperature.round() as u8;
+        self.table[regs.moving as usize] = u8::from(moving);
+    }
+
+    // 温度随电流上升，停止后回落到环境温度
+    fn update_temperature(&mut self, dt: f64) {
+        let regs = self.family.regs();
+        let current = from_sign_magnitude(self.read_word(regs.present_current), 15) as f64;
+        let equilibrium = AMBIENT_TEMPERATURE + current * 0.1;
+        self.temperature += (equilibrium - self.temperature) * dt / THERMAL_TIME_CONSTANT;
+        self.table[regs.present_temperature as usize] = self.temperature.round() as u8;
+    }
+
+    // 生成应答包，超过最大包长时不应答
+    fn status_packet(&self, params: &[u8]) -> Option<Vec<u8>> {
+        StatusPacket::new(self.id(), self.status, params).to_bytes().ok()
+    }
+}
+
+// 以给定加速度将速度逼近目标值，加速度为 0 表示立即到达
+fn approach(current: f64, target: f64, acc: f64, dt: f64) -> f64 {
+    if acc <= 0.0 {
+        return target;
+    }
+    let max_change = acc * dt;
+    current + (target - current).clamp(-max_change, max_change)
+}
+
+// 舵机链：解析主机发送的指令包并生成应答
+#[derive(Debug, Clone, Default)]
+pub struct ServoChain {
+    servos: Vec<SimServo>,
+    // 尚未组成完整数据包的输入字节
+    pending: Vec<u8>,
+    // 总线波特率，波特率设置不同的舵机收不到指令；None 时所有舵机都能收到
+    baudrate: Option<u32>,
+}
+
+impl ServoChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 添加舵机
+    pub fn add_servo(&mut self, servo: SimServo) -> &mut Self {
+        self.servos.push(servo);
+        self
+    }
+
+    // 设置总线波特率
+    pub fn set_baudrate(&mut self, baudrate: Option<u32>) {
+        self.baudrate = baudrate;
+    }
+
+    pub fn servos(&self) -> &[SimServo] {
+        &self.servos
+    }
+
+    pub fn servo(&self, id: u8) -> Option<&SimServo> {
+        self.servos.iter().find(|s| s.id() == id)
+    }
+
+    pub fn servo_mut(&mut self, id: u8) -> Option<&mut SimServo> {
+        self.servos.iter_mut().find(|s| s.id() == id)
+    }
+
+    // 推进所有舵机的运动仿真
+    pub fn advance(&mut self, dt: Duration) {
+        for servo in &mut self.servos {
+            servo.advance(dt);
+        }
+    }
+
+    // 处理主机发送的字节，返回舵机应答包（按应答顺序）
+    pub fn process(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        self.pending.extend_from_slice(bytes);
+        let mut responses = Vec::new();
+
+        loop {
+            // 寻找包头
+            match self.pending.windows(2).position(|w| w == HEADER) {
+                Some(0) => {}
+                Some(pos) => {
+                    self.pending.drain(..pos);
+                }
+                None => {
+                    // 保留可能是包头一部分的最后一个 0xFF
+                    let keep = usize::from(self.pending.last() == Some(&0xFF));
+                    let len = self.pending.len();
+                    self.pending.drain(..len - keep);
+                    break;
+                }
+            }
+
+            if self.pending.len() < 4 {
+                break;
+            }
+            let length = self.pending[3] as usize;
+            if length < 2 {
+                self.pending.drain(..2);
+                continue;
+            }
+            let total = length + 4;
+            if self.pending.len() < total {
+                break;
+            }
+
+            let bytes: Vec<u8> = self.pending.drain(..total).collect();
+            if let Ok(packet) = InstructionPacket::decode(&bytes) {
+                responses.extend(self.execute(&packet));
+            }
+        }
+
+        responses
+    }
+
+    // 在异步字节流上模拟舵机链，直到对端关闭；舵机状态按真实时间推进
+    #[cfg(feature = "async")]
+    pub async fn serve_async<S>(&mut self, mut stream: S) -> io::Result<()>
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut buf = [0u8; TABLE_SIZE];
+        let mut last_update = Instant::now();
+        loop {
+            let bytes_read = stream.read(&mut buf).await?;
+            if bytes_read == 0 {
+                return Ok(());
+            }
+
+            let now = Instant::now();
+            self.advance(now - last_update);
+            last_update = now;
+
+            for response in self.process(&buf[..bytes_read]) {
+                stream.write_all(&response).await?;
+            }
+            stream.flush().await?;
+        }
+    }
+
+    // 执行一条指令
+    fn execute(&mut self, packet: &InstructionPacket) -> Vec<Vec<u8>> {
+        let mut responses = Vec::new();
+        let id = packet.id;
+        let params = packet.params;
+        let broadcast = id == BROADCAST_ID;
+        let baudrate = self.baudrate;
+        let hears = |servo: &SimServo| baudrate.is_none() || servo.baudrate() == baudrate;
+
+        match packet.instruction {
+            INST::Ping => {
+                for servo in self.servos.iter().filter(|s| hears(s) && (broadcast || s.id() == id)) {
+                    responses.extend(servo.status_packet(&[]));
+                }
+            }
+            INST::Read => {
+                if let (false, [address, length]) = (broadcast, params) {
+                    if let Some(servo) = self.servo(id).filter(|s| hears(s)) {
+                        responses.extend(servo.status_packet(servo.read(*address, *length)));
+                    }
+                }
+            }
+            INST::Write => {
+                if let Some((&address, data)) = params.split_first() {
+                    for servo in self.servos.iter_mut().filter(|s| hears(s) && (broadcast || s.id() == id)) {
+                        servo.write(address, data);
+                        if !broadcast {
+                            responses.extend(servo.status_packet(&[]));
+                        }
+                    }
+                }
+            }
+            INST::RegWrite => {
+                if let Some((&address, data)) = params.split_first() {
+                    for servo in self.servos.iter_mut().filter(|s| hears(s) && (broadcast || s.id() == id)) {
+                        servo.reg_write = Some((address, data.to_vec()));
+                        if !broadcast {
+                            responses.extend(servo.status_packet(&[]));
+                        }
+                    }
+                }
+            }
+            INST::Action => {
+                for servo in self.servos.iter_mut().filter(|s| hears(s) && (broadcast || s.id() == id)) {
+                    if let Some((address, data)) = servo.reg_write.take() {
+                        servo.write(address, &data);
+                    }
+                    if !broadcast {
+                        responses.extend(servo.status_packet(&[]));
+                    }
+                }
+            }
+            INST::SyncWrite => {
+                if let [address, length, rest @ ..] = params {
+                    let chunk = *length as usize + 1;
+                    for entry in rest.chunks_exact(chunk) {
+                        if let Some(servo) = self.servo_mut(entry[0]).filter(|s| hears(s)) {
+                            servo.write(*address, &entry[1..]);
+                        }
+                    }
+                }
+            }
+            INST::SyncRead => {
+                if let [address, length, ids @ ..] = params {
+                    for &scs_id in ids {
+                        if let Some(servo) = self.servo(scs_id).filter(|s| hears(s)) {
+                            responses.extend(servo.status_packet(servo.read(*address, *length)));
+                        }
+                    }
+                }
+            }
+            INST::BulkRead => {
+                if let [0x00, entries @ ..] = params {
+                    // 每个舵机: [长度, ID, 起始地址]
+                    for entry in entries.chunks_exact(3) {
+                        let (length, scs_id, address) = (entry[0], entry[1], entry[2]);
+                        if let Some(servo) = self.servo(scs_id).filter(|s| hears(s)) {
+                            responses.extend(servo.status_packet(servo.read(address, length)));
+                        }
+                    }
+                }
+            }
+        }
+
+        responses
+    }
+}
+
+// 仿真时钟
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimClock {
+    // 跟随真实时间推进
+    Realtime,
+    // 仅在调用 advance 时推进，结果可复现
+    Manual,
+}
+
+// 虚拟总线：将舵机链包装为 Transport，可配置应答延迟和丢包率
+#[derive(Debug)]
+pub struct VirtualBus {
+    chain: ServoChain,
+    clock: SimClock,
+    last_update: Instant,
+    baudrate: u32,
+    latency: Duration,
+    drop_rate: f64,
+    rng_state: u64,
+    // 等待发送给主机的应答（可读取时间，数据）
+    responses: VecDeque<(Instant, Vec<u8>)>,
+    // 已到达、尚未被读取的字节
+    rx_buffer: VecDeque<u8>,
+}
+
+impl VirtualBus {
+    pub fn new(mut chain: ServoChain) -> Self {
+        chain.set_baudrate(Some(1000000));
+        Self {
+            chain,
+            clock: SimClock::Realtime,
+            last_update: Instant::now(),
+            baudrate: 1000000,
+            latency: Duration::ZERO,
+            drop_rate: 0.0,
+            rng_state: 0x9E37_79B9_7F4A_7C15,
+            responses: VecDeque::new(),
+            rx_buffer: VecDeque::new(),
+        }
+    }
+
+    // 设置应答延迟
+    pub fn set_latency(&mut self, latency: Duration) {
+        self.latency = latency;
+    }
+
+    // 设置丢包率（0.0 ~ 1.0）
+    pub fn set_drop_rate(&mut self, drop_rate: f64) {
+        self.drop_rate = drop_rate.clamp(0.0, 1.0);
+    }
+
+    // 设置丢包随机数种子，便于复现
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng_state = seed.max(1);
+    }
+
+    // 设置仿真时钟
+    pub fn set_clock(&mut self, clock: SimClock) {
+        self.clock = clock;
+        self.last_update = Instant::now();
+    }
+
+    // 推进仿真时间（手动时钟）
+    pub fn advance(&mut self, dt: Duration) {
+        self.chain.advance(dt);
+    }
+
+    // 实时时钟下按流逝的时间推进仿真
+    fn sync_clock(&mut self) {
+        if self.clock == SimClock::Realtime {
+            let now = Instant::now();
+            self.chain.advance(now - self.last_update);
+            self.last_update = now;
+        }
+    }
+
+    pub fn chain(&self) -> &ServoChain {
+        &self.chain
+    }
+
+    pub fn chain_mut(&mut self) -> &mut ServoChain {
+        &mut self.chain
+    }
+
+    // xorshift64，返回 [0, 1) 的随机数
+    fn next_random(&mut self) -> f64 {
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng_state = x;
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // 将已到达的应答移入接收缓冲区
+    fn deliver_ready(&mut self, now: Instant) {
+        while let Some((ready_at, _)) = self.responses.front() {
+            if *ready_at > now {
+                break;
+            }
+            if let Some((_, packet)) = self.responses.pop_front() {
+                self.rx_buffer.extend(packet);
+            }
+        }
+    }
+}
+
+impl Transport for VirtualBus {
+    fn write_port(&mut self, packet: &[u8]) -> io::Result<usize> {
+        self.sync_clock();
+        let ready_at = Instant::now() + self.latency;
+        for response in self.chain.process(packet) {
+            if self.drop_rate > 0.0 && self.next_random() < self.drop_rate {
+                continue;
+            }
+            self.responses.push_back((ready_at, response));
+        }
+        Ok(packet.len())
+    }
+
+    fn read_port_until(&mut self, buf: &mut [u8], deadline: Instant) -> io::Result<usize> {
+        loop {
+            let now = Instant::now();
+            self.deliver_ready(now);
+
+            if !self.rx_buffer.is_empty() {
+                let count = buf.len().min(self.rx_buffer.len());
+                for (dst, src) in buf.iter_mut().zip(self.rx_buffer.drain(..count)) {
+                    *dst = src;
+                }
+                return Ok(count);
+            }
+
+            // 等待下一个应答到达或超时
+            let wake = match self.responses.front() {
+                Some((ready_at, _)) if *ready_at <= deadline => *ready_at,
+                _ => deadline,
+            };
+            if wake <= now {
+                return Err(io::Error::new(ErrorKind::TimedOut, "no status packet"));
+            }
+            thread::sleep(wake - now);
+        }
+    }
+
+    fn clear_port(&mut self) -> io::Result<()> {
+        self.deliver_ready(Instant::now());
+        self.rx_buffer.clear();
+        Ok(())
+    }
+
+    fn get_bytes_available(&self) -> io::Result<u32> {
+        let now = Instant::now();
+        let pending: usize = self
+            .responses
+            .iter()
+            .take_while(|(ready_at, _)| *ready_at <= now)
+            .map(|(_, packet)| packet.len())
+            .sum();
+        Ok((self.rx_buffer.len() + pending) as u32)
+    }
+
+    fn get_baudrate(&self) -> u32 {
+        self.baudrate
+    }
+
+    // 切换后只有波特率设置相同的舵机能收到指令
+    fn set_baudrate(&mut self, baudrate: u32) -> io::Result<()> {
+        self.baudrate = baudrate;
+        self.chain.set_baudrate(Some(baudrate));
+        Ok(())
+    }
+}
+
+// 测试用：同一系列的一组舵机
+#[cfg(test)]
+pub(crate) fn sim_chain(family: SimFamily, ids: &[u8]) -> ServoChain {
+    let mut chain = ServoChain::new();
+    for &id in ids {
+        chain.add_servo(SimServo::new(id, family));
+    }
+    chain
+}
+
+#[cfg(test)]
+pub(crate) fn sim_bus(family: SimFamily, ids: &[u8]) -> VirtualBus {
+    VirtualBus::new(sim_chain(family, ids))
+}
+
+// 测试用：记录主机每次写入的数据的虚拟总线
+#[cfg(test)]
+pub(crate) struct Recorder {
+    pub bus: VirtualBus,
+    pub writes: Vec<Vec<u8>>,
+}
+
+#[cfg(test)]
+impl Recorder {
+    pub fn new(bus: VirtualBus) -> Self {
+        Self { bus, writes: Vec::new() }
+    }
+}
+
+#[cfg(test)]
+impl Transport for Recorder {
+    fn write_port(&mut self, packet: &[u8]) -> io::Result<usize> {
+        self.writes.push(packet.to_vec());
+        self.bus.write_port(packet)
+    }
+
+    fn read_port_until(&mut self, buf: &mut [u8], deadline: Instant) -> io::Result<usize> {
+        self.bus.read_port_until(buf, deadline)
+    }
+
+    fn clear_port(&mut self) -> io::Result<()> {
+        self.bus.clear_port()
+    }
+
+    fn get_bytes_available(&self) -> io::Result<u32> {
+        self.bus.get_bytes_available()
+    }
+
+    fn get_baudrate(&self) -> u32 {
+        self.bus.get_baudrate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scscl::Scscl, sms_sts::regs, FtServoError, SmsSts};
+
+    // 手动时钟下的 SMS/STS 舵机，扭矩已使能，位置在中点 2048
+    fn manual_servo() -> SmsSts<VirtualBus> {
+        let mut bus = sim_bus(SimFamily::SmsSts, &[1]);
+        bus.set_clock(SimClock::Manual);
+        let mut sms_sts = SmsSts::new(bus);
+        sms_sts.write_torque_enable(1, true).unwrap();
+        sms_sts
+    }
+
+    fn advance(sms_sts: &mut SmsSts<VirtualBus>, millis: u64) {
+        sms_sts.get_port_handler_mut().advance(Duration::from_millis(millis));
+    }
+
+    #[test]
+    fn moves_toward_the_goal_at_the_goal_speed() {
+        let mut sms_sts = manual_servo();
+        sms_sts.write_pos_ex(1, 3048, 0, 1000).unwrap();
+
+        advance(&mut sms_sts, 500);
+        assert!((sms_sts.read_pos(1).unwrap() - 2548).abs() <= 1);
+        assert_eq!(sms_sts.read_speed(1).unwrap(), 1000);
+    }
+
+    #[test]
+    fn accelerates_at_the_acc_setting() {
+        let mut sms_sts = manual_servo();
+        // ACC 10 为 1000 步/秒²
+        sms_sts.write_reg(1, regs::ACC, 10).unwrap();
+        sms_sts.write_reg(1, regs::GOAL_SPEED, 1000).unwrap();
+        sms_sts.write_reg(1, regs::GOAL_POSITION, 3048).unwrap();
+
+        advance(&mut sms_sts, 500);
+        assert_eq!(sms_sts.read_speed(1).unwrap(), 500);
+        // 0.5 * 1000 * 0.5² = 125 步
+        assert!((sms_sts.read_pos(1).unwrap() - 2048 - 125).abs() <= 1);
+    }
+
+    #[test]
+    fn moving_flag_clears_on_arrival() {
+        let mut sms_sts = manual_servo();
+        assert!(!sms_sts.read_moving(1).unwrap());
+
+        sms_sts.write_pos_ex(1, 2548, 0, 1000).unwrap();
+        advance(&mut sms_sts, 100);
+        assert!(sms_sts.read_moving(1).unwrap());
+
+        advance(&mut sms_sts, 500);
+        assert!(!sms_sts.read_moving(1).unwrap());
+        assert_eq!(sms_sts.read_pos(1).unwrap(), 2548);
+        assert_eq!(sms_sts.read_speed(1).unwrap(), 0);
+    }
+
+    #[test]
+    fn torque_off_stops_motion() {
+        let mut sms_sts = manual_servo();
+        sms_sts.write_pos_ex(1, 3048, 0, 1000).unwrap();
+        advance(&mut sms_sts, 100);
+
+        sms_sts.write_torque_enable(1, false).unwrap();
+        advance(&mut sms_sts, 1);
+        let stopped_at = sms_sts.read_pos(1).unwrap();
+        advance(&mut sms_sts, 500);
+        assert_eq!(sms_sts.read_pos(1).unwrap(), stopped_at);
+        assert!(!sms_sts.read_moving(1).unwrap());
+    }
+
+    #[test]
+    fn scscl_has_no_acceleration_register() {
+        let mut bus = sim_bus(SimFamily::Scscl, &[1]);
+        bus.set_clock(SimClock::Manual);
+        let mut scscl = Scscl::new(bus);
+        scscl.write_torque_enable(1, true).unwrap();
+        // SMS/STS 的 ACC 地址，SCSCL 不按加速度启动
+        scscl.get_port_handler_mut().chain_mut().servo_mut(1).unwrap().write_byte(41, 10);
+
+        scscl.write_pos(1, 812, 0, 500).unwrap();
+        scscl.get_port_handler_mut().advance(Duration::from_millis(100));
+        assert_eq!(scscl.read_speed(1).unwrap(), 500);
+        assert!((scscl.read_pos(1).unwrap() - 512 - 50).abs() <= 1);
+    }
+
+    // 依次 Ping，记录每次是否收到应答
+    fn answered(bus: VirtualBus, count: usize) -> Vec<bool> {
+        let mut sms_sts = SmsSts::new(bus);
+        (0..count).map(|_| sms_sts.ping(1).is_ok()).collect()
+    }
+
+    #[test]
+    fn latency_past_the_timeout_is_a_timeout() {
+        let mut bus = sim_bus(SimFamily::SmsSts, &[1]);
+        bus.set_clock(SimClock::Manual);
+        bus.set_latency(Duration::from_millis(200));
+        let mut sms_sts = SmsSts::new(bus);
+
+        assert!(matches!(sms_sts.ping(1), Err(FtServoError::Timeout { id: 1, .. })));
+    }
+
+    #[test]
+    fn drop_rate_one_drops_every_reply() {
+        let mut bus = sim_bus(SimFamily::SmsSts, &[1]);
+        bus.set_clock(SimClock::Manual);
+        bus.set_drop_rate(1.0);
+        let mut sms_sts = SmsSts::new(bus);
+
+        for _ in 0..3 {
+            assert!(matches!(sms_sts.ping(1), Err(FtServoError::Timeout { id: 1, .. })));
+        }
+    }
+
+    #[test]
+    fn seeded_drops_are_reproducible() {
+        let seeded = || {
+            let mut bus = sim_bus(SimFamily::SmsSts, &[1]);
+            bus.set_clock(SimClock::Manual);
+            bus.set_drop_rate(0.5);
+            bus.set_seed(7);
+            bus
+        };
+
+        let first = answered(seeded(), 8);
+        assert_eq!(answered(seeded(), 8), first);
+        assert!(first.contains(&true) && first.contains(&false));
+    }
+
+    #[test]
+    fn read_word_at_the_end_of_the_table() {
+        let mut servo = SimServo::new(1, SimFamily::SmsSts);
+        servo.write_byte(255, 0x12);
+        assert_eq!(servo.read_word(255), 0x12);
+    }
+}
@@ -1,8 +1,19 @@
+use std::collections::HashMap;
+
+#[cfg(feature = "config")]
+use crate::config::{self, BusConfig, Difference, ServoConfig};
 use crate::{
+    group_bulk_read::GroupBulkRead,
+    group_sync_read::GroupSyncRead,
     group_sync_write::GroupSyncWrite,
+    model::{Family, ServoModel},
     port_handler::PortHandler,
-    protocol_packet_handler::{Endian, ProtocolPacketHandler},
-    scservo_def::COMM,
+    protocol_packet_handler::{check_sync_lengths, signed_word, Endian, PingInfo, ProtocolPacketHandler},
+    register::Register,
+    scservo_def::ServoStatus,
+    setup,
+    transport::Transport,
+    FtServoError, Result,
 };
 
 // 波特率定义
@@ -17,21 +28,47 @@ pub const SMS_STS_38400: u8 = 7;
 
 // 内存表定义
 // -------EPROM(只读)--------
+pub const SMS_STS_FIRMWARE_MAJOR: u8 = 0;
+pub const SMS_STS_FIRMWARE_MINOR: u8 = 1;
 pub const SMS_STS_MODEL_L: u8 = 3;
 pub const SMS_STS_MODEL_H: u8 = 4;
 
 // -------EPROM(读写)--------
 pub const SMS_STS_ID: u8 = 5;
 pub const SMS_STS_BAUD_RATE: u8 = 6;
+pub const SMS_STS_RETURN_DELAY_TIME: u8 = 7;
+pub const SMS_STS_STATUS_RETURN_LEVEL: u8 = 8;
 pub const SMS_STS_MIN_ANGLE_LIMIT_L: u8 = 9;
 pub const SMS_STS_MIN_ANGLE_LIMIT_H: u8 = 10;
 pub const SMS_STS_MAX_ANGLE_LIMIT_L: u8 = 11;
 pub const SMS_STS_MAX_ANGLE_LIMIT_H: u8 = 12;
+pub const SMS_STS_MAX_TEMPERATURE_LIMIT: u8 = 13;
+pub const SMS_STS_MAX_INPUT_VOLTAGE: u8 = 14;
+pub const SMS_STS_MIN_INPUT_VOLTAGE: u8 = 15;
+pub const SMS_STS_MAX_TORQUE_L: u8 = 16;
+pub const SMS_STS_MAX_TORQUE_H: u8 = 17;
+pub const SMS_STS_PHASE: u8 = 18;
+pub const SMS_STS_UNLOADING_CONDITION: u8 = 19;
+pub const SMS_STS_LED_ALARM_CONDITION: u8 = 20;
+pub const SMS_STS_P_COEFFICIENT: u8 = 21;
+pub const SMS_STS_D_COEFFICIENT: u8 = 22;
+pub const SMS_STS_I_COEFFICIENT: u8 = 23;
+pub const SMS_STS_MIN_STARTUP_FORCE_L: u8 = 24;
+pub const SMS_STS_MIN_STARTUP_FORCE_H: u8 = 25;
 pub const SMS_STS_CW_DEAD: u8 = 26;
 pub const SMS_STS_CCW_DEAD: u8 = 27;
+pub const SMS_STS_PROTECTION_CURRENT_L: u8 = 28;
+pub const SMS_STS_PROTECTION_CURRENT_H: u8 = 29;
+pub const SMS_STS_ANGULAR_RESOLUTION: u8 = 30;
 pub const SMS_STS_OFS_L: u8 = 31;
 pub const SMS_STS_OFS_H: u8 = 32;
 pub const SMS_STS_MODE: u8 = 33;
+pub const SMS_STS_PROTECTIVE_TORQUE: u8 = 34;
+pub const SMS_STS_PROTECTION_TIME: u8 = 35;
+pub const SMS_STS_OVERLOAD_TORQUE: u8 = 36;
+pub const SMS_STS_SPEED_P_COEFFICIENT: u8 = 37;
+pub const SMS_STS_OVER_CURRENT_PROTECTION_TIME: u8 = 38;
+pub const SMS_STS_SPEED_I_COEFFICIENT: u8 = 39;
 
 // -------SRAM(读写)--------
 pub const SMS_STS_TORQUE_ENABLE: u8 = 40;
@@ -42,6 +79,8 @@ pub const SMS_STS_GOAL_TIME_L: u8 = 44;
 pub const SMS_STS_GOAL_TIME_H: u8 = 45;
 pub const SMS_STS_GOAL_SPEED_L: u8 = 46;
 pub const SMS_STS_GOAL_SPEED_H: u8 = 47;
+pub const SMS_STS_TORQUE_LIMIT_L: u8 = 48;
+pub const SMS_STS_TORQUE_LIMIT_H: u8 = 49;
 pub const SMS_STS_LOCK: u8 = 55;
 
 // -------SRAM(只读)--------
@@ -53,258 +92,705 @@ pub const SMS_STS_PRESENT_LOAD_L: u8 = 60;
 pub const SMS_STS_PRESENT_LOAD_H: u8 = 61;
 pub const SMS_STS_PRESENT_VOLTAGE: u8 = 62;
 pub const SMS_STS_PRESENT_TEMPERATURE: u8 = 63;
+pub const SMS_STS_STATUS: u8 = 65;
 pub const SMS_STS_MOVING: u8 = 66;
 pub const SMS_STS_PRESENT_CURRENT_L: u8 = 69;
 pub const SMS_STS_PRESENT_CURRENT_H: u8 = 70;
 
-pub struct SmsSts {
-    ph: ProtocolPacketHandler,
-    group_sync_write: GroupSyncWrite,
+// 控制表
+pub mod regs {
+    use super::*;
+    use crate::{register::Register, scservo_def::MAX_ID};
+
+    // -------EPROM(只读)--------
+    pub const FIRMWARE_MAJOR: Register = Register::new("FIRMWARE_MAJOR", SMS_STS_FIRMWARE_MAJOR, 1).eeprom().read_only();
+    pub const FIRMWARE_MINOR: Register = Register::new("FIRMWARE_MINOR", SMS_STS_FIRMWARE_MINOR, 1).eeprom().read_only();
+    pub const MODEL: Register = Register::new("MODEL", SMS_STS_MODEL_L, 2).eeprom().read_only();
+
+    // -------EPROM(读写)--------
+    pub const ID: Register = Register::new("ID", SMS_STS_ID, 1).eeprom().range(0, MAX_ID as i32);
+    pub const BAUD_RATE: Register = Register::new("BAUD_RATE", SMS_STS_BAUD_RATE, 1).eeprom().range(0, 7);
+    pub const RETURN_DELAY_TIME: Register = Register::new("RETURN_DELAY_TIME", SMS_STS_RETURN_DELAY_TIME, 1).eeprom().unit("2us").range(0, 254);
+    // 0 只有读取和 Ping 应答，1 所有指令都应答
+    pub const STATUS_RETURN_LEVEL: Register = Register::new("STATUS_RETURN_LEVEL", SMS_STS_STATUS_RETURN_LEVEL, 1).eeprom().range(0, 1);
+    pub const MIN_ANGLE_LIMIT: Register = Register::new("MIN_ANGLE_LIMIT", SMS_STS_MIN_ANGLE_LIMIT_L, 2).eeprom().unit("step").range(0, 4095);
+    pub const MAX_ANGLE_LIMIT: Register = Register::new("MAX_ANGLE_LIMIT", SMS_STS_MAX_ANGLE_LIMIT_L, 2).eeprom().unit("step").range(0, 4095);
+    pub const MAX_TEMPERATURE_LIMIT: Register = Register::new("MAX_TEMPERATURE_LIMIT", SMS_STS_MAX_TEMPERATURE_LIMIT, 1).eeprom().unit("°C").range(0, 100);
+    pub const MAX_INPUT_VOLTAGE: Register = Register::new("MAX_INPUT_VOLTAGE", SMS_STS_MAX_INPUT_VOLTAGE, 1).eeprom().unit("0.1V").range(0, 254);
+    pub const MIN_INPUT_VOLTAGE: Register = Register::new("MIN_INPUT_VOLTAGE", SMS_STS_MIN_INPUT_VOLTAGE, 1).eeprom().unit("0.1V").range(0, 254);
+    pub const MAX_TORQUE: Register = Register::new("MAX_TORQUE", SMS_STS_MAX_TORQUE_L, 2).eeprom().unit("0.1%").range(0, 1000);
+    // 运行相位，按位设置，修改前请参考舵机手册
+    pub const PHASE: Register = Register::new("PHASE", SMS_STS_PHASE, 1).eeprom();
+    // 卸载条件与 LED 报警条件，与状态字节的故障位相同
+    pub const UNLOADING_CONDITION: Register = Register::new("UNLOADING_CONDITION", SMS_STS_UNLOADING_CONDITION, 1).eeprom();
+    pub const LED_ALARM_CONDITION: Register = Register::new("LED_ALARM_CONDITION", SMS_STS_LED_ALARM_CONDITION, 1).eeprom();
+    pub const P_COEFFICIENT: Register = Register::new("P_COEFFICIENT", SMS_STS_P_COEFFICIENT, 1).eeprom().range(0, 254);
+    pub const D_COEFFICIENT: Register = Register::new("D_COEFFICIENT", SMS_STS_D_COEFFICIENT, 1).eeprom().range(0, 254);
+    pub const I_COEFFICIENT: Register = Register::new("I_COEFFICIENT", SMS_STS_I_COEFFICIENT, 1).eeprom().range(0, 254);
+    pub const MIN_STARTUP_FORCE: Register = Register::new("MIN_STARTUP_FORCE", SMS_STS_MIN_STARTUP_FORCE_L, 2).eeprom().unit("0.1%").range(0, 1000);
+    pub const CW_DEAD: Register = Register::new("CW_DEAD", SMS_STS_CW_DEAD, 1).eeprom().unit("step").range(0, 32);
+    pub const CCW_DEAD: Register = Register::new("CCW_DEAD", SMS_STS_CCW_DEAD, 1).eeprom().unit("step").range(0, 32);
+    pub const PROTECTION_CURRENT: Register = Register::new("PROTECTION_CURRENT", SMS_STS_PROTECTION_CURRENT_L, 2).eeprom().unit("6.5mA").range(0, 511);
+    pub const ANGULAR_RESOLUTION: Register = Register::new("ANGULAR_RESOLUTION", SMS_STS_ANGULAR_RESOLUTION, 1).eeprom();
+    pub const OFS: Register = Register::new("OFS", SMS_STS_OFS_L, 2).eeprom().signed(11).unit("step");
+    pub const MODE: Register = Register::new("MODE", SMS_STS_MODE, 1).eeprom().range(0, 3);
+    pub const PROTECTIVE_TORQUE: Register = Register::new("PROTECTIVE_TORQUE", SMS_STS_PROTECTIVE_TORQUE, 1).eeprom().unit("1%").range(0, 100);
+    pub const PROTECTION_TIME: Register = Register::new("PROTECTION_TIME", SMS_STS_PROTECTION_TIME, 1).eeprom().unit("10ms").range(0, 254);
+    pub const OVERLOAD_TORQUE: Register = Register::new("OVERLOAD_TORQUE", SMS_STS_OVERLOAD_TORQUE, 1).eeprom().unit("1%").range(0, 100);
+    pub const SPEED_P_COEFFICIENT: Register = Register::new("SPEED_P_COEFFICIENT", SMS_STS_SPEED_P_COEFFICIENT, 1).eeprom().range(0, 254);
+    pub const OVER_CURRENT_PROTECTION_TIME: Register = Register::new("OVER_CURRENT_PROTECTION_TIME", SMS_STS_OVER_CURRENT_PROTECTION_TIME, 1).eeprom().unit("10ms").range(0, 254);
+    pub const SPEED_I_COEFFICIENT: Register = Register::new("SPEED_I_COEFFICIENT", SMS_STS_SPEED_I_COEFFICIENT, 1).eeprom().range(0, 254);
+
+    // -------SRAM(读写)--------
+    // 1 使能，0 关闭，128 将当前位置校正为中位
+    pub const TORQUE_ENABLE: Register = Register::new("TORQUE_ENABLE", SMS_STS_TORQUE_ENABLE, 1).range(0, 128);
+    pub const ACC: Register = Register::new("ACC", SMS_STS_ACC, 1).unit("100step/s²").range(0, 254);
+    pub const GOAL_POSITION: Register = Register::new("GOAL_POSITION", SMS_STS_GOAL_POSITION_L, 2).signed(15).unit("step");
+    pub const GOAL_TIME: Register = Register::new("GOAL_TIME", SMS_STS_GOAL_TIME_L, 2).unit("ms");
+    pub const GOAL_SPEED: Register = Register::new("GOAL_SPEED", SMS_STS_GOAL_SPEED_L, 2).signed(15).unit("step/s");
+    pub const TORQUE_LIMIT: Register = Register::new("TORQUE_LIMIT", SMS_STS_TORQUE_LIMIT_L, 2).unit("0.1%").range(0, 1000);
+    pub const LOCK: Register = Register::new("LOCK", SMS_STS_LOCK, 1).range(0, 1);
+
+    // -------SRAM(只读)--------
+    pub const PRESENT_POSITION: Register = Register::new("PRESENT_POSITION", SMS_STS_PRESENT_POSITION_L, 2).signed(15).read_only().unit("step");
+    pub const PRESENT_SPEED: Register = Register::new("PRESENT_SPEED", SMS_STS_PRESENT_SPEED_L, 2).signed(15).read_only().unit("step/s");
+    pub const PRESENT_LOAD: Register = Register::new("PRESENT_LOAD", SMS_STS_PRESENT_LOAD_L, 2).signed(10).read_only().unit("0.1%");
+    pub const PRESENT_VOLTAGE: Register = Register::new("PRESENT_VOLTAGE", SMS_STS_PRESENT_VOLTAGE, 1).read_only().unit("0.1V");
+    pub const PRESENT_TEMPERATURE: Register = Register::new("PRESENT_TEMPERATURE", SMS_STS_PRESENT_TEMPERATURE, 1).read_only().unit("°C");
+    pub const STATUS: Register = Register::new("STATUS", SMS_STS_STATUS, 1).read_only();
+    pub const MOVING: Register = Register::new("MOVING", SMS_STS_MOVING, 1).read_only();
+    pub const PRESENT_CURRENT: Register = Register::new("PRESENT_CURRENT", SMS_STS_PRESENT_CURRENT_L, 2).signed(15).read_only();
+
+    // 所有寄存器，按地址排列
+    pub const TABLE: &[Register] = &[
+        FIRMWARE_MAJOR, FIRMWARE_MINOR, MODEL,
+        ID, BAUD_RATE, RETURN_DELAY_TIME, STATUS_RETURN_LEVEL, MIN_ANGLE_LIMIT, MAX_ANGLE_LIMIT,
+        MAX_TEMPERATURE_LIMIT, MAX_INPUT_VOLTAGE, MIN_INPUT_VOLTAGE, MAX_TORQUE, PHASE,
+        UNLOADING_CONDITION, LED_ALARM_CONDITION, P_COEFFICIENT, D_COEFFICIENT, I_COEFFICIENT,
+        MIN_STARTUP_FORCE, CW_DEAD, CCW_DEAD, PROTECTION_CURRENT, ANGULAR_RESOLUTION, OFS, MODE,
+        PROTECTIVE_TORQUE, PROTECTION_TIME, OVERLOAD_TORQUE, SPEED_P_COEFFICIENT,
+        OVER_CURRENT_PROTECTION_TIME, SPEED_I_COEFFICIENT,
+        TORQUE_ENABLE, ACC, GOAL_POSITION, GOAL_TIME, GOAL_SPEED, TORQUE_LIMIT, LOCK,
+        PRESENT_POSITION, PRESENT_SPEED, PRESENT_LOAD, PRESENT_VOLTAGE, PRESENT_TEMPERATURE, STATUS, MOVING,
+        PRESENT_CURRENT,
+    ];
+}
+
+// 运行模式（MODE 寄存器）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OperatingMode {
+    // 位置伺服
+    Position = 0,
+    // 恒速（轮式）
+    Wheel = 1,
+    // PWM 开环调速
+    Pwm = 2,
+    // 步进
+    Step = 3,
+}
+
+impl TryFrom<u8> for OperatingMode {
+    type Error = u8;
+
+    fn try_from(value: u8) -> std::result::Result<Self, Self::Error> {
+        match value {
+            0 => Ok(OperatingMode::Position),
+            1 => Ok(OperatingMode::Wheel),
+            2 => Ok(OperatingMode::Pwm),
+            3 => Ok(OperatingMode::Step),
+            _ => Err(value),
+        }
+    }
+}
+
+impl OperatingMode {
+    // 解码 MODE 寄存器的值
+    pub(crate) fn decode(value: u8) -> Result<Self> {
+        Self::try_from(value).map_err(|mode| FtServoError::InvalidParameter(format!("unknown operating mode {}", mode)))
+    }
+}
+
+// ACC起始的位置控制数据长度：ACC(1) + 位置(2) + 时间(2) + 速度(2)
+pub(crate) const SMS_STS_POS_EX_LEN: u32 = 7;
+
+// 位置控制的同步写入参数：ID + ACC/位置/时间/速度（小端），位置按 GOAL_POSITION 的符号位编码
+pub(crate) fn pos_ex_param(scs_id: u32, position: i32, time: u32, speed: u32) -> Result<[u8; 1 + SMS_STS_POS_EX_LEN as usize]> {
+    let end = Endian::SmallEndian;
+    let pos = regs::GOAL_POSITION.encode(&end, position)?;
+    let [time_l, time_h] = end.split_word(time as u16);
+    let [speed_l, speed_h] = end.split_word(speed as u16);
+    Ok([scs_id as u8, 0, pos[0], pos[1], time_l, time_h, speed_l, speed_h])
+}
+
+// 位置环 PID 系数的写入数据，P/D/I 三个寄存器地址连续
+pub(crate) fn pid_param(p: u8, d: u8, i: u8) -> Result<[u8; 3]> {
+    regs::P_COEFFICIENT.check_write(p as i32)?;
+    regs::D_COEFFICIENT.check_write(d as i32)?;
+    regs::I_COEFFICIENT.check_write(i as i32)?;
+    Ok([p, d, i])
+}
+
+// 输入电压范围的写入数据，最高电压的地址在前
+pub(crate) fn voltage_limits_param(min: u8, max: u8) -> Result<[u8; 2]> {
+    regs::MIN_INPUT_VOLTAGE.check_write(min as i32)?;
+    regs::MAX_INPUT_VOLTAGE.check_write(max as i32)?;
+    if min > max {
+        return Err(FtServoError::InvalidParameter(format!(
+            "minimum voltage {} is above maximum {}",
+            min, max
+        )));
+    }
+    Ok([max, min])
+}
+
+pub struct SmsSts<T: Transport = PortHandler> {
+    ph: ProtocolPacketHandler<T>,
+    // 已检测的舵机型号
+    models: HashMap<u8, ServoModel>,
 }
 
-impl SmsSts {
-    pub fn new(port_handler: PortHandler) -> Self {
-        let ph = ProtocolPacketHandler::new(port_handler, Endian::SmallEndian);
-        let group_sync_write = GroupSyncWrite::new(
-            ProtocolPacketHandler::new(
-                PortHandler::new(&ph.port_handler.get_port_name()),
-                Endian::SmallEndian,
-            ),
-            SMS_STS_ACC as u32,
-            7,
-        );
-        
+impl<T: Transport> SmsSts<T> {
+    pub fn new(port_handler: T) -> Self {
         Self {
-            ph,
-            group_sync_write,
+            ph: ProtocolPacketHandler::new(port_handler, Endian::SmallEndian),
+            models: HashMap::new(),
         }
     }
 
     // 获取端口处理器的可变引用
-    pub fn get_port_handler_mut(&mut self) -> &mut PortHandler {
+    pub fn get_port_handler_mut(&mut self) -> &mut T {
         &mut self.ph.port_handler
     }
 
     // 获取端口处理器的不可变引用
-    pub fn get_port_handler(&self) -> &PortHandler {
+    pub fn get_port_handler(&self) -> &T {
         &self.ph.port_handler
     }
 
-    // 写入位置扩展（包含时间和速度）
-    pub fn write_pos_ex(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> COMM {
-        let mut data = vec![0u32; 7];
-        data[0] = 0; // ACC
-        data[1] = self.ph.scs_lobyte(position) as u32;
-        data[2] = self.ph.scs_hibyte(position) as u32;
-        data[3] = self.ph.scs_lobyte(time as i32) as u32;
-        data[4] = self.ph.scs_hibyte(time as i32) as u32;
-        data[5] = self.ph.scs_lobyte(speed as i32) as u32;
-        data[6] = self.ph.scs_hibyte(speed as i32) as u32;
-        
-        self.group_sync_write.clear_param();
-        match self.group_sync_write.add_param(scs_id, data) {
-            Ok(_) => self.group_sync_write.tx_packet(),
-            Err(_) => COMM::TxError,
+    // 最近一次应答中的舵机状态
+    pub fn last_status(&self) -> ServoStatus {
+        self.ph.last_status()
+    }
+
+    // 按控制表描述读取寄存器，如 read_reg(1, regs::PRESENT_POSITION)
+    pub fn read_reg(&mut self, scs_id: u32, reg: Register) -> Result<i32> {
+        self.ph.read_reg(scs_id, reg)
+    }
+
+    // 按控制表描述写入寄存器，检查读写权限和取值范围
+    pub fn write_reg(&mut self, scs_id: u32, reg: Register, value: i32) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, reg, value)
+    }
+
+    // 写入是否等待舵机应答，关闭后写入方法返回空状态
+    pub fn set_write_ack(&mut self, enabled: bool) {
+        self.ph.set_write_ack(enabled);
+    }
+
+    // 舵机报告故障时是否返回 Hardware 错误
+    pub fn set_strict_status(&mut self, enabled: bool) {
+        self.ph.set_strict_status(enabled);
+    }
+
+    // 读取型号号并缓存该舵机的型号，未知型号按 SMS/STS 系列默认值处理
+    pub fn detect_model(&mut self, scs_id: u32) -> Result<ServoModel> {
+        let number = self.read_model(scs_id)?;
+        let model = ServoModel::from_number_or_generic(number, Family::SmsSts);
+        self.models.insert(scs_id as u8, model);
+        Ok(model)
+    }
+
+    // 已检测或指定的型号
+    pub fn model(&self, scs_id: u32) -> Option<ServoModel> {
+        self.models.get(&(scs_id as u8)).copied()
+    }
+
+    // 指定舵机型号，不读取舵机
+    pub fn set_model(&mut self, scs_id: u32, model: ServoModel) {
+        self.models.insert(scs_id as u8, model);
+    }
+
+    // 读取 EEPROM 中所有可写寄存器
+    #[cfg(feature = "config")]
+    pub fn backup_config(&mut self, scs_id: u32) -> Result<ServoConfig> {
+        config::backup(&mut self.ph, Family::SmsSts, scs_id)
+    }
+
+    // 依次备份多个舵机
+    #[cfg(feature = "config")]
+    pub fn backup_configs(&mut self, scs_ids: &[u32]) -> Result<BusConfig> {
+        config::backup_all(&mut self.ph, Family::SmsSts, scs_ids)
+    }
+
+    // 将配置写入舵机，自动解锁和锁定 EEPROM；配置中的ID不同时最后修改ID
+    // 波特率不写入，与舵机当前值不同时作为差异返回，需要用 change_baud 修改
+    #[cfg(feature = "config")]
+    pub fn restore_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        let skipped = config::restore(&mut self.ph, Family::SmsSts, scs_id, config)?;
+        if let Some(new_id) = config.id() {
+            self.move_model(scs_id, new_id);
         }
+        Ok(skipped)
     }
 
-    // 读取当前位置
-    pub fn read_pos(&mut self, scs_id: u32) -> Result<i32, COMM> {
-        let (data, result) = self.ph.read_2byte_tx_rx(scs_id, SMS_STS_PRESENT_POSITION_L as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 7 { // 包头(2) + ID(1) + 长度(1) + 错误(1) + 数据(2)
-                    let pos = self.ph.scs_makeword(data[5] as i32, data[6] as i32);
-                    Ok(self.ph.scs_tohost(pos, 15))
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
+    // 按配置中记录的ID恢复每个舵机，返回各舵机没有写入的差异
+    #[cfg(feature = "config")]
+    pub fn restore_configs(&mut self, config: &BusConfig) -> Result<Vec<(u32, Difference)>> {
+        config::restore_all(&mut self.ph, Family::SmsSts, config)
+    }
+
+    // 读取舵机当前的 EEPROM，返回与配置不一致的寄存器
+    #[cfg(feature = "config")]
+    pub fn verify_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        config::verify(&mut self.ph, Family::SmsSts, scs_id, config)
+    }
+
+    // 只写入与配置不一致的寄存器（ID 和波特率除外），返回写入的差异
+    #[cfg(feature = "config")]
+    pub fn apply_config(&mut self, scs_id: u32, config: &ServoConfig) -> Result<Vec<Difference>> {
+        config::apply(&mut self.ph, Family::SmsSts, scs_id, config)
+    }
+
+    // 修改ID后，已检测的型号随ID迁移
+    fn move_model(&mut self, old_id: u32, new_id: u32) {
+        if let Some(model) = self.models.remove(&(old_id as u8)) {
+            self.models.insert(new_id as u8, model);
         }
     }
 
-    // 读取当前速度
-    pub fn read_speed(&mut self, scs_id: u32) -> Result<i32, COMM> {
-        let (data, result) = self.ph.read_2byte_tx_rx(scs_id, SMS_STS_PRESENT_SPEED_L as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 7 {
-                    let speed = self.ph.scs_makeword(data[5] as i32, data[6] as i32);
-                    Ok(self.ph.scs_tohost(speed, 15))
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
+    // 缓存的型号，该舵机第一次角度读写时读取型号号并缓存
+    fn model_or_detect(&mut self, scs_id: u32) -> Result<ServoModel> {
+        match self.model(scs_id) {
+            Some(model) => Ok(model),
+            None => self.detect_model(scs_id),
         }
     }
 
-    // 同时读取位置和速度
-    pub fn read_pos_speed(&mut self, scs_id: u32) -> Result<(i32, i32), COMM> {
-        let pos = self.read_pos(scs_id)?;
-        let speed = self.read_speed(scs_id)?;
-        Ok((pos, speed))
+    // 读取当前位置（度），按型号换算
+    pub fn read_pos_degrees(&mut self, scs_id: u32) -> Result<f64> {
+        let model = self.model_or_detect(scs_id)?;
+        Ok(model.steps_to_degrees(self.read_pos(scs_id)?))
+    }
+
+    // 读取当前速度（度/秒），按型号换算
+    pub fn read_speed_degrees(&mut self, scs_id: u32) -> Result<f64> {
+        let model = self.model_or_detect(scs_id)?;
+        Ok(model.steps_to_degrees(self.read_speed(scs_id)?))
+    }
+
+    // 写入目标位置（度）和速度（度/秒），位置超出型号行程或速度超过型号最大速度时返回错误
+    pub fn write_pos_ex_degrees(&mut self, scs_id: u32, degrees: f64, time: u32, speed: f64) -> Result<()> {
+        let (position, speed) = self.model_or_detect(scs_id)?.goal_steps(degrees, speed)?;
+        self.write_pos_ex(scs_id, position, time, speed)
+    }
+
+    // 组装同步写入的参数：ID + ACC/位置/时间/速度
+    fn push_pos_ex_param(&self, param: &mut Vec<u8>, scs_id: u32, position: i32, time: u32, speed: u32) -> Result<()> {
+        param.extend_from_slice(&pos_ex_param(scs_id, position, time, speed)?);
+        Ok(())
+    }
+
+    // 写入位置扩展（包含时间和速度）
+    pub fn write_pos_ex(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> Result<()> {
+        let mut param = Vec::with_capacity(1 + SMS_STS_POS_EX_LEN as usize);
+        self.push_pos_ex_param(&mut param, scs_id, position, time, speed)?;
+
+        self.ph.sync_write_tx_only(SMS_STS_ACC as u32, SMS_STS_POS_EX_LEN, &param)
+    }
+
+    // 读取当前位置
+    pub fn read_pos(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, SMS_STS_PRESENT_POSITION_L as u32)?;
+        Ok(self.ph.scs_tohost(data as i32, 15))
+    }
+
+    // 读取当前速度
+    pub fn read_speed(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, SMS_STS_PRESENT_SPEED_L as u32)?;
+        Ok(self.ph.scs_tohost(data as i32, 15))
+    }
+
+    // 读取块数据中 offset 处的双字节有符号值
+    fn signed_word(&self, data: &[u8], offset: usize, sign_bit: i32) -> i32 {
+        signed_word(&self.ph.scs_getend(), data, offset, sign_bit)
+    }
+
+    // 同时读取位置和速度（一次读取连续的4个字节）
+    pub fn read_pos_speed(&mut self, scs_id: u32) -> Result<(i32, i32)> {
+        let data = self.ph.read_tx_rx(scs_id, SMS_STS_PRESENT_POSITION_L as u32, 4)?;
+        Ok((self.signed_word(&data, 0, 15), self.signed_word(&data, 2, 15)))
+    }
+
+    // 同时读取位置、速度和负载（一次读取连续的6个字节）
+    pub fn read_pos_speed_load(&mut self, scs_id: u32) -> Result<(i32, i32, i32)> {
+        let data = self.ph.read_tx_rx(scs_id, SMS_STS_PRESENT_POSITION_L as u32, 6)?;
+        Ok((
+            self.signed_word(&data, 0, 15),
+            self.signed_word(&data, 2, 15),
+            self.signed_word(&data, 4, 10),
+        ))
+    }
+
+    // 同时读取电压和温度
+    pub fn read_voltage_temperature(&mut self, scs_id: u32) -> Result<(u8, u8)> {
+        let data = self.ph.read_tx_rx(scs_id, SMS_STS_PRESENT_VOLTAGE as u32, 2)?;
+        Ok((data[0], data[1]))
     }
 
     // 读取运动状态
-    pub fn read_moving(&mut self, scs_id: u32) -> Result<bool, COMM> {
-        let (data, result) = self.ph.read_1byte_tx_rx(scs_id, SMS_STS_MOVING as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 6 {
-                    Ok(data[5] != 0)
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
-        }
+    pub fn read_moving(&mut self, scs_id: u32) -> Result<bool> {
+        let data = self.ph.read_1byte_tx_rx(scs_id, SMS_STS_MOVING as u32)?;
+        Ok(data != 0)
+    }
+
+    // 读取型号号
+    pub fn read_model(&mut self, scs_id: u32) -> Result<u16> {
+        self.ph.read_2byte_tx_rx(scs_id, SMS_STS_MODEL_L as u32)
+    }
+
+    // 读取固件版本（主版本, 次版本）
+    pub fn read_firmware_version(&mut self, scs_id: u32) -> Result<(u8, u8)> {
+        let data = self.ph.read_tx_rx(scs_id, SMS_STS_FIRMWARE_MAJOR as u32, 2)?;
+        Ok((data[0], data[1]))
+    }
+
+    // 读取运行模式
+    pub fn read_mode(&mut self, scs_id: u32) -> Result<OperatingMode> {
+        let mode = self.ph.read_1byte_tx_rx(scs_id, SMS_STS_MODE as u32)?;
+        OperatingMode::decode(mode)
+    }
+
+    // 写入运行模式（EEPROM，需先解锁）
+    pub fn write_mode(&mut self, scs_id: u32, mode: OperatingMode) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, regs::MODE, mode as i32)
+    }
+
+    // 读取位置环 PID 系数 (P, D, I)
+    pub fn read_pid(&mut self, scs_id: u32) -> Result<(u8, u8, u8)> {
+        let data = self.ph.read_tx_rx(scs_id, SMS_STS_P_COEFFICIENT as u32, 3)?;
+        Ok((data[0], data[1], data[2]))
+    }
+
+    // 写入位置环 PID 系数 (P, D, I)，三个寄存器一次写入（EEPROM，需先解锁）
+    pub fn write_pid(&mut self, scs_id: u32, p: u8, d: u8, i: u8) -> Result<ServoStatus> {
+        let param = pid_param(p, d, i)?;
+        self.ph.write_tx_rx(scs_id, SMS_STS_P_COEFFICIENT as u32, &param)
+    }
+
+    // 读取运行时扭矩限制（SRAM，0.1%）
+    pub fn read_torque_limit(&mut self, scs_id: u32) -> Result<u16> {
+        self.ph.read_2byte_tx_rx(scs_id, SMS_STS_TORQUE_LIMIT_L as u32)
+    }
+
+    // 写入运行时扭矩限制（SRAM，0~1000，0.1%），上电后恢复为最大扭矩
+    pub fn write_torque_limit(&mut self, scs_id: u32, limit: u16) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, regs::TORQUE_LIMIT, limit as i32)
+    }
+
+    // 读取最大扭矩（EEPROM，0.1%）
+    pub fn read_max_torque(&mut self, scs_id: u32) -> Result<u16> {
+        self.ph.read_2byte_tx_rx(scs_id, SMS_STS_MAX_TORQUE_L as u32)
+    }
+
+    // 写入最大扭矩（EEPROM，0~1000，0.1%，需先解锁）
+    pub fn write_max_torque(&mut self, scs_id: u32, torque: u16) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, regs::MAX_TORQUE, torque as i32)
+    }
+
+    // 读取保护电流（6.5mA）
+    pub fn read_protection_current(&mut self, scs_id: u32) -> Result<u16> {
+        self.ph.read_2byte_tx_rx(scs_id, SMS_STS_PROTECTION_CURRENT_L as u32)
+    }
+
+    // 写入保护电流（EEPROM，0~511，6.5mA，需先解锁）
+    pub fn write_protection_current(&mut self, scs_id: u32, current: u16) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, regs::PROTECTION_CURRENT, current as i32)
+    }
+
+    // 读取最高温度限制（°C）
+    pub fn read_max_temperature(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, SMS_STS_MAX_TEMPERATURE_LIMIT as u32)
+    }
+
+    // 写入最高温度限制（EEPROM，0~100°C，需先解锁）
+    pub fn write_max_temperature(&mut self, scs_id: u32, temperature: u8) -> Result<ServoStatus> {
+        self.ph.write_reg(scs_id, regs::MAX_TEMPERATURE_LIMIT, temperature as i32)
+    }
+
+    // 读取输入电压范围（最低, 最高），单位 0.1V
+    pub fn read_voltage_limits(&mut self, scs_id: u32) -> Result<(u8, u8)> {
+        let data = self.ph.read_tx_rx(scs_id, SMS_STS_MAX_INPUT_VOLTAGE as u32, 2)?;
+        Ok((data[1], data[0]))
+    }
+
+    // 写入输入电压范围（最低, 最高），单位 0.1V（EEPROM，需先解锁）
+    pub fn write_voltage_limits(&mut self, scs_id: u32, min: u8, max: u8) -> Result<ServoStatus> {
+        let param = voltage_limits_param(min, max)?;
+        self.ph.write_tx_rx(scs_id, SMS_STS_MAX_INPUT_VOLTAGE as u32, &param)
+    }
+
+    // 读取卸载条件：出现这些故障时舵机卸载扭矩
+    pub fn read_unloading_condition(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        let data = self.ph.read_1byte_tx_rx(scs_id, SMS_STS_UNLOADING_CONDITION as u32)?;
+        Ok(ServoStatus::from_bits_retain(data))
+    }
+
+    // 写入卸载条件（EEPROM，需先解锁）
+    pub fn write_unloading_condition(&mut self, scs_id: u32, condition: ServoStatus) -> Result<ServoStatus> {
+        self.ph.write_1byte_tx_rx(scs_id, SMS_STS_UNLOADING_CONDITION as u32, condition.bits())
+    }
+
+    // 读取 LED 报警条件：出现这些故障时 LED 闪烁
+    pub fn read_led_alarm_condition(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        let data = self.ph.read_1byte_tx_rx(scs_id, SMS_STS_LED_ALARM_CONDITION as u32)?;
+        Ok(ServoStatus::from_bits_retain(data))
+    }
+
+    // 写入 LED 报警条件（EEPROM，需先解锁）
+    pub fn write_led_alarm_condition(&mut self, scs_id: u32, condition: ServoStatus) -> Result<ServoStatus> {
+        self.ph.write_1byte_tx_rx(scs_id, SMS_STS_LED_ALARM_CONDITION as u32, condition.bits())
+    }
+
+    // 读取运行相位
+    pub fn read_phase(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, SMS_STS_PHASE as u32)
+    }
+
+    // 读取舵机当前的故障状态（STATUS 寄存器）
+    pub fn read_status(&mut self, scs_id: u32) -> Result<ServoStatus> {
+        let data = self.ph.read_1byte_tx_rx(scs_id, SMS_STS_STATUS as u32)?;
+        Ok(ServoStatus::from_bits_retain(data))
     }
 
     // 同步写入多个舵机位置
-    pub fn sync_write_pos_ex(&mut self, scs_ids: Vec<u32>, positions: Vec<i32>, times: Vec<u32>, speeds: Vec<u32>) -> COMM {
-        if scs_ids.len() != positions.len() || positions.len() != times.len() || times.len() != speeds.len() {
-            return COMM::TxError;
-        }
+    pub fn sync_write_pos_ex(&mut self, scs_ids: Vec<u32>, positions: Vec<i32>, times: Vec<u32>, speeds: Vec<u32>) -> Result<()> {
+        check_sync_lengths(scs_ids.len(), positions.len(), times.len(), speeds.len())?;
 
-        self.group_sync_write.clear_param();
-        
+        let mut param = Vec::with_capacity(scs_ids.len() * (1 + SMS_STS_POS_EX_LEN as usize));
         for i in 0..scs_ids.len() {
-            let mut data = vec![0u32; 7];
-            data[0] = 0; // ACC
-            data[1] = self.ph.scs_lobyte(positions[i]) as u32;
-            data[2] = self.ph.scs_hibyte(positions[i]) as u32;
-            data[3] = self.ph.scs_lobyte(times[i] as i32) as u32;
-            data[4] = self.ph.scs_hibyte(times[i] as i32) as u32;
-            data[5] = self.ph.scs_lobyte(speeds[i] as i32) as u32;
-            data[6] = self.ph.scs_hibyte(speeds[i] as i32) as u32;
-            
-            if let Err(_) = self.group_sync_write.add_param(scs_ids[i], data) {
-                return COMM::TxError;
-            }
+            self.push_pos_ex_param(&mut param, scs_ids[i], positions[i], times[i], speeds[i])?;
         }
-        
-        self.group_sync_write.tx_packet()
+
+        self.ph.sync_write_tx_only(SMS_STS_ACC as u32, SMS_STS_POS_EX_LEN, &param)
     }
 
-    // 寄存器写入位置扩展
-    pub fn reg_write_pos_ex(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> COMM {
-        // 先写入位置
-        let pos_result = self.ph.write_2byte_tx_rx(scs_id, SMS_STS_GOAL_POSITION_L as u32, position as u16);
-        if pos_result != COMM::Success {
-            return pos_result;
-        }
-        
-        // 写入时间
-        let time_result = self.ph.write_2byte_tx_rx(scs_id, SMS_STS_GOAL_TIME_L as u32, time as u16);
-        if time_result != COMM::Success {
-            return time_result;
-        }
-        
-        // 写入速度
-        self.ph.write_2byte_tx_rx(scs_id, SMS_STS_GOAL_SPEED_L as u32, speed as u16)
+    // 寄存器写入位置扩展：一条 RegWrite 写入 ACC/位置/时间/速度，舵机在 reg_action 后才开始运动
+    pub fn reg_write_pos_ex(&mut self, scs_id: u32, position: i32, time: u32, speed: u32) -> Result<ServoStatus> {
+        let param = pos_ex_param(scs_id, position, time, speed)?;
+        self.ph.reg_write_tx_rx(scs_id, SMS_STS_ACC as u32, &param[1..])
     }
 
-    // 寄存器动作
-    pub fn reg_action(&mut self, scs_id: u32) -> COMM {
+    // 寄存器动作，scs_id 为 BROADCAST_ID 时所有舵机同时开始
+    pub fn reg_action(&mut self, scs_id: u32) -> Result<ServoStatus> {
         self.ph.action(scs_id)
     }
 
     // 轮式模式
-    pub fn wheel_mode(&mut self, scs_id: u32, mode: u8) -> COMM {
+    pub fn wheel_mode(&mut self, scs_id: u32, mode: u8) -> Result<ServoStatus> {
         self.ph.write_1byte_tx_rx(scs_id, SMS_STS_MODE as u32, mode)
     }
 
     // 写入扭矩使能
-    pub fn write_torque_enable(&mut self, scs_id: u32, enable: bool) -> COMM {
+    pub fn write_torque_enable(&mut self, scs_id: u32, enable: bool) -> Result<ServoStatus> {
         self.ph.write_1byte_tx_rx(scs_id, SMS_STS_TORQUE_ENABLE as u32, if enable { 1 } else { 0 })
     }
 
     // 锁定EPROM
-    pub fn lock_eprom(&mut self, scs_id: u32) -> COMM {
+    pub fn lock_eprom(&mut self, scs_id: u32) -> Result<ServoStatus> {
         self.ph.write_1byte_tx_rx(scs_id, SMS_STS_LOCK as u32, 1)
     }
 
     // 解锁EPROM
-    pub fn unlock_eprom(&mut self, scs_id: u32) -> COMM {
+    pub fn unlock_eprom(&mut self, scs_id: u32) -> Result<ServoStatus> {
         self.ph.write_1byte_tx_rx(scs_id, SMS_STS_LOCK as u32, 0)
     }
 
-    // Ping舵机
-    pub fn ping(&mut self, scs_id: u32) -> COMM {
+    // Ping舵机，等待应答
+    pub fn ping(&mut self, scs_id: u32) -> Result<PingInfo> {
         self.ph.ping(scs_id)
     }
 
+    // Ping舵机并读取型号号
+    pub fn ping_with_model(&mut self, scs_id: u32) -> Result<PingInfo> {
+        self.ph.ping_with_model(scs_id)
+    }
+
     // 读取电压
-    pub fn read_voltage(&mut self, scs_id: u32) -> Result<u8, COMM> {
-        let (data, result) = self.ph.read_1byte_tx_rx(scs_id, SMS_STS_PRESENT_VOLTAGE as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 6 {
-                    Ok(data[5] as u8)
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
-        }
+    pub fn read_voltage(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, SMS_STS_PRESENT_VOLTAGE as u32)
     }
 
     // 读取温度
-    pub fn read_temperature(&mut self, scs_id: u32) -> Result<u8, COMM> {
-        let (data, result) = self.ph.read_1byte_tx_rx(scs_id, SMS_STS_PRESENT_TEMPERATURE as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 6 {
-                    Ok(data[5] as u8)
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
-        }
+    pub fn read_temperature(&mut self, scs_id: u32) -> Result<u8> {
+        self.ph.read_1byte_tx_rx(scs_id, SMS_STS_PRESENT_TEMPERATURE as u32)
     }
 
     // 读取负载
-    pub fn read_load(&mut self, scs_id: u32) -> Result<i32, COMM> {
-        let (data, result) = self.ph.read_2byte_tx_rx(scs_id, SMS_STS_PRESENT_LOAD_L as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 7 {
-                    let load = self.ph.scs_makeword(data[5] as i32, data[6] as i32);
-                    Ok(self.ph.scs_tohost(load, 10))
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
-        }
+    pub fn read_load(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, SMS_STS_PRESENT_LOAD_L as u32)?;
+        Ok(self.ph.scs_tohost(data as i32, 10))
     }
 
     // 读取电流
-    pub fn read_current(&mut self, scs_id: u32) -> Result<i32, COMM> {
-        let (data, result) = self.ph.read_2byte_tx_rx(scs_id, SMS_STS_PRESENT_CURRENT_L as u32);
-        match result {
-            COMM::Success => {
-                if data.len() >= 7 {
-                    let current = self.ph.scs_makeword(data[5] as i32, data[6] as i32);
-                    Ok(self.ph.scs_tohost(current, 15))
-                } else {
-                    Err(COMM::RxCorrupt)
-                }
-            }
-            _ => Err(result),
-        }
+    pub fn read_current(&mut self, scs_id: u32) -> Result<i32> {
+        let data = self.ph.read_2byte_tx_rx(scs_id, SMS_STS_PRESENT_CURRENT_L as u32)?;
+        Ok(self.ph.scs_tohost(data as i32, 15))
+    }
+
+    // 设置ID，只写入寄存器（需先解锁 EEPROM），完整流程见 change_id
+    // 舵机写入后以新ID应答，只发送不等待
+    pub fn set_id(&mut self, old_id: u32, new_id: u32) -> Result<()> {
+        self.ph.write_1byte_tx_only(old_id, SMS_STS_ID as u32, new_id as u8)?;
+        self.move_model(old_id, new_id);
+        Ok(())
+    }
+
+    // 设置波特率寄存器（SMS_STS_1M 等），只写入寄存器，完整流程见 change_baud
+    // 舵机写入后以新波特率应答，只发送不等待
+    pub fn set_baudrate(&mut self, scs_id: u32, baudrate: u8) -> Result<()> {
+        self.ph.write_1byte_tx_only(scs_id, SMS_STS_BAUD_RATE as u32, baudrate)
+    }
+
+    // 安全修改ID：确认目标ID空闲，自动解锁和锁定 EEPROM，最后在新ID上 Ping 确认
+    pub fn change_id(&mut self, old_id: u32, new_id: u32) -> Result<()> {
+        setup::change_id(&mut self.ph, Family::SmsSts, old_id, new_id)?;
+        self.move_model(old_id, new_id);
+        Ok(())
+    }
+
+    // 修改波特率（如 115200）：舵机切换后本地端口随之切换，在新波特率下锁定 EEPROM 并确认
+    // 总线上的其他舵机仍使用原波特率，需要一起修改时使用 change_baud_all
+    pub fn change_baud(&mut self, scs_id: u32, baudrate: u32) -> Result<()> {
+        setup::change_baud(&mut self.ph, Family::SmsSts, &[scs_id], baudrate)
+    }
+
+    // 同时修改多个舵机的波特率，端口只切换一次
+    pub fn change_baud_all(&mut self, scs_ids: &[u32], baudrate: u32) -> Result<()> {
+        setup::change_baud(&mut self.ph, Family::SmsSts, scs_ids, baudrate)
+    }
+}
+
+// 可克隆的总线（如 SharedBus）上，group 读写与控制器共用同一个端口
+impl<T: Transport + Clone> SmsSts<T> {
+    // 在同一条总线上创建同步写入
+    pub fn group_sync_write(&self, start_address: u32, data_length: u32) -> GroupSyncWrite<T> {
+        let ph = ProtocolPacketHandler::new(self.ph.port_handler.clone(), self.ph.scs_getend());
+        GroupSyncWrite::new(ph, start_address, data_length)
+    }
+
+    // 在同一条总线上创建同步读取
+    pub fn group_sync_read(&self, start_address: u32, data_length: u32) -> GroupSyncRead<T> {
+        let ph = ProtocolPacketHandler::new(self.ph.port_handler.clone(), self.ph.scs_getend());
+        GroupSyncRead::new(ph, start_address, data_length)
+    }
+
+    // 在同一条总线上创建批量读取
+    pub fn group_bulk_read(&self) -> GroupBulkRead<T> {
+        let ph = ProtocolPacketHandler::new(self.ph.port_handler.clone(), self.ph.scs_getend());
+        GroupBulkRead::new(ph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        scservo_def::{baud_rate_code, INST},
+        sim::{sim_bus, SimFamily},
+    };
+
+    #[test]
+    fn reads_return_data_when_servo_reports_fault() {
+        let mut bus = sim_bus(SimFamily::SmsSts, &[1]);
+        let fault = ServoStatus::OVERHEAT | ServoStatus::OVERLOAD;
+        bus.chain_mut().servo_mut(1).unwrap().set_status(fault.bits());
+        let mut sms_sts = SmsSts::new(bus);
+
+        assert_eq!(sms_sts.read_temperature(1).unwrap(), 30);
+        assert_eq!(sms_sts.last_status(), fault);
+    }
+
+    #[test]
+    fn strict_status_turns_fault_into_hardware_error() {
+        let mut bus = sim_bus(SimFamily::SmsSts, &[1]);
+        bus.chain_mut().servo_mut(1).unwrap().set_status(ServoStatus::OVERLOAD.bits());
+        let mut sms_sts = SmsSts::new(bus);
+        sms_sts.set_strict_status(true);
+
+        let err = sms_sts.read_temperature(1).unwrap_err();
+        assert!(matches!(
+            err,
+            FtServoError::Hardware { id: 1, instruction: INST::Read, status } if status == ServoStatus::OVERLOAD
+        ));
+        assert!(matches!(sms_sts.ping(1), Ok(info) if info.status == ServoStatus::OVERLOAD));
+
+        sms_sts.set_strict_status(false);
+        assert_eq!(sms_sts.read_temperature(1).unwrap(), 30);
+    }
+
+    #[test]
+    fn negative_goal_position_sets_the_sign_bit() {
+        let mut sms_sts = SmsSts::new(sim_bus(SimFamily::SmsSts, &[1]));
+        sms_sts.write_pos_ex(1, -100, 0, 0).unwrap();
+
+        let servo = sms_sts.get_port_handler().chain().servo(1).unwrap();
+        assert_eq!(servo.read_word(SMS_STS_GOAL_POSITION_L), 0x8000 | 100);
+        assert_eq!(sms_sts.read_reg(1, regs::GOAL_POSITION).unwrap(), -100);
+        assert!(matches!(sms_sts.write_pos_ex(1, -40000, 0, 0), Err(FtServoError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn set_id_does_not_wait_for_the_reply_from_the_new_id() {
+        let mut sms_sts = SmsSts::new(sim_bus(SimFamily::SmsSts, &[1]));
+        sms_sts.detect_model(1).unwrap();
+
+        sms_sts.unlock_eprom(1).unwrap();
+        sms_sts.set_id(1, 5).unwrap();
+        sms_sts.lock_eprom(5).unwrap();
+
+        assert!(sms_sts.model(1).is_none());
+        assert!(sms_sts.model(5).is_some());
+        assert_eq!(sms_sts.ping(5).unwrap().id, 5);
+    }
+
+    #[test]
+    fn change_id_moves_the_servo_and_locks_it() {
+        let mut sms_sts = SmsSts::new(sim_bus(SimFamily::SmsSts, &[1]));
+        sms_sts.change_id(1, 5).unwrap();
+
+        assert!(matches!(sms_sts.ping(1), Err(FtServoError::Timeout { .. })));
+        assert_eq!(sms_sts.ping(5).unwrap().id, 5);
+        assert_eq!(sms_sts.read_reg(5, regs::LOCK).unwrap(), 1);
     }
 
-    // 设置ID
-    pub fn set_id(&mut self, old_id: u32, new_id: u32) -> COMM {
-        self.ph.write_1byte_tx_rx(old_id, SMS_STS_ID as u32, new_id as u8)
+    #[test]
+    fn change_id_rejects_an_id_in_use() {
+        let mut sms_sts = SmsSts::new(sim_bus(SimFamily::SmsSts, &[1, 2]));
+
+        assert!(matches!(sms_sts.change_id(1, 2), Err(FtServoError::InvalidParameter(_))));
+        assert_eq!(sms_sts.ping(1).unwrap().id, 1);
+        assert_eq!(sms_sts.read_reg(1, regs::LOCK).unwrap(), 1);
     }
 
-    // 设置波特率
-    pub fn set_baudrate(&mut self, scs_id: u32, baudrate: u8) -> COMM {
-        self.ph.write_1byte_tx_rx(scs_id, SMS_STS_BAUD_RATE as u32, baudrate)
+    #[test]
+    fn change_id_rejects_an_id_out_of_range() {
+        let mut sms_sts = SmsSts::new(sim_bus(SimFamily::SmsSts, &[1]));
+
+        assert!(matches!(sms_sts.change_id(1, 253), Err(FtServoError::InvalidParameter(_))));
+        assert_eq!(sms_sts.ping(1).unwrap().id, 1);
+    }
+
+    #[test]
+    fn change_baud_all_switches_the_port_and_locks_every_servo() {
+        let mut sms_sts = SmsSts::new(sim_bus(SimFamily::SmsSts, &[1, 2]));
+        sms_sts.change_baud_all(&[1, 2], 115_200).unwrap();
+
+        assert_eq!(sms_sts.get_port_handler().get_baudrate(), 115_200);
+        let code = baud_rate_code(115_200).unwrap() as i32;
+        for scs_id in [1, 2] {
+            assert_eq!(sms_sts.read_reg(scs_id, regs::BAUD_RATE).unwrap(), code);
+            assert_eq!(sms_sts.read_reg(scs_id, regs::LOCK).unwrap(), 1);
+        }
     }
 }
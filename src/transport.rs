@@ -0,0 +1,143 @@
+use std::{
+    io::{self, ErrorKind},
+    time::{Duration, Instant},
+};
+
+// 字节传输抽象：协议层只依赖此 trait，串口、网络桥接、模拟器等都可实现
+pub trait Transport {
+    // 写入字节，返回实际写入的长度
+    fn write_port(&mut self, packet: &[u8]) -> io::Result<usize>;
+
+    // 在截止时间之前读取数据，读到数据返回长度，到期仍无数据返回 TimedOut
+    fn read_port_until(&mut self, buf: &mut [u8], deadline: Instant) -> io::Result<usize>;
+
+    // 清除输入缓冲区中残留的数据
+    fn clear_port(&mut self) -> io::Result<()>;
+
+    // 输入缓冲区中可读取的字节数
+    fn get_bytes_available(&self) -> io::Result<u32>;
+
+    // 当前波特率
+    fn get_baudrate(&self) -> u32;
+
+    // 切换波特率，不支持时返回 Unsupported
+    fn set_baudrate(&mut self, _baudrate: u32) -> io::Result<()> {
+        Err(io::Error::new(ErrorKind::Unsupported, "changing the baud rate is not supported"))
+    }
+
+    // 每个字节的发送时间（1起始位 + 8数据位 + 1停止位）
+    fn get_tx_time_per_byte(&self) -> Duration {
+        Duration::from_secs_f64(10.0 / self.get_baudrate().max(1) as f64)
+    }
+
+    // 开始一次收发事务（发送指令包到收完状态包），可嵌套
+    // 独占的端口无需处理，共享总线在事务结束前阻止其他句柄访问
+    fn begin_transaction(&mut self) {}
+
+    // 结束收发事务
+    fn end_transaction(&mut self) {}
+
+    // 事务结束时仍未读取的应答交给总线登记，由下一个使用总线的句柄丢弃，返回是否已登记
+    // 独占的端口返回 false，由协议处理器自己记录
+    fn defer_replies(&mut self, _count: usize, _deadline: Instant) -> bool {
+        false
+    }
+
+    // 取出总线上登记的未读取应答：数量和最晚到达时间
+    fn take_deferred_replies(&mut self) -> Option<(usize, Instant)> {
+        None
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn write_port(&mut self, packet: &[u8]) -> io::Result<usize> {
+        (**self).write_port(packet)
+    }
+
+    fn read_port_until(&mut self, buf: &mut [u8], deadline: Instant) -> io::Result<usize> {
+        (**self).read_port_until(buf, deadline)
+    }
+
+    fn clear_port(&mut self) -> io::Result<()> {
+        (**self).clear_port()
+    }
+
+    fn get_bytes_available(&self) -> io::Result<u32> {
+        (**self).get_bytes_available()
+    }
+
+    fn get_baudrate(&self) -> u32 {
+        (**self).get_baudrate()
+    }
+
+    fn set_baudrate(&mut self, baudrate: u32) -> io::Result<()> {
+        (**self).set_baudrate(baudrate)
+    }
+
+    fn get_tx_time_per_byte(&self) -> Duration {
+        (**self).get_tx_time_per_byte()
+    }
+
+    fn begin_transaction(&mut self) {
+        (**self).begin_transaction()
+    }
+
+    fn end_transaction(&mut self) {
+        (**self).end_transaction()
+    }
+
+    fn defer_replies(&mut self, count: usize, deadline: Instant) -> bool {
+        (**self).defer_replies(count, deadline)
+    }
+
+    fn take_deferred_replies(&mut self) -> Option<(usize, Instant)> {
+        (**self).take_deferred_replies()
+    }
+}
+
+// 借用的端口，可以临时交给协议处理器使用（例如扫描时仍由调用者持有端口）
+impl<T: Transport + ?Sized> Transport for &mut T {
+    fn write_port(&mut self, packet: &[u8]) -> io::Result<usize> {
+        (**self).write_port(packet)
+    }
+
+    fn read_port_until(&mut self, buf: &mut [u8], deadline: Instant) -> io::Result<usize> {
+        (**self).read_port_until(buf, deadline)
+    }
+
+    fn clear_port(&mut self) -> io::Result<()> {
+        (**self).clear_port()
+    }
+
+    fn get_bytes_available(&self) -> io::Result<u32> {
+        (**self).get_bytes_available()
+    }
+
+    fn get_baudrate(&self) -> u32 {
+        (**self).get_baudrate()
+    }
+
+    fn set_baudrate(&mut self, baudrate: u32) -> io::Result<()> {
+        (**self).set_baudrate(baudrate)
+    }
+
+    fn get_tx_time_per_byte(&self) -> Duration {
+        (**self).get_tx_time_per_byte()
+    }
+
+    fn begin_transaction(&mut self) {
+        (**self).begin_transaction()
+    }
+
+    fn end_transaction(&mut self) {
+        (**self).end_transaction()
+    }
+
+    fn defer_replies(&mut self, count: usize, deadline: Instant) -> bool {
+        (**self).defer_replies(count, deadline)
+    }
+
+    fn take_deferred_replies(&mut self) -> Option<(usize, Instant)> {
+        (**self).take_deferred_replies()
+    }
+}